@@ -0,0 +1,16 @@
+Texture2D<float4> Texture : register(t0, space2);
+Texture3D<float4> Lut : register(t1, space2);
+SamplerState Sampler : register(s0, space2);
+SamplerState LutSampler : register(s1, space2);
+
+float4 main(float2 TexCoord : TEXCOORD0) : SV_Target0
+{
+    float4 color = Texture.Sample(Sampler, TexCoord);
+
+    // sample between the centers of the first and last texels, so 0 and 1 map exactly
+    float size, height, depth;
+    Lut.GetDimensions(size, height, depth);
+    float3 lutCoord = saturate(color.rgb) * ((size - 1.0f) / size) + 0.5f / size;
+
+    return float4(Lut.Sample(LutSampler, lutCoord).rgb, color.a);
+}
@@ -0,0 +1,25 @@
+cbuffer UBO : register(b0, space3)
+{
+    float2 Resolution : packoffset(c0);
+    float Intensity : packoffset(c0.z);
+    float Time : packoffset(c0.w);
+};
+
+Texture2D<float4> Texture : register(t0, space2);
+SamplerState Sampler : register(s0, space2);
+
+static const float PI = 3.14159265f;
+
+float4 main(float2 TexCoord : TEXCOORD0) : SV_Target0
+{
+    // a slight horizontal split of the color channels, like a misaligned shadow mask
+    float2 offset = float2(1.0f / Resolution.x, 0.0f);
+    float4 color = Texture.Sample(Sampler, TexCoord);
+    color.r = Texture.Sample(Sampler, TexCoord + offset).r;
+    color.b = Texture.Sample(Sampler, TexCoord - offset).b;
+
+    // one dark line every other output row, slowly rolling down the screen
+    float scanline = 0.5f + 0.5f * sin((TexCoord.y * Resolution.y - Time * 8.0f) * PI);
+    color.rgb *= 1.0f - Intensity * scanline;
+    return color;
+}
@@ -1,8 +1,13 @@
+cbuffer UBO : register(b0, space2)
+{
+    float4 FillColor : packoffset(c0);
+};
+
 RWTexture2D<unorm float4> outImage : register(u0, space1);
 
 [numthreads(8, 8, 1)]
 void main(uint3 GlobalInvocationID : SV_DispatchThreadID)
 {
     int2 coord = int2(GlobalInvocationID.xy);
-    outImage[coord] = float4(1.0f, 1.0f, 0.0f, 1.0f);
+    outImage[coord] = FillColor;
 }
@@ -1,6 +1,8 @@
 cbuffer UBO : register(b0, space2)
 {
-    float ubo_time : packoffset(c0);
+    float4 StartColor : packoffset(c0);
+    float4 EndColor : packoffset(c1);
+    float ubo_time : packoffset(c2);
 };
 
 RWTexture2D<unorm float4> OutImage : register(u0, space1);
@@ -22,6 +24,7 @@ void main(uint3 GlobalInvocationID : SV_DispatchThreadID)
     float2 coord = float2(GlobalInvocationID.xy);
     float2 uv = coord / size;
 
-    float3 col = 0.5f.xxx + (cos((ubo_time.xxx + uv.xyx) + float3(0.0f, 2.0f, 4.0f)) * 0.5f);
-    OutImage[int2(coord)] = float4(col, 1.0f);
+    // a diagonal band between the endpoint colors that scrolls with time
+    float t = 0.5f + (cos(ubo_time + ((uv.x + uv.y) * 3.14159274f)) * 0.5f);
+    OutImage[int2(coord)] = lerp(StartColor, EndColor, t.xxxx);
 }
@@ -1,6 +1,13 @@
 Texture2D<float4> InImage : register(t0, space0);
 RWTexture2D<unorm float4> OutImage : register(u0, space1);
 
+cbuffer ToneMapSettings : register(b0, space2)
+{
+    float Exposure : packoffset(c0.x);
+    float WhitePoint : packoffset(c0.y);
+    float PaperWhiteNits : packoffset(c0.z);
+};
+
 float3 NormalizeHDRSceneValue(float3 hdrSceneValue, float paperWhiteNits)
 {
     return (hdrSceneValue * paperWhiteNits) / 10000.0f.xxx;
@@ -24,5 +31,5 @@ void main(uint3 GlobalInvocationID : SV_DispatchThreadID)
 {
     int2 coord = int2(GlobalInvocationID.xy);
     float4 inPixel = InImage[coord];
-    OutImage[coord] = ConvertToHDR10(inPixel, 200.0f);
+    OutImage[coord] = ConvertToHDR10(inPixel, PaperWhiteNits);
 }
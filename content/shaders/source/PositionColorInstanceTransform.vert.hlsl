@@ -0,0 +1,38 @@
+cbuffer UBO : register(b0, space1)
+{
+    float4x4 ViewProjectionMatrix : packoffset(c0);
+};
+
+struct Input
+{
+    // per vertex
+    float3 Position : TEXCOORD0;
+    float4 Color : TEXCOORD1;
+    // per instance
+    float4 InstancePositionRotation : TEXCOORD2;
+    float2 InstanceScale : TEXCOORD3;
+    float4 InstanceColor : TEXCOORD4;
+};
+
+struct Output
+{
+    float4 Color : TEXCOORD0;
+    float4 Position : SV_Position;
+};
+
+Output main(Input input)
+{
+    float c = cos(input.InstancePositionRotation.w);
+    float s = sin(input.InstancePositionRotation.w);
+
+    float2 coord = input.Position.xy * input.InstanceScale;
+    float2x2 rotation = {c, s, -s, c};
+    coord = mul(coord, rotation);
+
+    float3 coordWithDepth = float3(coord + input.InstancePositionRotation.xy, input.InstancePositionRotation.z);
+
+    Output output;
+    output.Color = input.Color * input.InstanceColor;
+    output.Position = mul(ViewProjectionMatrix, float4(coordWithDepth, 1.0f));
+    return output;
+}
@@ -3,7 +3,8 @@ struct SpriteData
     float3 Position;
     float Rotation;
     float2 Scale;
-    float2 Padding;
+    float Layer;
+    float Padding;
     float TexU, TexV, TexW, TexH;
     float4 Color;
 };
@@ -12,6 +13,7 @@ struct Output
 {
     float2 Texcoord : TEXCOORD0;
     float4 Color : TEXCOORD1;
+    nointerpolation float Layer : TEXCOORD2;
     float4 Position : SV_Position;
 };
 
@@ -58,6 +60,7 @@ Output main(uint id : SV_VertexID)
     output.Position = mul(ViewProjectionMatrix, float4(coordWithDepth, 1.0f));
     output.Texcoord = texcoord[vert];
     output.Color = sprite.Color;
+    output.Layer = sprite.Layer;
 
     return output;
 }
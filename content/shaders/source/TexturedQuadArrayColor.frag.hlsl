@@ -0,0 +1,14 @@
+Texture2DArray<float4> Texture : register(t0, space2);
+SamplerState Sampler : register(s0, space2);
+
+struct Input
+{
+    float2 TexCoord : TEXCOORD0;
+    float4 Color : TEXCOORD1;
+    nointerpolation float Layer : TEXCOORD2;
+};
+
+float4 main(Input input) : SV_Target0
+{
+    return input.Color * Texture.Sample(Sampler, float3(input.TexCoord, input.Layer));
+}
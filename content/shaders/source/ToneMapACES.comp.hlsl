@@ -1,6 +1,13 @@
 Texture2D<float4> inImage : register(t0, space0);
 RWTexture2D<float4> outImage : register(u0, space1);
 
+cbuffer ToneMapSettings : register(b0, space2)
+{
+    float Exposure : packoffset(c0.x);
+    float WhitePoint : packoffset(c0.y);
+    float PaperWhiteNits : packoffset(c0.z);
+};
+
 float3 rtt_and_odt_fit(float3 v)
 {
     float3 a = (v * (v + 0.0245786f.xxx)) - 0.000090537f.xxx;
@@ -20,6 +27,11 @@ void main(uint3 GlobalInvocationID : SV_DispatchThreadID)
 {
     int2 coord = int2(GlobalInvocationID.xy);
     float4 inPixel = inImage[coord];
-    float3 outColor = aces_fitted(inPixel.xyz);
+    float3 outColor = aces_fitted(inPixel.xyz * Exposure);
+    // no white point leaves the fit unnormalized
+    if (WhitePoint > 0.0f)
+    {
+        outColor /= aces_fitted(WhitePoint.xxx);
+    }
     outImage[coord] = float4(outColor, 1.0f);
 }
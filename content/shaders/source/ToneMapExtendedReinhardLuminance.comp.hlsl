@@ -1,6 +1,13 @@
 Texture2D<float4> inImage : register(t0, space0);
 RWTexture2D<float4> outImage : register(u0, space1);
 
+cbuffer ToneMapSettings : register(b0, space2)
+{
+    float Exposure : packoffset(c0.x);
+    float WhitePoint : packoffset(c0.y);
+    float PaperWhiteNits : packoffset(c0.z);
+};
+
 float luminance(float3 v)
 {
     return dot(v, float3(0.2126f, 0.7152f, 0.0722f));
@@ -15,7 +22,8 @@ float3 change_luminance(float3 c_in, float l_out)
 float3 reinhard_extended_luminance(float3 v, float max_white_l)
 {
     float l_old = luminance(v);
-    float numerator = l_old * (1.0f + (l_old / (max_white_l * max_white_l)));
+    // no white point is plain Reinhard on luminance
+    float numerator = max_white_l > 0.0f ? l_old * (1.0f + (l_old / (max_white_l * max_white_l))) : l_old;
     float l_new = numerator / (1.0f + l_old);
     return change_luminance(v, l_new);
 }
@@ -25,6 +33,6 @@ void main(uint3 GlobalInvocationID : SV_DispatchThreadID)
 {
     int2 coord = int2(GlobalInvocationID.xy);
     float4 inPixel = inImage[coord];
-    float3 outColor = reinhard_extended_luminance(inPixel.xyz, 662.0f);
+    float3 outColor = reinhard_extended_luminance(inPixel.xyz * Exposure, WhitePoint);
     outImage[coord] = float4(outColor, 1.0f);
 }
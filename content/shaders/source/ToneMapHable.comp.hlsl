@@ -1,6 +1,13 @@
 Texture2D<float4> inImage : register(t0, space0);
 RWTexture2D<float4> outImage : register(u0, space1);
 
+cbuffer ToneMapSettings : register(b0, space2)
+{
+    float Exposure : packoffset(c0.x);
+    float WhitePoint : packoffset(c0.y);
+    float PaperWhiteNits : packoffset(c0.z);
+};
+
 float3 hable_tonemap_partial(float3 x)
 {
     float A = 0.15f;
@@ -17,7 +24,12 @@ float3 hable_filmic(float3 v)
     float exposure_bias = 2.0f;
     float3 curr = hable_tonemap_partial(v * exposure_bias);
 
-    float3 W = 11.2f.xxx;
+    // no white point leaves the curve unnormalized
+    if (WhitePoint <= 0.0f)
+    {
+        return curr;
+    }
+    float3 W = WhitePoint.xxx;
     float3 white_scale = 1.0f.xxx / hable_tonemap_partial(W);
     return curr * white_scale;
 }
@@ -27,6 +39,6 @@ void main(uint3 GlobalInvocationID : SV_DispatchThreadID)
 {
     int2 coord = int2(GlobalInvocationID.xy);
     float4 inPixel = inImage[coord];
-    float3 outColor = hable_filmic(inPixel.xyz);
+    float3 outColor = hable_filmic(inPixel.xyz * Exposure);
     outImage[coord] = float4(outColor, 1.0f);
 }
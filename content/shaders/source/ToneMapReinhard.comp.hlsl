@@ -1,9 +1,18 @@
 Texture2D<float4> inImage : register(t0, space0);
 RWTexture2D<float4> outImage : register(u0, space1);
 
-float3 reinhard(float3 v)
+cbuffer ToneMapSettings : register(b0, space2)
 {
-    return v / (1.0f.xxx + v);
+    float Exposure : packoffset(c0.x);
+    float WhitePoint : packoffset(c0.y);
+    float PaperWhiteNits : packoffset(c0.z);
+};
+
+float3 reinhard_extended(float3 v, float max_white)
+{
+    // no white point is plain Reinhard
+    float3 numerator = max_white > 0.0f ? v * (1.0f.xxx + (v / (max_white * max_white).xxx)) : v;
+    return numerator / (1.0f.xxx + v);
 }
 
 [numthreads(8, 8, 1)]
@@ -11,6 +20,6 @@ void main(uint3 GlobalInvocationID : SV_DispatchThreadID)
 {
     int2 coord = int2(GlobalInvocationID.xy);
     float4 inPixel = inImage[coord];
-    float3 outColor = reinhard(inPixel.xyz);
+    float3 outColor = reinhard_extended(inPixel.xyz * Exposure, WhitePoint);
     outImage[coord] = float4(outColor, 1.0f);
 }
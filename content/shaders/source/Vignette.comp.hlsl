@@ -0,0 +1,28 @@
+cbuffer UBO : register(b0, space2)
+{
+    float Strength : packoffset(c0.x);
+    float Radius : packoffset(c0.y);
+};
+
+Texture2D<float4> InImage : register(t0, space0);
+RWTexture2D<unorm float4> OutImage : register(u0, space1);
+
+[numthreads(8, 8, 1)]
+void main(uint3 GlobalInvocationID : SV_DispatchThreadID)
+{
+    float w, h;
+    OutImage.GetDimensions(w, h);
+    int2 coord = int2(GlobalInvocationID.xy);
+    if (coord.x >= int(w) || coord.y >= int(h))
+    {
+        return;
+    }
+
+    // distance from the center, 1 at the middle of each edge
+    float2 uv = (float2(coord) + 0.5f) / float2(w, h);
+    float distance = length((uv - 0.5f) * 2.0f);
+    float darkening = smoothstep(Radius, Radius + 1.0f, distance) * Strength;
+
+    float4 inPixel = InImage[coord];
+    OutImage[coord] = float4(inPixel.rgb * (1.0f - darkening), inPixel.a);
+}
@@ -1,94 +1,154 @@
+use std::ffi::CStr;
 use std::ptr::null_mut;
-use std::sync::Mutex;
 
-use sdl3_main::{AppResult, app_event, app_init, app_iterate, app_quit};
+use sdl3_main::AppResult;
 use sdl3_sys::everything::*;
 
+use sdl3_experiment::app::{Frame, Game};
 use sdl3_experiment::common::*;
+use sdl3_experiment::gpu::GpuContext;
+use sdl3_experiment::input::Input;
+use sdl3_experiment::run_game;
 
-const BLOCK_SIZE_IN_PIXELS: i32 = 24;
-
-const GAME_WIDTH: i8 = 24;
-const GAME_HEIGHT: i8 = 18;
-
-struct AppState {
-    window: *mut SDL_Window,
-    device: *mut SDL_GPUDevice,
+struct BasicTriangle {
     fill_pipeline: *mut SDL_GPUGraphicsPipeline,
     line_pipeline: *mut SDL_GPUGraphicsPipeline,
-    game_state: GameState,
+    toggles: Toggles,
 }
 
-impl Drop for AppState {
-    fn drop(&mut self) {
-        unsafe {
-            if !self.fill_pipeline.is_null() {
-                SDL_ReleaseGPUGraphicsPipeline(self.device, self.fill_pipeline);
-            }
-            if !self.line_pipeline.is_null() {
-                SDL_ReleaseGPUGraphicsPipeline(self.device, self.line_pipeline);
-            }
+/// The gameplay state; kept apart from the pipelines so it can be driven by a replay.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Toggles {
+    use_wire_frame_mode: bool,
+    use_small_viewport: bool,
+    use_scissor_rect: bool,
+}
 
-            deinit_gpu_window(self.device, self.window);
+impl Toggles {
+    fn fixed_update(&mut self, input: &Input) {
+        if input.just_pressed(SDL_Scancode::LEFT) {
+            self.use_wire_frame_mode = !self.use_wire_frame_mode;
+        }
+        if input.just_pressed(SDL_Scancode::DOWN) {
+            self.use_small_viewport = !self.use_small_viewport;
+        }
+        if input.just_pressed(SDL_Scancode::RIGHT) {
+            self.use_scissor_rect = !self.use_scissor_rect;
         }
     }
 }
 
-unsafe impl Send for AppState {}
+impl Game for BasicTriangle {
+    const TITLE: &'static CStr = c"Basic Triangle";
+
+    fn init(gpu: &GpuContext) -> Option<Self> {
+        let device = gpu.device;
+
+        unsafe {
+            let vert_shader = load_shader(device, "RawTriangle.vert");
+            if vert_shader.is_null() {
+                dbg_sdl_error("failed to load vert shader");
+                return None;
+            }
+
+            let frag_shader = load_shader(device, "SolidColor.frag");
+            if frag_shader.is_null() {
+                dbg_sdl_error("failed to load frag shader");
+                return None;
+            }
+
+            let mut pipeline_create_info = SDL_GPUGraphicsPipelineCreateInfo {
+                vertex_shader: vert_shader,
+                fragment_shader: frag_shader,
+                primitive_type: SDL_GPU_PRIMITIVETYPE_TRIANGLELIST,
+                target_info: SDL_GPUGraphicsPipelineTargetInfo {
+                    num_color_targets: 1,
+                    color_target_descriptions: [SDL_GPUColorTargetDescription {
+                        format: gpu.target_format(),
+                        ..Default::default()
+                    }]
+                    .as_ptr(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            pipeline_create_info.rasterizer_state.fill_mode = SDL_GPU_FILLMODE_FILL;
+            let fill_pipeline = SDL_CreateGPUGraphicsPipeline(device, &pipeline_create_info);
+            if fill_pipeline.is_null() {
+                dbg_sdl_error("failed to create fill pipeline");
+                return None;
+            }
+
+            pipeline_create_info.rasterizer_state.fill_mode = SDL_GPU_FILLMODE_LINE;
+            let line_pipeline = SDL_CreateGPUGraphicsPipeline(device, &pipeline_create_info);
+            if line_pipeline.is_null() {
+                dbg_sdl_error("failed to create line pipeline");
+                return None;
+            }
 
-#[app_iterate]
-fn app_iterate(app: &mut AppState) -> AppResult {
-    unsafe {
-        let ticks = SDL_GetTicks();
-        app.game_state.step(ticks);
+            SDL_ReleaseGPUShader(device, vert_shader);
+            SDL_ReleaseGPUShader(device, frag_shader);
 
-        let command_buffer = SDL_AcquireGPUCommandBuffer(app.device);
-        if command_buffer.is_null() {
-            dbg_sdl_error("failed to acquire command buffer");
-            return AppResult::Failure;
+            println!("Press Left to toggle wireframe mode");
+            println!("Press Down to toggle small viewport");
+            println!("Press Right to toggle scissor rect");
+
+            Some(Self {
+                fill_pipeline,
+                line_pipeline,
+                toggles: Toggles::default(),
+            })
         }
+    }
 
-        let mut swapchain_texture: *mut SDL_GPUTexture = null_mut();
-        if !SDL_AcquireGPUSwapchainTexture(
-            command_buffer,
-            app.window,
-            &mut swapchain_texture,
-            null_mut(),
-            null_mut(),
-        ) {
-            dbg_sdl_error("failed to acquire swapchain texture");
-            return AppResult::Failure;
+    fn quit(&mut self, device: *mut SDL_GPUDevice) {
+        unsafe {
+            if !self.fill_pipeline.is_null() {
+                SDL_ReleaseGPUGraphicsPipeline(device, self.fill_pipeline);
+            }
+            if !self.line_pipeline.is_null() {
+                SDL_ReleaseGPUGraphicsPipeline(device, self.line_pipeline);
+            }
         }
+    }
+
+    fn fixed_update(&mut self, input: &Input) {
+        self.toggles.fixed_update(input);
+    }
 
-        if !swapchain_texture.is_null() {
-            let mut color_target_info: SDL_GPUColorTargetInfo = Default::default();
-            color_target_info.texture = swapchain_texture;
-            color_target_info.clear_color = SDL_FColor {
-                r: 0.0,
-                g: 0.0,
-                b: 0.0,
-                a: 1.0,
+    fn render(&mut self, frame: &Frame) -> AppResult {
+        unsafe {
+            let color_target_info = SDL_GPUColorTargetInfo {
+                texture: frame.target,
+                clear_color: SDL_FColor {
+                    r: 0.0,
+                    g: 0.0,
+                    b: 0.0,
+                    a: 1.0,
+                },
+                load_op: SDL_GPU_LOADOP_CLEAR,
+                store_op: SDL_GPU_STOREOP_STORE,
+                ..Default::default()
             };
-            color_target_info.load_op = SDL_GPU_LOADOP_CLEAR;
-            color_target_info.store_op = SDL_GPU_STOREOP_STORE;
 
             let num_color_targets = 1;
             let depth_stencil_target_info = null_mut();
             let render_pass = SDL_BeginGPURenderPass(
-                command_buffer,
+                frame.command_buffer,
                 &color_target_info,
                 num_color_targets,
                 depth_stencil_target_info,
             );
 
-            let pipeline = if app.game_state.use_wire_frame_mode {
-                app.line_pipeline
+            let pipeline = if self.toggles.use_wire_frame_mode {
+                self.line_pipeline
             } else {
-                app.fill_pipeline
+                self.fill_pipeline
             };
             SDL_BindGPUGraphicsPipeline(render_pass, pipeline);
 
-            if app.game_state.use_small_viewport {
+            if self.toggles.use_small_viewport {
                 let small_viewport = SDL_GPUViewport {
                     x: 160.0,
                     y: 120.0,
@@ -100,7 +160,7 @@ fn app_iterate(app: &mut AppState) -> AppResult {
                 SDL_SetGPUViewport(render_pass, &small_viewport);
             }
 
-            if app.game_state.use_scissor_rect {
+            if self.toggles.use_scissor_rect {
                 let scissor = SDL_Rect {
                     x: 320,
                     y: 240,
@@ -114,169 +174,8 @@ fn app_iterate(app: &mut AppState) -> AppResult {
             SDL_EndGPURenderPass(render_pass);
         }
 
-        SDL_SubmitGPUCommandBuffer(command_buffer);
+        AppResult::Continue
     }
-
-    AppResult::Continue
 }
 
-#[app_init]
-fn app_init() -> Option<Box<Mutex<AppState>>> {
-    unsafe {
-        let title = c"Basic Triangle".as_ptr();
-        let Some((window, device)) = init_gpu_window(title, SDL_WindowFlags::default()) else {
-            return None;
-        };
-
-        let vert_shader = load_shader(device, "RawTriangle.vert");
-        if vert_shader.is_null() {
-            dbg_sdl_error("failed to load vert shader");
-            return None;
-        }
-
-        let frag_shader = load_shader(device, "SolidColor.frag");
-        if frag_shader.is_null() {
-            dbg_sdl_error("failed to load frag shader");
-            return None;
-        }
-
-        let mut pipeline_create_info = SDL_GPUGraphicsPipelineCreateInfo {
-            vertex_shader: vert_shader,
-            fragment_shader: frag_shader,
-            primitive_type: SDL_GPU_PRIMITIVETYPE_TRIANGLELIST,
-            target_info: SDL_GPUGraphicsPipelineTargetInfo {
-                num_color_targets: 1,
-                color_target_descriptions: [SDL_GPUColorTargetDescription {
-                    format: SDL_GetGPUSwapchainTextureFormat(device, window),
-                    ..Default::default()
-                }]
-                .as_ptr(),
-                ..Default::default()
-            },
-            ..Default::default()
-        };
-
-        pipeline_create_info.rasterizer_state.fill_mode = SDL_GPU_FILLMODE_FILL;
-        let fill_pipeline = SDL_CreateGPUGraphicsPipeline(device, &pipeline_create_info);
-        if fill_pipeline.is_null() {
-            dbg_sdl_error("failed to create fill pipeline");
-            return None;
-        }
-
-        pipeline_create_info.rasterizer_state.fill_mode = SDL_GPU_FILLMODE_LINE;
-        let line_pipeline = SDL_CreateGPUGraphicsPipeline(device, &pipeline_create_info);
-        if line_pipeline.is_null() {
-            dbg_sdl_error("failed to create line pipeline");
-            return None;
-        }
-
-        SDL_ReleaseGPUShader(device, vert_shader);
-        SDL_ReleaseGPUShader(device, frag_shader);
-
-        let app = AppState {
-            window,
-            device,
-            fill_pipeline,
-            line_pipeline,
-            game_state: GameState::new(),
-        };
-
-        println!("Press Left to toggle wireframe mode");
-        println!("Press Down to toggle small viewport");
-        println!("Press Right to toggle scissor rect");
-
-        Some(Box::new(Mutex::new(app)))
-    }
-}
-
-#[app_event]
-fn app_event(app: &mut AppState, event: &SDL_Event) -> AppResult {
-    unsafe {
-        match SDL_EventType(event.r#type) {
-            SDL_EVENT_QUIT => AppResult::Success,
-
-            SDL_EVENT_KEY_DOWN => {
-                app.game_state.key_pressed(event.key.scancode);
-                AppResult::Continue
-            }
-
-            SDL_EVENT_KEY_UP => {
-                app.game_state.key_released(event.key.scancode);
-                AppResult::Continue
-            }
-
-            _ => AppResult::Continue,
-        }
-    }
-}
-
-#[app_quit]
-fn app_quit() {}
-
-const STEP_RATE_IN_MILLISECONDS: u64 = 16;
-
-pub const WINDOW_WIDTH: i32 = BLOCK_SIZE_IN_PIXELS * GAME_WIDTH as i32;
-pub const WINDOW_HEIGHT: i32 = BLOCK_SIZE_IN_PIXELS * GAME_HEIGHT as i32;
-
-pub struct GameState {
-    accumulated_ticks: u64,
-    last_step: u64,
-    keys_down: Vec<SDL_Scancode>,
-    keys_just_pressed: Vec<SDL_Scancode>,
-
-    pub use_wire_frame_mode: bool,
-    pub use_small_viewport: bool,
-    pub use_scissor_rect: bool,
-}
-
-impl GameState {
-    pub fn new() -> Self {
-        Self {
-            accumulated_ticks: 0,
-            last_step: 0,
-            keys_down: Default::default(),
-            keys_just_pressed: Default::default(),
-
-            use_wire_frame_mode: false,
-            use_small_viewport: false,
-            use_scissor_rect: false,
-        }
-    }
-
-    pub fn key_pressed(&mut self, scan_code: SDL_Scancode) {
-        if !self.keys_down.contains(&scan_code) {
-            self.keys_down.push(scan_code);
-            self.keys_just_pressed.push(scan_code);
-        }
-    }
-
-    pub fn key_released(&mut self, scan_code: SDL_Scancode) {
-        self.keys_down.retain(|k| *k != scan_code);
-        self.keys_just_pressed.retain(|k| *k != scan_code);
-    }
-
-    pub fn step(&mut self, current_tick: u64) {
-        let new_ticks = current_tick - self.last_step;
-        self.accumulated_ticks += new_ticks;
-        self.last_step = current_tick;
-
-        while self.accumulated_ticks >= STEP_RATE_IN_MILLISECONDS {
-            self.accumulated_ticks -= STEP_RATE_IN_MILLISECONDS;
-            self.fixed_step();
-
-            self.keys_just_pressed.clear();
-        }
-    }
-
-    fn fixed_step(&mut self) {
-        if self.keys_just_pressed.contains(&SDL_Scancode::LEFT) {
-            self.use_wire_frame_mode = !self.use_wire_frame_mode;
-        }
-        if self.keys_just_pressed.contains(&SDL_Scancode::DOWN) {
-            self.use_small_viewport = !self.use_small_viewport;
-        }
-        if self.keys_just_pressed.contains(&SDL_Scancode::RIGHT) {
-            self.use_scissor_rect = !self.use_scissor_rect;
-        }
-    }
-}
+run_game!(BasicTriangle);
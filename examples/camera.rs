@@ -0,0 +1,325 @@
+use std::ffi::CStr;
+use std::ptr::null_mut;
+
+use sdl3_main::AppResult;
+use sdl3_sys::everything::*;
+
+use sdl3_experiment::app::{Frame, Game};
+use sdl3_experiment::camera::Camera2D;
+use sdl3_experiment::common::*;
+use sdl3_experiment::debug_draw::DebugDraw;
+use sdl3_experiment::gpu::GpuContext;
+use sdl3_experiment::input::Input;
+use sdl3_experiment::run_game;
+use sdl3_experiment::sprite_batch::{GPUSprite, SpriteBatch};
+
+/// The world is a field of raviolis, larger than the window in every direction.
+const TILE_SIZE: f32 = 64.0;
+const TILES_X: u32 = 40;
+const TILES_Y: u32 = 30;
+const WORLD_WIDTH: f32 = TILE_SIZE * TILES_X as f32;
+const WORLD_HEIGHT: f32 = TILE_SIZE * TILES_Y as f32;
+
+const PLAYER_SIZE: f32 = 48.0;
+const PLAYER_SPEED: f32 = 6.0;
+
+const ZOOM_STEP: f32 = 1.02;
+const ROTATION_STEP: f32 = 0.02;
+
+const GREEN: SDL_FColor = SDL_FColor {
+    r: 0.3,
+    g: 1.0,
+    b: 0.3,
+    a: 1.0,
+};
+const YELLOW: SDL_FColor = SDL_FColor {
+    r: 1.0,
+    g: 0.9,
+    b: 0.2,
+    a: 1.0,
+};
+const GRID_COLOR: SDL_FColor = SDL_FColor {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 0.15,
+};
+
+struct CameraExample {
+    sprite_batch: SpriteBatch,
+    texture: *mut SDL_GPUTexture,
+    sampler: *mut SDL_GPUSampler,
+    camera: Camera2D,
+    debug_draw: DebugDraw,
+    show_debug: bool,
+
+    player_x: f32,
+    player_y: f32,
+    /// Where the mouse was last clicked, in world coordinates.
+    marker: Option<(f32, f32)>,
+}
+
+impl CameraExample {
+    fn ravioli(x: f32, y: f32, size: f32, index: u32) -> GPUSprite {
+        GPUSprite {
+            x,
+            y,
+            w: size,
+            h: size,
+            tex_u: (index % 2) as f32 * 0.5,
+            tex_v: (index / 2 % 2) as f32 * 0.5,
+            tex_w: 0.5,
+            tex_h: 0.5,
+            ..Default::default()
+        }
+    }
+
+    /// Grid lines on the tile edges, and the world bounds.
+    fn push_grid(&mut self) {
+        for x in 0..=TILES_X {
+            let x = x as f32 * TILE_SIZE;
+            self.debug_draw
+                .line((x, 0.0), (x, WORLD_HEIGHT), GRID_COLOR);
+        }
+        for y in 0..=TILES_Y {
+            let y = y as f32 * TILE_SIZE;
+            self.debug_draw.line((0.0, y), (WORLD_WIDTH, y), GRID_COLOR);
+        }
+        self.debug_draw
+            .rect(0.0, 0.0, WORLD_WIDTH, WORLD_HEIGHT, GREEN);
+    }
+}
+
+impl Game for CameraExample {
+    const TITLE: &'static CStr = c"2D Camera";
+
+    fn window_flags() -> SDL_WindowFlags {
+        SDL_WINDOW_RESIZABLE
+    }
+
+    fn init(gpu: &GpuContext) -> Option<Self> {
+        let device = gpu.device;
+
+        unsafe {
+            let sprite_batch =
+                SpriteBatch::new(device, gpu.target_format(), TILES_X * TILES_Y + 2)?;
+
+            let texture = load_texture(device, "ravioli_atlas.bmp");
+            if texture.is_null() {
+                return None;
+            }
+            let sampler = create_sampler(
+                device,
+                SDL_GPU_FILTER_LINEAR,
+                SDL_GPU_SAMPLERADDRESSMODE_CLAMP_TO_EDGE,
+            );
+            let mut debug_draw = DebugDraw::new(gpu)?;
+            debug_draw.text_scale = 2.0;
+
+            let player_x = WORLD_WIDTH * 0.5;
+            let player_y = WORLD_HEIGHT * 0.5;
+            let mut camera = Camera2D::new(Self::WINDOW_WIDTH as f32, Self::WINDOW_HEIGHT as f32);
+            camera.bounds = Some(SDL_FRect {
+                x: 0.0,
+                y: 0.0,
+                w: WORLD_WIDTH,
+                h: WORLD_HEIGHT,
+            });
+            camera.target = Some((player_x, player_y));
+            camera.snap_to_target();
+
+            println!("Use the arrow keys to move; the camera follows, staying inside the world");
+            println!("Press Z/X to zoom, Q/E to rotate, Space to shake and R to reset");
+            println!("Click to mark a point in the world");
+            println!("Press G to show or hide the debug drawing");
+
+            Some(Self {
+                sprite_batch,
+                texture,
+                sampler,
+                camera,
+                debug_draw,
+                show_debug: true,
+                player_x,
+                player_y,
+                marker: None,
+            })
+        }
+    }
+
+    fn quit(&mut self, device: *mut SDL_GPUDevice) {
+        unsafe {
+            self.sprite_batch.release(device);
+            self.debug_draw.release(device);
+            if !self.texture.is_null() {
+                SDL_ReleaseGPUTexture(device, self.texture);
+            }
+            if !self.sampler.is_null() {
+                SDL_ReleaseGPUSampler(device, self.sampler);
+            }
+        }
+    }
+
+    fn fixed_update(&mut self, input: &Input) {
+        self.debug_draw.begin_step();
+
+        let mut dx = 0.0;
+        let mut dy = 0.0;
+        if input.is_down(SDL_Scancode::LEFT) {
+            dx -= 1.0;
+        }
+        if input.is_down(SDL_Scancode::RIGHT) {
+            dx += 1.0;
+        }
+        if input.is_down(SDL_Scancode::UP) {
+            dy -= 1.0;
+        }
+        if input.is_down(SDL_Scancode::DOWN) {
+            dy += 1.0;
+        }
+
+        // move relative to the screen, whichever way the camera is turned
+        let (sin, cos) = self.camera.rotation.sin_cos();
+        self.player_x =
+            (self.player_x + (dx * cos - dy * sin) * PLAYER_SPEED).clamp(0.0, WORLD_WIDTH);
+        self.player_y =
+            (self.player_y + (dx * sin + dy * cos) * PLAYER_SPEED).clamp(0.0, WORLD_HEIGHT);
+
+        if input.is_down(SDL_Scancode::Z) {
+            self.camera.zoom = (self.camera.zoom * ZOOM_STEP).min(4.0);
+        }
+        if input.is_down(SDL_Scancode::X) {
+            self.camera.zoom = (self.camera.zoom / ZOOM_STEP).max(0.25);
+        }
+        if input.is_down(SDL_Scancode::Q) {
+            self.camera.rotation -= ROTATION_STEP;
+        }
+        if input.is_down(SDL_Scancode::E) {
+            self.camera.rotation += ROTATION_STEP;
+        }
+        if input.just_pressed(SDL_Scancode::SPACE) {
+            self.camera.add_trauma(0.5);
+        }
+        if input.just_pressed(SDL_Scancode::R) {
+            self.camera.zoom = 1.0;
+            self.camera.rotation = 0.0;
+        }
+
+        if input.just_pressed(SDL_Scancode::G) {
+            self.show_debug = !self.show_debug;
+        }
+
+        self.camera.target = Some((self.player_x, self.player_y));
+        self.camera.fixed_update();
+
+        // the player's collision box and the way the camera is heading, as of this step
+        let (camera_x, camera_y) = (self.camera.x, self.camera.y);
+        let (player_x, player_y) = (self.player_x, self.player_y);
+        self.debug_draw.until_next_step(|debug| {
+            let half_size = PLAYER_SIZE * 0.5;
+            debug.rect(
+                player_x - half_size,
+                player_y - half_size,
+                PLAYER_SIZE,
+                PLAYER_SIZE,
+                GREEN,
+            );
+            debug.arrow((camera_x, camera_y), (player_x, player_y), YELLOW);
+        });
+    }
+
+    fn render(&mut self, frame: &Frame) -> AppResult {
+        self.camera.update(frame);
+
+        unsafe {
+            for y in 0..TILES_Y {
+                for x in 0..TILES_X {
+                    self.sprite_batch.push(GPUSprite {
+                        a: 0.5,
+                        ..Self::ravioli(
+                            x as f32 * TILE_SIZE,
+                            y as f32 * TILE_SIZE,
+                            TILE_SIZE,
+                            x + y,
+                        )
+                    });
+                }
+            }
+            if let Some((x, y)) = self.marker {
+                self.sprite_batch.push(GPUSprite {
+                    r: 1.0,
+                    g: 0.3,
+                    b: 0.3,
+                    ..Self::ravioli(x - 12.0, y - 12.0, 24.0, 0)
+                });
+            }
+            let half_size = PLAYER_SIZE * 0.5;
+            self.sprite_batch.push(Self::ravioli(
+                self.player_x - half_size,
+                self.player_y - half_size,
+                PLAYER_SIZE,
+                3,
+            ));
+            if !self.sprite_batch.upload(frame.device, frame.command_buffer) {
+                return AppResult::Failure;
+            }
+
+            let render_pass = SDL_BeginGPURenderPass(
+                frame.command_buffer,
+                &SDL_GPUColorTargetInfo {
+                    texture: frame.target,
+                    load_op: SDL_GPULoadOp::CLEAR,
+                    store_op: SDL_GPUStoreOp::STORE,
+                    clear_color: SDL_FColor {
+                        r: 0.1,
+                        g: 0.1,
+                        b: 0.15,
+                        a: 1.0,
+                    },
+                    ..Default::default()
+                },
+                1,
+                null_mut(),
+            );
+
+            self.sprite_batch.draw(
+                frame.command_buffer,
+                render_pass,
+                self.texture,
+                self.sampler,
+                &self.camera.view_projection(),
+            );
+
+            SDL_EndGPURenderPass(render_pass);
+
+            if self.show_debug {
+                self.push_grid();
+                if !self.debug_draw.flush(frame, &self.camera.view_projection()) {
+                    return AppResult::Failure;
+                }
+            }
+        }
+
+        AppResult::Continue
+    }
+
+    fn handle_event(&mut self, event: &SDL_Event) -> AppResult {
+        unsafe {
+            if SDL_EventType(event.r#type) == SDL_EVENT_MOUSE_BUTTON_DOWN {
+                // mouse positions are in points, like the camera's screen
+                let (x, y) = self.camera.screen_to_world(event.button.x, event.button.y);
+                println!("clicked world position: ({x:.1}, {y:.1})");
+                self.marker = Some((x, y));
+                // stays for two seconds at 60 fps
+                self.debug_draw.for_frames(120, |debug| {
+                    debug.circle((x, y), 24.0, YELLOW);
+                    debug.text_at(x + 28.0, y - 8.0, format!("{x:.0}, {y:.0}"), YELLOW);
+                });
+            }
+        }
+
+        AppResult::Continue
+    }
+}
+
+run_game!(CameraExample);
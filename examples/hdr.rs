@@ -0,0 +1,274 @@
+use std::ffi::CStr;
+use std::ptr::null_mut;
+
+use sdl3_main::AppResult;
+use sdl3_sys::everything::*;
+
+use sdl3_experiment::app::{Frame, Game};
+use sdl3_experiment::common::*;
+use sdl3_experiment::gpu::GpuContext;
+use sdl3_experiment::hdr::{HdrOutput, HdrPipeline, SCENE_FORMAT};
+use sdl3_experiment::input::Input;
+use sdl3_experiment::run_game;
+use sdl3_experiment::sprite_batch::{GPUSprite, SpriteBatch};
+
+/// Each row is a hue, each column doubles the brightness of the one before,
+/// from well below SDR white to far above it.
+const HUES: [[f32; 3]; 5] = [
+    [1.0, 1.0, 1.0],
+    [1.0, 0.2, 0.1],
+    [0.2, 1.0, 0.2],
+    [0.1, 0.3, 1.0],
+    [1.0, 0.6, 0.1],
+];
+const COLUMNS: usize = 10;
+const DARKEST_STOP: f32 = -3.0;
+
+struct Hdr {
+    hdr: HdrPipeline,
+    sprite_batch: SpriteBatch,
+    texture: *mut SDL_GPUTexture,
+    sampler: *mut SDL_GPUSampler,
+
+    /// Set by the fixed step and applied at render time, where the window is available.
+    requested_output: Option<HdrOutput>,
+    /// Scrolls a bright band across the bars.
+    time: f32,
+}
+
+impl Hdr {
+    const EXPOSURE_STEP: f32 = 1.189_207; // a quarter stop
+    const WHITE_POINT_STEP: f32 = 0.5;
+    const PAPER_WHITE_STEP: f32 = 20.0;
+
+    fn update_settings(&mut self, input: &Input) {
+        let operator = self.hdr.operator();
+        let settings = &mut self.hdr.settings;
+        let before = *settings;
+
+        if input.just_pressed(SDL_Scancode::A) {
+            settings.auto_exposure = !settings.auto_exposure;
+        }
+        if input.just_pressed(SDL_Scancode::UP) {
+            settings.auto_exposure = false;
+            settings.set_exposure(settings.exposure * Self::EXPOSURE_STEP);
+        }
+        if input.just_pressed(SDL_Scancode::DOWN) {
+            settings.auto_exposure = false;
+            settings.set_exposure(settings.exposure / Self::EXPOSURE_STEP);
+        }
+        // operators without a white point start from the lowest one
+        let white_point = settings.white_point(operator).unwrap_or(1.0);
+        if input.just_pressed(SDL_Scancode::RIGHT) {
+            settings.white_point = Some(white_point + Self::WHITE_POINT_STEP);
+        }
+        if input.just_pressed(SDL_Scancode::LEFT) {
+            settings.white_point = Some((white_point - Self::WHITE_POINT_STEP).max(1.0));
+        }
+        if input.just_pressed(SDL_Scancode::PAGEUP) {
+            settings.paper_white_nits =
+                (settings.paper_white_nits + Self::PAPER_WHITE_STEP).min(1000.0);
+        }
+        if input.just_pressed(SDL_Scancode::PAGEDOWN) {
+            settings.paper_white_nits =
+                (settings.paper_white_nits - Self::PAPER_WHITE_STEP).max(80.0);
+        }
+
+        if *settings != before {
+            println!(
+                "exposure: {:.2}{}, white point: {}, paper white: {} nits",
+                settings.exposure,
+                if settings.auto_exposure {
+                    " (auto)"
+                } else {
+                    ""
+                },
+                settings
+                    .white_point(operator)
+                    .map_or("none".to_string(), |w| format!("{w:.1}")),
+                settings.paper_white_nits
+            );
+        }
+    }
+}
+
+impl Game for Hdr {
+    const TITLE: &'static CStr = c"HDR Tone Mapping";
+
+    fn init(gpu: &GpuContext) -> Option<Self> {
+        let device = gpu.device;
+
+        unsafe {
+            let hdr = HdrPipeline::new(device, Self::MAX_FRAMES_IN_FLIGHT)?;
+            let sprite_batch =
+                SpriteBatch::new(device, SCENE_FORMAT, (HUES.len() * COLUMNS + 1) as u32)?;
+
+            let texture = create_white_texture(device);
+            if texture.is_null() {
+                return None;
+            }
+            let sampler = create_sampler(
+                device,
+                SDL_GPU_FILTER_NEAREST,
+                SDL_GPU_SAMPLERADDRESSMODE_CLAMP_TO_EDGE,
+            );
+
+            println!("Press T to cycle tone mapping operators");
+            println!("Press O to cycle SDR, HDR extended linear and HDR10 output");
+            println!("Press Up/Down to change exposure, or A to toggle auto-exposure");
+            println!("Press Left/Right to change the white point");
+            println!("Press Page Up/Page Down to change HDR10 paper white");
+            println!("tone map: {:?}, output: {:?}", hdr.operator(), hdr.output());
+
+            Some(Self {
+                hdr,
+                sprite_batch,
+                texture,
+                sampler,
+                requested_output: None,
+                time: 0.0,
+            })
+        }
+    }
+
+    fn quit(&mut self, device: *mut SDL_GPUDevice) {
+        unsafe {
+            self.hdr.release(device);
+            self.sprite_batch.release(device);
+            if !self.texture.is_null() {
+                SDL_ReleaseGPUTexture(device, self.texture);
+            }
+            if !self.sampler.is_null() {
+                SDL_ReleaseGPUSampler(device, self.sampler);
+            }
+        }
+    }
+
+    fn fixed_update(&mut self, input: &Input) {
+        if input.just_pressed(SDL_Scancode::T) {
+            self.hdr.set_operator(self.hdr.operator().next());
+            // back to the new operator's own white point
+            self.hdr.settings.white_point = None;
+            println!("tone map: {:?}", self.hdr.operator());
+        }
+
+        if input.just_pressed(SDL_Scancode::O) {
+            let current = self.requested_output.unwrap_or(self.hdr.output());
+            let index = HdrOutput::ALL.iter().position(|o| *o == current).unwrap();
+            self.requested_output = Some(HdrOutput::ALL[(index + 1) % HdrOutput::ALL.len()]);
+        }
+
+        self.update_settings(input);
+
+        self.time += 1.0 / Self::STEPS_PER_SECOND as f32;
+    }
+
+    fn render(&mut self, frame: &Frame) -> AppResult {
+        unsafe {
+            if let Some(output) = self.requested_output.take() {
+                let present_mode = frame.present_mode.unwrap_or(SDL_GPUPresentMode::VSYNC);
+                if self
+                    .hdr
+                    .set_output(frame.device, frame.window, output, present_mode)
+                {
+                    println!("output: {output:?}");
+                } else {
+                    println!(
+                        "output {output:?} is unavailable, staying on {:?}",
+                        self.hdr.output()
+                    );
+                }
+            }
+
+            if !self.hdr.prepare(frame.device, frame.width, frame.height) {
+                return AppResult::Failure;
+            }
+
+            let cell_w = frame.width as f32 / COLUMNS as f32;
+            let cell_h = frame.height as f32 / (HUES.len() + 1) as f32;
+            for (row, hue) in HUES.iter().enumerate() {
+                for column in 0..COLUMNS {
+                    let brightness = 2.0_f32.powf(DARKEST_STOP + column as f32);
+                    self.sprite_batch.push(GPUSprite {
+                        x: column as f32 * cell_w + 2.0,
+                        y: row as f32 * cell_h + 2.0,
+                        w: cell_w - 4.0,
+                        h: cell_h - 4.0,
+                        r: hue[0] * brightness,
+                        g: hue[1] * brightness,
+                        b: hue[2] * brightness,
+                        a: 1.0,
+                        ..Default::default()
+                    });
+                }
+            }
+
+            // a band sweeping from black to very bright along the bottom row
+            let sweep = (self.time * 0.5).fract();
+            let brightness = 2.0_f32.powf(DARKEST_STOP + sweep * COLUMNS as f32);
+            self.sprite_batch.push(GPUSprite {
+                x: sweep * frame.width as f32 - cell_w / 2.0,
+                y: HUES.len() as f32 * cell_h + 2.0,
+                w: cell_w,
+                h: cell_h - 4.0,
+                r: brightness,
+                g: brightness,
+                b: brightness,
+                a: 1.0,
+                ..Default::default()
+            });
+
+            if !self.sprite_batch.upload(frame.device, frame.command_buffer) {
+                return AppResult::Failure;
+            }
+
+            let render_pass = SDL_BeginGPURenderPass(
+                frame.command_buffer,
+                &SDL_GPUColorTargetInfo {
+                    texture: self.hdr.scene_texture(),
+                    load_op: SDL_GPULoadOp::CLEAR,
+                    store_op: SDL_GPUStoreOp::STORE,
+                    clear_color: SDL_FColor {
+                        r: 0.02,
+                        g: 0.02,
+                        b: 0.02,
+                        a: 1.0,
+                    },
+                    ..Default::default()
+                },
+                1,
+                null_mut(),
+            );
+
+            let camera = Matrix4x4::create_orthographic_off_center(
+                0.0,
+                frame.width as f32,
+                frame.height as f32,
+                0.0,
+                0.0,
+                -1.0,
+            );
+            self.sprite_batch.draw(
+                frame.command_buffer,
+                render_pass,
+                self.texture,
+                self.sampler,
+                &camera,
+            );
+
+            SDL_EndGPURenderPass(render_pass);
+
+            self.hdr.resolve(
+                frame.device,
+                frame.command_buffer,
+                frame.target,
+                frame.width,
+                frame.height,
+            );
+        }
+
+        AppResult::Continue
+    }
+}
+
+run_game!(Hdr);
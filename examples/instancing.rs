@@ -0,0 +1,446 @@
+use std::ffi::{CStr, c_void};
+use std::ptr::null_mut;
+
+use sdl3_main::AppResult;
+use sdl3_sys::everything::*;
+
+use sdl3_experiment::app::{Frame, Game};
+use sdl3_experiment::camera::ScreenCamera;
+use sdl3_experiment::common::*;
+use sdl3_experiment::gpu::GpuContext;
+use sdl3_experiment::input::Input;
+use sdl3_experiment::run_game;
+use sdl3_experiment::sprite_batch::{GPUSprite, SpriteBatch};
+
+const MIN_INSTANCES: usize = 1024;
+const MAX_INSTANCES: usize = 65536;
+const INSTANCE_SIZE: f32 = 16.0;
+
+const WHITE: SDL_FColor = SDL_FColor {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+/// The one mesh every instance draws: a unit quad with its origin in the top left,
+/// matching the quads PullSpriteBatch.vert builds.
+const QUAD_VERTICES: [PositionColorVertex; 4] = [
+    PositionColorVertex {
+        position: [0.0, 0.0, 0.0],
+        color: WHITE,
+    },
+    PositionColorVertex {
+        position: [1.0, 0.0, 0.0],
+        color: WHITE,
+    },
+    PositionColorVertex {
+        position: [1.0, 1.0, 0.0],
+        color: WHITE,
+    },
+    PositionColorVertex {
+        position: [0.0, 1.0, 0.0],
+        color: WHITE,
+    },
+];
+const QUAD_INDICES: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+/// PositionColorInstanced.vert lays its instances out in a 4x4 grid from `SV_InstanceID`.
+const GRID_INSTANCES: u32 = 16;
+
+/// see the per instance inputs of PositionColorInstanceTransform.vert.hlsl
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct InstanceData {
+    position: [f32; 3],
+    rotation: f32,
+    scale: [f32; 2],
+    color: SDL_FColor,
+}
+
+impl Vertex for InstanceData {
+    const ATTRIBUTES: &'static [SDL_GPUVertexAttribute] = &[
+        SDL_GPUVertexAttribute {
+            location: 0,
+            buffer_slot: 0,
+            // position and rotation together
+            format: SDL_GPU_VERTEXELEMENTFORMAT_FLOAT4,
+            offset: std::mem::offset_of!(InstanceData, position) as u32,
+        },
+        SDL_GPUVertexAttribute {
+            location: 1,
+            buffer_slot: 0,
+            format: SDL_GPU_VERTEXELEMENTFORMAT_FLOAT2,
+            offset: std::mem::offset_of!(InstanceData, scale) as u32,
+        },
+        SDL_GPUVertexAttribute {
+            location: 2,
+            buffer_slot: 0,
+            format: SDL_GPU_VERTEXELEMENTFORMAT_FLOAT4,
+            offset: std::mem::offset_of!(InstanceData, color) as u32,
+        },
+    ];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Mode {
+    /// Per-instance data in an instance-rate vertex buffer, one indexed draw.
+    Instanced,
+    /// The same data as `GPUSprite`s in a storage buffer, pulled by `SV_VertexID`.
+    Pulled,
+    /// No per-instance data at all; the shader places each instance from its index.
+    Grid,
+}
+
+impl Mode {
+    fn next(self) -> Self {
+        match self {
+            Mode::Instanced => Mode::Pulled,
+            Mode::Pulled => Mode::Grid,
+            Mode::Grid => Mode::Instanced,
+        }
+    }
+}
+
+/// The 'gameplay data' of an instance
+struct Particle {
+    x: f32,
+    y: f32,
+    dx: f32,
+    dy: f32,
+    rotation: f32,
+    spin: f32,
+    color: SDL_FColor,
+}
+
+impl Particle {
+    fn random(width: f32, height: f32) -> Self {
+        unsafe {
+            Self {
+                x: SDL_randf() * width,
+                y: SDL_randf() * height,
+                dx: (SDL_randf() - 0.5) * 4.0,
+                dy: (SDL_randf() - 0.5) * 4.0,
+                rotation: SDL_randf() * SDL_PI_F * 2.0,
+                spin: (SDL_randf() - 0.5) * 0.2,
+                color: SDL_FColor {
+                    r: 0.5 + SDL_randf() * 0.5,
+                    g: 0.5 + SDL_randf() * 0.5,
+                    b: 0.5 + SDL_randf() * 0.5,
+                    a: 0.8,
+                },
+            }
+        }
+    }
+
+    /// Bounces off the edges of a screen of the given size
+    fn fixed_update(&mut self, width: f32, height: f32) {
+        self.x += self.dx;
+        self.y += self.dy;
+        if self.x < 0.0 || self.x > width {
+            self.dx = -self.dx;
+        }
+        if self.y < 0.0 || self.y > height {
+            self.dy = -self.dy;
+        }
+        self.rotation += self.spin;
+    }
+
+    fn to_instance(&self) -> InstanceData {
+        InstanceData {
+            position: [self.x, self.y, 0.0],
+            rotation: self.rotation,
+            scale: [INSTANCE_SIZE, INSTANCE_SIZE],
+            color: self.color,
+        }
+    }
+
+    fn to_sprite(&self) -> GPUSprite {
+        GPUSprite {
+            x: self.x,
+            y: self.y,
+            rotation: self.rotation,
+            w: INSTANCE_SIZE,
+            h: INSTANCE_SIZE,
+            r: self.color.r,
+            g: self.color.g,
+            b: self.color.b,
+            a: self.color.a,
+            ..Default::default()
+        }
+    }
+}
+
+/// Average frame times over about a second, to compare the modes.
+#[derive(Default)]
+struct FrameStats {
+    last_ticks_ns: u64,
+    elapsed_ns: u64,
+    frames: u32,
+}
+
+struct Instancing {
+    instanced_pipeline: *mut SDL_GPUGraphicsPipeline,
+    grid_pipeline: *mut SDL_GPUGraphicsPipeline,
+    mesh_vertices: VertexBuffer<PositionColorVertex>,
+    mesh_indices: IndexBuffer<u16>,
+    instances: DynamicVertexBuffer<InstanceData>,
+    instance_data: Vec<InstanceData>,
+    sprite_batch: SpriteBatch,
+    white_texture: *mut SDL_GPUTexture,
+    sampler: *mut SDL_GPUSampler,
+    camera: ScreenCamera,
+
+    particles: Vec<Particle>,
+    mode: Mode,
+    stats: FrameStats,
+}
+
+impl Instancing {
+    fn set_count(&mut self, count: usize) {
+        let count = count.clamp(MIN_INSTANCES, MAX_INSTANCES);
+        self.particles.truncate(count);
+        while self.particles.len() < count {
+            let particle = Particle::random(self.camera.width(), self.camera.height());
+            self.particles.push(particle);
+        }
+        println!("instances: {count}");
+    }
+
+    fn bytes_per_frame(&self) -> usize {
+        match self.mode {
+            Mode::Instanced => self.particles.len() * std::mem::size_of::<InstanceData>(),
+            Mode::Pulled => self.particles.len() * std::mem::size_of::<GPUSprite>(),
+            Mode::Grid => 0,
+        }
+    }
+
+    fn update_stats(&mut self) {
+        let now = unsafe { SDL_GetTicksNS() };
+        let stats = &mut self.stats;
+        if stats.last_ticks_ns != 0 {
+            stats.elapsed_ns += now - stats.last_ticks_ns;
+            stats.frames += 1;
+        }
+        stats.last_ticks_ns = now;
+
+        if stats.elapsed_ns >= SDL_NS_PER_SECOND as u64 {
+            let ms = stats.elapsed_ns as f64 / stats.frames as f64 / 1_000_000.0;
+            stats.elapsed_ns = 0;
+            stats.frames = 0;
+            println!(
+                "{:?}: {:.2} ms per frame, {} bytes uploaded per frame",
+                self.mode,
+                ms,
+                self.bytes_per_frame()
+            );
+        }
+    }
+}
+
+impl Game for Instancing {
+    const TITLE: &'static CStr = c"Instancing";
+
+    fn window_flags() -> SDL_WindowFlags {
+        SDL_WINDOW_RESIZABLE
+    }
+
+    // don't let vsync hide the difference between the modes
+    fn swapchain_settings() -> SwapchainSettings {
+        SwapchainSettings {
+            present_modes: &[SDL_GPUPresentMode::IMMEDIATE],
+            ..SwapchainSettings::VSYNC
+        }
+    }
+
+    fn init(gpu: &GpuContext) -> Option<Self> {
+        let device = gpu.device;
+        let format = gpu.target_format();
+
+        unsafe {
+            let instanced_pipeline = create_graphics_pipeline(
+                device,
+                format,
+                "PositionColorInstanceTransform.vert",
+                "SolidColor.frag",
+                &VertexInputLayout::new()
+                    .with_vertices::<PositionColorVertex>()
+                    .with_instances::<InstanceData>(),
+            );
+            let grid_pipeline = create_graphics_pipeline(
+                device,
+                format,
+                "PositionColorInstanced.vert",
+                "SolidColor.frag",
+                &VertexInputLayout::new().with_vertices::<PositionColorVertex>(),
+            );
+            if instanced_pipeline.is_null() || grid_pipeline.is_null() {
+                return None;
+            }
+
+            let mesh_vertices = VertexBuffer::new(device, &QUAD_VERTICES)?;
+            let mesh_indices = IndexBuffer::new(device, &QUAD_INDICES)?;
+            let instances = DynamicVertexBuffer::new(device, MAX_INSTANCES as u32)?;
+            let sprite_batch = SpriteBatch::new(device, format, MAX_INSTANCES as u32)?;
+
+            let white_texture = create_white_texture(device);
+            if white_texture.is_null() {
+                return None;
+            }
+            let sampler = create_sampler(
+                device,
+                SDL_GPU_FILTER_NEAREST,
+                SDL_GPU_SAMPLERADDRESSMODE_CLAMP_TO_EDGE,
+            );
+
+            println!("Press Space to switch between instanced, pulled and grid drawing");
+            println!("Press Up/Down to double or halve the instance count");
+
+            let mut example = Self {
+                instanced_pipeline,
+                grid_pipeline,
+                mesh_vertices,
+                mesh_indices,
+                instances,
+                instance_data: Vec::with_capacity(MAX_INSTANCES),
+                sprite_batch,
+                white_texture,
+                sampler,
+                camera: ScreenCamera::new(Self::WINDOW_WIDTH as f32, Self::WINDOW_HEIGHT as f32),
+                particles: Vec::with_capacity(MAX_INSTANCES),
+                mode: Mode::Instanced,
+                stats: FrameStats::default(),
+            };
+            example.set_count(8192);
+
+            Some(example)
+        }
+    }
+
+    fn quit(&mut self, device: *mut SDL_GPUDevice) {
+        unsafe {
+            if !self.instanced_pipeline.is_null() {
+                SDL_ReleaseGPUGraphicsPipeline(device, self.instanced_pipeline);
+            }
+            if !self.grid_pipeline.is_null() {
+                SDL_ReleaseGPUGraphicsPipeline(device, self.grid_pipeline);
+            }
+            self.mesh_vertices.release(device);
+            self.mesh_indices.release(device);
+            self.instances.release(device);
+            self.sprite_batch.release(device);
+            if !self.white_texture.is_null() {
+                SDL_ReleaseGPUTexture(device, self.white_texture);
+            }
+            if !self.sampler.is_null() {
+                SDL_ReleaseGPUSampler(device, self.sampler);
+            }
+        }
+    }
+
+    fn fixed_update(&mut self, input: &Input) {
+        if input.just_pressed(SDL_Scancode::SPACE) {
+            self.mode = self.mode.next();
+            self.stats = FrameStats::default();
+            println!("mode: {:?}", self.mode);
+        }
+        if input.just_pressed(SDL_Scancode::UP) {
+            self.set_count(self.particles.len() * 2);
+        }
+        if input.just_pressed(SDL_Scancode::DOWN) {
+            self.set_count(self.particles.len() / 2);
+        }
+
+        let (width, height) = (self.camera.width(), self.camera.height());
+        for particle in &mut self.particles {
+            particle.fixed_update(width, height);
+        }
+    }
+
+    fn render(&mut self, frame: &Frame) -> AppResult {
+        self.update_stats();
+        self.camera.update(frame);
+
+        unsafe {
+            match self.mode {
+                Mode::Instanced => {
+                    self.instance_data.clear();
+                    self.instance_data
+                        .extend(self.particles.iter().map(Particle::to_instance));
+                    if !self.instances.upload(
+                        frame.device,
+                        frame.command_buffer,
+                        &self.instance_data,
+                    ) {
+                        return AppResult::Failure;
+                    }
+                }
+                Mode::Pulled => {
+                    for particle in &self.particles {
+                        self.sprite_batch.push(particle.to_sprite());
+                    }
+                    if !self.sprite_batch.upload(frame.device, frame.command_buffer) {
+                        return AppResult::Failure;
+                    }
+                }
+                Mode::Grid => {}
+            }
+
+            let render_pass = SDL_BeginGPURenderPass(
+                frame.command_buffer,
+                &SDL_GPUColorTargetInfo {
+                    texture: frame.target,
+                    load_op: SDL_GPULoadOp::CLEAR,
+                    store_op: SDL_GPUStoreOp::STORE,
+                    clear_color: SDL_FColor {
+                        r: 0.0,
+                        g: 0.0,
+                        b: 0.0,
+                        a: 1.0,
+                    },
+                    ..Default::default()
+                },
+                1,
+                null_mut(),
+            );
+
+            let camera = self.camera.matrix();
+            match self.mode {
+                Mode::Instanced => {
+                    SDL_BindGPUGraphicsPipeline(render_pass, self.instanced_pipeline);
+                    self.mesh_vertices.bind(render_pass, 0);
+                    self.instances.bind(render_pass, 1);
+                    self.mesh_indices.bind(render_pass);
+                    SDL_PushGPUVertexUniformData(
+                        frame.command_buffer,
+                        0,
+                        camera as *const Matrix4x4 as *const c_void,
+                        std::mem::size_of::<Matrix4x4>() as u32,
+                    );
+                    self.mesh_indices.draw(render_pass, self.instances.len());
+                }
+                Mode::Pulled => {
+                    self.sprite_batch.draw(
+                        frame.command_buffer,
+                        render_pass,
+                        self.white_texture,
+                        self.sampler,
+                        camera,
+                    );
+                }
+                Mode::Grid => {
+                    SDL_BindGPUGraphicsPipeline(render_pass, self.grid_pipeline);
+                    self.mesh_vertices.bind(render_pass, 0);
+                    self.mesh_indices.bind(render_pass);
+                    self.mesh_indices.draw(render_pass, GRID_INSTANCES);
+                }
+            }
+
+            SDL_EndGPURenderPass(render_pass);
+        }
+
+        AppResult::Continue
+    }
+}
+
+run_game!(Instancing);
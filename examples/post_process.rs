@@ -0,0 +1,265 @@
+use std::ffi::CStr;
+use std::ptr::null_mut;
+
+use sdl3_main::AppResult;
+use sdl3_sys::everything::*;
+
+use sdl3_experiment::app::{Frame, Game};
+use sdl3_experiment::common::*;
+use sdl3_experiment::gpu::GpuContext;
+use sdl3_experiment::input::Input;
+use sdl3_experiment::post_process::{PostEffect, PostProcessStack, SCENE_FORMAT};
+use sdl3_experiment::run_game;
+use sdl3_experiment::sprite_batch::{GPUSprite, SpriteBatch};
+
+const SPRITE_COUNT: usize = 64;
+const SPRITE_SIZE: f32 = 48.0;
+
+/// The number keys toggle the effects in this order, which is also the order they're applied in.
+const EFFECT_KEYS: [(SDL_Scancode, PostEffect); 4] = [
+    (SDL_Scancode::_1, PostEffect::Blur),
+    (SDL_Scancode::_2, PostEffect::Scanlines),
+    (SDL_Scancode::_3, PostEffect::Vignette),
+    (SDL_Scancode::_4, PostEffect::ColorGrade),
+];
+
+/// A ravioli bouncing around the scene
+struct Bouncer {
+    x: f32,
+    y: f32,
+    dx: f32,
+    dy: f32,
+    ravioli: usize,
+}
+
+impl Bouncer {
+    // ravioli image offsets
+    const U_COORDS: [f32; 4] = [0.0, 0.5, 0.0, 0.5];
+    const V_COORDS: [f32; 4] = [0.0, 0.0, 0.5, 0.5];
+
+    fn random() -> Self {
+        unsafe {
+            Self {
+                x: SDL_randf(),
+                y: SDL_randf(),
+                dx: (SDL_randf() - 0.5) * 0.01,
+                dy: (SDL_randf() - 0.5) * 0.01,
+                ravioli: SDL_rand(4) as usize,
+            }
+        }
+    }
+
+    fn fixed_update(&mut self) {
+        self.x += self.dx;
+        self.y += self.dy;
+        if !(0.0..=1.0).contains(&self.x) {
+            self.dx = -self.dx;
+        }
+        if !(0.0..=1.0).contains(&self.y) {
+            self.dy = -self.dy;
+        }
+    }
+
+    /// Positions are kept in 0..1 so the scene fills any target size.
+    fn to_gpu(&self, width: f32, height: f32) -> GPUSprite {
+        GPUSprite {
+            x: self.x * (width - SPRITE_SIZE),
+            y: self.y * (height - SPRITE_SIZE),
+            w: SPRITE_SIZE,
+            h: SPRITE_SIZE,
+            tex_u: Self::U_COORDS[self.ravioli],
+            tex_v: Self::V_COORDS[self.ravioli],
+            tex_w: 0.5,
+            tex_h: 0.5,
+            ..Default::default()
+        }
+    }
+}
+
+struct PostProcess {
+    post_process: PostProcessStack,
+    sprite_batch: SpriteBatch,
+    texture: *mut SDL_GPUTexture,
+    sampler: *mut SDL_GPUSampler,
+
+    bouncers: Vec<Bouncer>,
+    /// Set by the fixed step and applied at render time, where the device is available.
+    next_lut_requested: bool,
+    time: f32,
+}
+
+impl PostProcess {
+    fn print_stack(&self) {
+        let stack: Vec<String> = self
+            .post_process
+            .effects()
+            .iter()
+            .map(|(effect, enabled)| format!("{effect:?}: {}", if *enabled { "on" } else { "off" }))
+            .collect();
+        println!(
+            "{} (blur passes: {}, lut: {:?})",
+            stack.join(", "),
+            self.post_process.settings.blur_passes,
+            self.post_process.lut_preset()
+        );
+    }
+}
+
+impl Game for PostProcess {
+    const TITLE: &'static CStr = c"Post Processing";
+
+    fn init(gpu: &GpuContext) -> Option<Self> {
+        let device = gpu.device;
+
+        unsafe {
+            let effects = EFFECT_KEYS.map(|(_, effect)| effect);
+            let mut post_process = PostProcessStack::new(device, &effects)?;
+            post_process.set_enabled(PostEffect::Vignette, true);
+
+            let sprite_batch = SpriteBatch::new(device, SCENE_FORMAT, SPRITE_COUNT as u32)?;
+
+            let texture = load_texture(device, "ravioli_atlas.bmp");
+            if texture.is_null() {
+                return None;
+            }
+            let sampler = create_sampler(
+                device,
+                SDL_GPU_FILTER_NEAREST,
+                SDL_GPU_SAMPLERADDRESSMODE_CLAMP_TO_EDGE,
+            );
+
+            println!("Press 1-4 to toggle blur, scanlines, vignette and color grading");
+            println!("Press Up/Down to change the number of blur passes");
+            println!("Press L to cycle the color grading lookup table");
+
+            let example = Self {
+                post_process,
+                sprite_batch,
+                texture,
+                sampler,
+                bouncers: (0..SPRITE_COUNT).map(|_| Bouncer::random()).collect(),
+                next_lut_requested: false,
+                time: 0.0,
+            };
+            example.print_stack();
+
+            Some(example)
+        }
+    }
+
+    fn quit(&mut self, device: *mut SDL_GPUDevice) {
+        unsafe {
+            self.post_process.release(device);
+            self.sprite_batch.release(device);
+            if !self.texture.is_null() {
+                SDL_ReleaseGPUTexture(device, self.texture);
+            }
+            if !self.sampler.is_null() {
+                SDL_ReleaseGPUSampler(device, self.sampler);
+            }
+        }
+    }
+
+    fn fixed_update(&mut self, input: &Input) {
+        let mut changed = false;
+        for (key, effect) in EFFECT_KEYS {
+            if input.just_pressed(key) {
+                self.post_process.toggle(effect);
+                changed = true;
+            }
+        }
+
+        let settings = &mut self.post_process.settings;
+        if input.just_pressed(SDL_Scancode::UP) {
+            settings.blur_passes = (settings.blur_passes + 1).min(8);
+            changed = true;
+        }
+        if input.just_pressed(SDL_Scancode::DOWN) {
+            settings.blur_passes = settings.blur_passes.saturating_sub(1);
+            changed = true;
+        }
+        if input.just_pressed(SDL_Scancode::L) {
+            self.next_lut_requested = true;
+        }
+
+        if changed {
+            self.print_stack();
+        }
+
+        for bouncer in &mut self.bouncers {
+            bouncer.fixed_update();
+        }
+
+        self.time += 1.0 / Self::STEPS_PER_SECOND as f32;
+    }
+
+    fn render(&mut self, frame: &Frame) -> AppResult {
+        unsafe {
+            if self.next_lut_requested {
+                self.next_lut_requested = false;
+                let preset = self.post_process.lut_preset().next();
+                if self.post_process.set_lut_preset(frame.device, preset) {
+                    self.print_stack();
+                }
+            }
+
+            if !self
+                .post_process
+                .prepare(frame.device, frame.width, frame.height)
+            {
+                return AppResult::Failure;
+            }
+
+            let width = frame.width as f32;
+            let height = frame.height as f32;
+            for bouncer in &self.bouncers {
+                self.sprite_batch.push(bouncer.to_gpu(width, height));
+            }
+            if !self.sprite_batch.upload(frame.device, frame.command_buffer) {
+                return AppResult::Failure;
+            }
+
+            let render_pass = SDL_BeginGPURenderPass(
+                frame.command_buffer,
+                &SDL_GPUColorTargetInfo {
+                    texture: self.post_process.scene_texture(),
+                    load_op: SDL_GPULoadOp::CLEAR,
+                    store_op: SDL_GPUStoreOp::STORE,
+                    clear_color: SDL_FColor {
+                        r: 0.3,
+                        g: 0.4,
+                        b: 0.5,
+                        a: 1.0,
+                    },
+                    ..Default::default()
+                },
+                1,
+                null_mut(),
+            );
+
+            let camera =
+                Matrix4x4::create_orthographic_off_center(0.0, width, height, 0.0, 0.0, -1.0);
+            self.sprite_batch.draw(
+                frame.command_buffer,
+                render_pass,
+                self.texture,
+                self.sampler,
+                &camera,
+            );
+
+            SDL_EndGPURenderPass(render_pass);
+
+            self.post_process.apply(
+                frame.command_buffer,
+                frame.target,
+                frame.width,
+                frame.height,
+                self.time,
+            );
+        }
+
+        AppResult::Continue
+    }
+}
+
+run_game!(PostProcess);
@@ -0,0 +1,189 @@
+use std::ffi::CStr;
+use std::ptr::null_mut;
+
+use sdl3_main::AppResult;
+use sdl3_sys::everything::*;
+
+use sdl3_experiment::app::{Frame, Game};
+use sdl3_experiment::common::*;
+use sdl3_experiment::gpu::GpuContext;
+use sdl3_experiment::input::Input;
+use sdl3_experiment::procedural::{ProceduralFill, ProceduralTexture};
+use sdl3_experiment::run_game;
+use sdl3_experiment::sprite_batch::{GPUSprite, SpriteBatch};
+
+const fn rgb(r: f32, g: f32, b: f32) -> SDL_FColor {
+    SDL_FColor { r, g, b, a: 1.0 }
+}
+
+/// Gradient endpoints cycled through with Space.
+const PALETTES: [(SDL_FColor, SDL_FColor); 3] = [
+    (rgb(0.1, 0.0, 0.3), rgb(0.9, 0.4, 0.2)),
+    (rgb(0.0, 0.2, 0.2), rgb(0.6, 0.9, 0.7)),
+    (rgb(0.05, 0.05, 0.05), rgb(0.5, 0.5, 0.6)),
+];
+
+/// Colors the small squares' texture cycles through, one per second.
+const SQUARE_COLORS: [SDL_FColor; 4] = [
+    rgb(1.0, 1.0, 0.0),
+    rgb(0.0, 1.0, 1.0),
+    rgb(1.0, 0.0, 1.0),
+    rgb(1.0, 1.0, 1.0),
+];
+const SQUARE_COUNT: u32 = 12;
+
+struct ProceduralTextureExample {
+    background: ProceduralTexture,
+    square: ProceduralTexture,
+    sprite_batch: SpriteBatch,
+    sampler: *mut SDL_GPUSampler,
+
+    palette: usize,
+    time: f32,
+}
+
+impl Game for ProceduralTextureExample {
+    const TITLE: &'static CStr = c"Procedural Texture";
+
+    fn init(gpu: &GpuContext) -> Option<Self> {
+        let device = gpu.device;
+
+        unsafe {
+            // the gradient is smooth, so a small texture stretched over the window is plenty
+            let background = ProceduralTexture::new(device, 160, 120)?;
+            let square = ProceduralTexture::new(device, 8, 8)?;
+            let sprite_batch = SpriteBatch::new(device, gpu.target_format(), SQUARE_COUNT + 1)?;
+
+            let sampler = create_sampler(
+                device,
+                SDL_GPU_FILTER_LINEAR,
+                SDL_GPU_SAMPLERADDRESSMODE_CLAMP_TO_EDGE,
+            );
+
+            println!("Press Space to change the background colors");
+
+            Some(Self {
+                background,
+                square,
+                sprite_batch,
+                sampler,
+                palette: 0,
+                time: 0.0,
+            })
+        }
+    }
+
+    fn quit(&mut self, device: *mut SDL_GPUDevice) {
+        unsafe {
+            self.background.release(device);
+            self.square.release(device);
+            self.sprite_batch.release(device);
+            if !self.sampler.is_null() {
+                SDL_ReleaseGPUSampler(device, self.sampler);
+            }
+        }
+    }
+
+    fn fixed_update(&mut self, input: &Input) {
+        if input.just_pressed(SDL_Scancode::SPACE) {
+            self.palette = (self.palette + 1) % PALETTES.len();
+        }
+
+        self.time += 1.0 / Self::STEPS_PER_SECOND as f32;
+    }
+
+    fn render(&mut self, frame: &Frame) -> AppResult {
+        unsafe {
+            let (start, end) = PALETTES[self.palette];
+            self.background.generate(
+                frame.command_buffer,
+                &ProceduralFill::Gradient {
+                    start,
+                    end,
+                    time: self.time,
+                },
+            );
+            self.square.generate(
+                frame.command_buffer,
+                &ProceduralFill::Solid {
+                    color: SQUARE_COLORS[self.time as usize % SQUARE_COLORS.len()],
+                },
+            );
+
+            let width = frame.width as f32;
+            let height = frame.height as f32;
+            let camera =
+                Matrix4x4::create_orthographic_off_center(0.0, width, height, 0.0, 0.0, -1.0);
+
+            // the background and squares use different textures, so they're two draws
+            self.sprite_batch.push(GPUSprite {
+                w: width,
+                h: height,
+                ..Default::default()
+            });
+            if !self.draw_batch(
+                frame,
+                self.background.texture(),
+                &camera,
+                SDL_GPULoadOp::CLEAR,
+            ) {
+                return AppResult::Failure;
+            }
+
+            for i in 0..SQUARE_COUNT {
+                let angle = self.time + i as f32 * SDL_PI_F * 2.0 / SQUARE_COUNT as f32;
+                self.sprite_batch.push(GPUSprite {
+                    x: width / 2.0 + angle.cos() * height / 3.0 - 16.0,
+                    y: height / 2.0 + angle.sin() * height / 3.0 - 16.0,
+                    rotation: angle,
+                    ..Default::default()
+                });
+            }
+            if !self.draw_batch(frame, self.square.texture(), &camera, SDL_GPULoadOp::LOAD) {
+                return AppResult::Failure;
+            }
+        }
+
+        AppResult::Continue
+    }
+}
+
+impl ProceduralTextureExample {
+    /// Uploads and draws the pushed sprites in their own render pass.
+    unsafe fn draw_batch(
+        &mut self,
+        frame: &Frame,
+        texture: *mut SDL_GPUTexture,
+        camera: &Matrix4x4,
+        load_op: SDL_GPULoadOp,
+    ) -> bool {
+        if !self.sprite_batch.upload(frame.device, frame.command_buffer) {
+            return false;
+        }
+
+        let render_pass = SDL_BeginGPURenderPass(
+            frame.command_buffer,
+            &SDL_GPUColorTargetInfo {
+                texture: frame.target,
+                load_op,
+                clear_color: rgb(0.0, 0.0, 0.0),
+                store_op: SDL_GPUStoreOp::STORE,
+                ..Default::default()
+            },
+            1,
+            null_mut(),
+        );
+        self.sprite_batch.draw(
+            frame.command_buffer,
+            render_pass,
+            texture,
+            self.sampler,
+            camera,
+        );
+        SDL_EndGPURenderPass(render_pass);
+
+        true
+    }
+}
+
+run_game!(ProceduralTextureExample);
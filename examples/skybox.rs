@@ -0,0 +1,393 @@
+use std::ffi::{CStr, c_void};
+use std::ptr::null_mut;
+
+use sdl3_main::AppResult;
+use sdl3_sys::everything::*;
+
+use sdl3_experiment::app::{Frame, Game};
+use sdl3_experiment::common::*;
+use sdl3_experiment::gpu::GpuContext;
+use sdl3_experiment::input::Input;
+use sdl3_experiment::run_game;
+
+const SKYBOX_FACES: [&str; 6] = [
+    "skybox_px.bmp",
+    "skybox_nx.bmp",
+    "skybox_py.bmp",
+    "skybox_ny.bmp",
+    "skybox_pz.bmp",
+    "skybox_nz.bmp",
+];
+const SKYBOX_CROSS: &str = "skybox_cross.bmp";
+
+/// The corners of a cube around the camera; also the direction each corner samples the cubemap in.
+const CUBE_VERTICES: [[f32; 3]; 8] = [
+    [-10.0, -10.0, -10.0],
+    [10.0, -10.0, -10.0],
+    [10.0, 10.0, -10.0],
+    [-10.0, 10.0, -10.0],
+    [-10.0, -10.0, 10.0],
+    [10.0, -10.0, 10.0],
+    [10.0, 10.0, 10.0],
+    [-10.0, 10.0, 10.0],
+];
+
+#[rustfmt::skip]
+const CUBE_INDICES: [u16; 36] = [
+    0, 1, 2, 0, 2, 3, // -Z
+    5, 4, 7, 5, 7, 6, // +Z
+    4, 0, 3, 4, 3, 7, // -X
+    1, 5, 6, 1, 6, 2, // +X
+    3, 2, 6, 3, 6, 7, // +Y
+    4, 5, 1, 4, 1, 0, // -Y
+];
+
+struct Skybox {
+    pipeline: *mut SDL_GPUGraphicsPipeline,
+    vertex_buffer: *mut SDL_GPUBuffer,
+    index_buffer: *mut SDL_GPUBuffer,
+    /// Loaded from six face images, and from one cross layout image.
+    cubemaps: [*mut SDL_GPUTexture; 2],
+    sampler: *mut SDL_GPUSampler,
+
+    camera: Camera,
+    use_cross_cubemap: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CameraMode {
+    /// Circles a point, slowly turning by itself when no keys are held.
+    Orbit,
+    /// Moves freely; the sky stays put, since it's infinitely far away.
+    Fly,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Camera {
+    mode: CameraMode,
+    yaw: f32,
+    pitch: f32,
+    orbit_target: Vector3,
+    orbit_distance: f32,
+    fly_position: Vector3,
+}
+
+impl Camera {
+    const TURN_SPEED: f32 = 0.03;
+    const AUTO_TURN_SPEED: f32 = 0.002;
+    const MOVE_SPEED: f32 = 0.1;
+    const MAX_PITCH: f32 = 1.5;
+
+    fn new() -> Self {
+        Self {
+            mode: CameraMode::Orbit,
+            yaw: 0.0,
+            pitch: 0.0,
+            orbit_target: Vector3::ZERO,
+            orbit_distance: 4.0,
+            fly_position: Vector3::ZERO,
+        }
+    }
+
+    fn forward(&self) -> Vector3 {
+        Vector3::new(
+            self.yaw.sin() * self.pitch.cos(),
+            self.pitch.sin(),
+            -self.yaw.cos() * self.pitch.cos(),
+        )
+    }
+
+    fn position(&self) -> Vector3 {
+        match self.mode {
+            CameraMode::Orbit => self.orbit_target - self.forward() * self.orbit_distance,
+            CameraMode::Fly => self.fly_position,
+        }
+    }
+
+    fn fixed_update(&mut self, input: &Input) {
+        if input.just_pressed(SDL_Scancode::TAB) {
+            // keep looking from the same place when switching
+            self.fly_position = self.position();
+            self.mode = match self.mode {
+                CameraMode::Orbit => CameraMode::Fly,
+                CameraMode::Fly => CameraMode::Orbit,
+            };
+            self.orbit_target = self.fly_position + self.forward() * self.orbit_distance;
+            println!("camera mode: {:?}", self.mode);
+        }
+
+        let mut turned = false;
+        if input.is_down(SDL_Scancode::LEFT) {
+            self.yaw -= Self::TURN_SPEED;
+            turned = true;
+        }
+        if input.is_down(SDL_Scancode::RIGHT) {
+            self.yaw += Self::TURN_SPEED;
+            turned = true;
+        }
+        if input.is_down(SDL_Scancode::UP) {
+            self.pitch += Self::TURN_SPEED;
+            turned = true;
+        }
+        if input.is_down(SDL_Scancode::DOWN) {
+            self.pitch -= Self::TURN_SPEED;
+            turned = true;
+        }
+        self.pitch = self.pitch.clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+
+        let forward = self.forward();
+        let right = forward.cross(Vector3::UP).normalize();
+        match self.mode {
+            CameraMode::Orbit => {
+                if !turned {
+                    self.yaw += Self::AUTO_TURN_SPEED;
+                }
+                if input.is_down(SDL_Scancode::W) {
+                    self.orbit_distance = (self.orbit_distance - Self::MOVE_SPEED).max(1.0);
+                }
+                if input.is_down(SDL_Scancode::S) {
+                    self.orbit_distance = (self.orbit_distance + Self::MOVE_SPEED).min(8.0);
+                }
+            }
+            CameraMode::Fly => {
+                let mut movement = Vector3::ZERO;
+                if input.is_down(SDL_Scancode::W) {
+                    movement = movement + forward;
+                }
+                if input.is_down(SDL_Scancode::S) {
+                    movement = movement - forward;
+                }
+                if input.is_down(SDL_Scancode::D) {
+                    movement = movement + right;
+                }
+                if input.is_down(SDL_Scancode::A) {
+                    movement = movement - right;
+                }
+                self.fly_position = self.fly_position + movement.normalize() * Self::MOVE_SPEED;
+            }
+        }
+    }
+
+    fn view(&self) -> Matrix4x4 {
+        let position = self.position();
+        Matrix4x4::create_look_at(position, position + self.forward(), Vector3::UP)
+    }
+}
+
+impl Game for Skybox {
+    const TITLE: &'static CStr = c"Skybox";
+
+    fn init(gpu: &GpuContext) -> Option<Self> {
+        let device = gpu.device;
+
+        unsafe {
+            let vert_shader = load_shader(device, "Skybox.vert");
+            if vert_shader.is_null() {
+                dbg_sdl_error("failed to load vert shader");
+                return None;
+            }
+
+            let frag_shader = load_shader(device, "Skybox.frag");
+            if frag_shader.is_null() {
+                dbg_sdl_error("failed to load frag shader");
+                return None;
+            }
+
+            let pipeline = SDL_CreateGPUGraphicsPipeline(
+                device,
+                &SDL_GPUGraphicsPipelineCreateInfo {
+                    vertex_shader: vert_shader,
+                    fragment_shader: frag_shader,
+                    primitive_type: SDL_GPU_PRIMITIVETYPE_TRIANGLELIST,
+                    vertex_input_state: SDL_GPUVertexInputState {
+                        vertex_buffer_descriptions: [SDL_GPUVertexBufferDescription {
+                            slot: 0,
+                            pitch: std::mem::size_of::<[f32; 3]>() as u32,
+                            input_rate: SDL_GPU_VERTEXINPUTRATE_VERTEX,
+                            instance_step_rate: 0,
+                        }]
+                        .as_ptr(),
+                        num_vertex_buffers: 1,
+                        vertex_attributes: [SDL_GPUVertexAttribute {
+                            location: 0,
+                            buffer_slot: 0,
+                            format: SDL_GPU_VERTEXELEMENTFORMAT_FLOAT3,
+                            offset: 0,
+                        }]
+                        .as_ptr(),
+                        num_vertex_attributes: 1,
+                    },
+                    target_info: SDL_GPUGraphicsPipelineTargetInfo {
+                        num_color_targets: 1,
+                        color_target_descriptions: [SDL_GPUColorTargetDescription {
+                            format: gpu.target_format(),
+                            ..Default::default()
+                        }]
+                        .as_ptr(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            );
+
+            SDL_ReleaseGPUShader(device, vert_shader);
+            SDL_ReleaseGPUShader(device, frag_shader);
+
+            if pipeline.is_null() {
+                dbg_sdl_error("failed to create skybox pipeline");
+                return None;
+            }
+
+            let vertex_buffer =
+                create_buffer_with_data(device, SDL_GPU_BUFFERUSAGE_VERTEX, &CUBE_VERTICES);
+            let index_buffer =
+                create_buffer_with_data(device, SDL_GPU_BUFFERUSAGE_INDEX, &CUBE_INDICES);
+            if vertex_buffer.is_null() || index_buffer.is_null() {
+                return None;
+            }
+
+            let cubemaps = [
+                load_cubemap(device, SKYBOX_FACES),
+                load_cubemap_cross(device, SKYBOX_CROSS),
+            ];
+            if cubemaps.iter().any(|cubemap| cubemap.is_null()) {
+                println!("failed to load skybox cubemaps");
+                return None;
+            }
+
+            let sampler = create_sampler(
+                device,
+                SDL_GPU_FILTER_LINEAR,
+                SDL_GPU_SAMPLERADDRESSMODE_CLAMP_TO_EDGE,
+            );
+
+            println!("Press Left/Right/Up/Down to look around");
+            println!("Press W/S to move in or out, and A/D to strafe when flying");
+            println!("Press Tab to switch between orbit and fly cameras");
+            println!("Press C to switch between the six-face and cross layout cubemaps");
+
+            Some(Self {
+                pipeline,
+                vertex_buffer,
+                index_buffer,
+                cubemaps,
+                sampler,
+                camera: Camera::new(),
+                use_cross_cubemap: false,
+            })
+        }
+    }
+
+    fn quit(&mut self, device: *mut SDL_GPUDevice) {
+        unsafe {
+            if !self.pipeline.is_null() {
+                SDL_ReleaseGPUGraphicsPipeline(device, self.pipeline);
+            }
+            if !self.vertex_buffer.is_null() {
+                SDL_ReleaseGPUBuffer(device, self.vertex_buffer);
+            }
+            if !self.index_buffer.is_null() {
+                SDL_ReleaseGPUBuffer(device, self.index_buffer);
+            }
+            for cubemap in self.cubemaps {
+                SDL_ReleaseGPUTexture(device, cubemap);
+            }
+            if !self.sampler.is_null() {
+                SDL_ReleaseGPUSampler(device, self.sampler);
+            }
+        }
+    }
+
+    fn fixed_update(&mut self, input: &Input) {
+        if input.just_pressed(SDL_Scancode::C) {
+            self.use_cross_cubemap = !self.use_cross_cubemap;
+            let layout = if self.use_cross_cubemap {
+                "cross"
+            } else {
+                "six faces"
+            };
+            println!("cubemap: {layout}");
+        }
+
+        self.camera.fixed_update(input);
+    }
+
+    fn render(&mut self, frame: &Frame) -> AppResult {
+        // the sky is infinitely far away, so only the camera's rotation matters
+        let mut view = self.camera.view();
+        view.m41 = 0.0;
+        view.m42 = 0.0;
+        view.m43 = 0.0;
+
+        let projection = Matrix4x4::create_perspective_field_of_view(
+            75.0_f32.to_radians(),
+            frame.width as f32 / frame.height as f32,
+            0.01,
+            100.0,
+        );
+        let view_projection = view.multiply(&projection);
+
+        let cubemap = self.cubemaps[self.use_cross_cubemap as usize];
+
+        unsafe {
+            let render_pass = SDL_BeginGPURenderPass(
+                frame.command_buffer,
+                &SDL_GPUColorTargetInfo {
+                    texture: frame.target,
+                    load_op: SDL_GPULoadOp::CLEAR,
+                    store_op: SDL_GPUStoreOp::STORE,
+                    clear_color: SDL_FColor {
+                        r: 0.0,
+                        g: 0.0,
+                        b: 0.0,
+                        a: 1.0,
+                    },
+                    ..Default::default()
+                },
+                1,
+                null_mut(),
+            );
+
+            SDL_BindGPUGraphicsPipeline(render_pass, self.pipeline);
+            SDL_BindGPUVertexBuffers(
+                render_pass,
+                0,
+                &SDL_GPUBufferBinding {
+                    buffer: self.vertex_buffer,
+                    offset: 0,
+                },
+                1,
+            );
+            SDL_BindGPUIndexBuffer(
+                render_pass,
+                &SDL_GPUBufferBinding {
+                    buffer: self.index_buffer,
+                    offset: 0,
+                },
+                SDL_GPU_INDEXELEMENTSIZE_16BIT,
+            );
+            SDL_BindGPUFragmentSamplers(
+                render_pass,
+                0,
+                &SDL_GPUTextureSamplerBinding {
+                    texture: cubemap,
+                    sampler: self.sampler,
+                },
+                1,
+            );
+            SDL_PushGPUVertexUniformData(
+                frame.command_buffer,
+                0,
+                &view_projection as *const Matrix4x4 as *const c_void,
+                std::mem::size_of::<Matrix4x4>() as u32,
+            );
+            SDL_DrawGPUIndexedPrimitives(render_pass, CUBE_INDICES.len() as u32, 1, 0, 0, 0);
+
+            SDL_EndGPURenderPass(render_pass);
+        }
+
+        AppResult::Continue
+    }
+}
+
+run_game!(Skybox);
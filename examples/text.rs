@@ -0,0 +1,246 @@
+use std::ffi::CStr;
+use std::ptr::null_mut;
+
+use sdl3_main::AppResult;
+use sdl3_sys::everything::*;
+
+use sdl3_experiment::app::{Frame, Game};
+use sdl3_experiment::camera::ScreenCamera;
+use sdl3_experiment::common::*;
+use sdl3_experiment::gpu::GpuContext;
+use sdl3_experiment::input::Input;
+use sdl3_experiment::run_game;
+use sdl3_experiment::sprite_batch::{GPUSprite, SpriteBatch};
+use sdl3_experiment::text::{BitmapFont, TextAlign, TextStyle};
+
+const MAX_GLYPHS: u32 = 4096;
+
+const MARGIN: f32 = 16.0;
+const MIN_WRAP_WIDTH: f32 = 64.0;
+
+const PARAGRAPH: &str = "Text is laid out from a BMFont description and drawn as sprites \
+through the PullSpriteBatch pipeline, one quad per glyph. Lines wrap between words to fit \
+the box, and can be aligned to its left, center or right.\n\nNewlines start new paragraphs.";
+
+const KERNING_SAMPLE: &str = "AVATAR  Type  LTWY  To, Yo.";
+
+const HELP: &str = "Left/Right: wrap width   Space: alignment   Up/Down: scale";
+
+struct TextExample {
+    font: BitmapFont,
+    text_batch: SpriteBatch,
+    panel_batch: SpriteBatch,
+    white_texture: *mut SDL_GPUTexture,
+    sampler: *mut SDL_GPUSampler,
+    camera: ScreenCamera,
+
+    wrap_width: f32,
+    align: TextAlign,
+    scale: f32,
+}
+
+impl Game for TextExample {
+    const TITLE: &'static CStr = c"Bitmap Text";
+
+    fn window_flags() -> SDL_WindowFlags {
+        SDL_WINDOW_RESIZABLE
+    }
+
+    fn init(gpu: &GpuContext) -> Option<Self> {
+        let device = gpu.device;
+        let format = gpu.target_format();
+
+        unsafe {
+            let font = BitmapFont::load(device, "pixel.fnt")?;
+            let text_batch = SpriteBatch::new(device, format, MAX_GLYPHS)?;
+            let panel_batch = SpriteBatch::new(device, format, 1)?;
+
+            let white_texture = create_white_texture(device);
+            if white_texture.is_null() {
+                return None;
+            }
+            // nearest, so a pixel font at whole number scales stays sharp
+            let sampler = create_sampler(
+                device,
+                SDL_GPU_FILTER_NEAREST,
+                SDL_GPU_SAMPLERADDRESSMODE_CLAMP_TO_EDGE,
+            );
+
+            Some(Self {
+                font,
+                text_batch,
+                panel_batch,
+                white_texture,
+                sampler,
+                camera: ScreenCamera::new(Self::WINDOW_WIDTH as f32, Self::WINDOW_HEIGHT as f32),
+                wrap_width: 320.0,
+                align: TextAlign::Left,
+                scale: 2.0,
+            })
+        }
+    }
+
+    fn quit(&mut self, device: *mut SDL_GPUDevice) {
+        unsafe {
+            self.font.release(device);
+            self.text_batch.release(device);
+            self.panel_batch.release(device);
+            if !self.white_texture.is_null() {
+                SDL_ReleaseGPUTexture(device, self.white_texture);
+            }
+            if !self.sampler.is_null() {
+                SDL_ReleaseGPUSampler(device, self.sampler);
+            }
+        }
+    }
+
+    fn fixed_update(&mut self, input: &Input) {
+        let max_wrap_width = (self.camera.width() - MARGIN * 2.0).max(MIN_WRAP_WIDTH);
+        if input.is_down(SDL_Scancode::LEFT) {
+            self.wrap_width = (self.wrap_width - 4.0).max(MIN_WRAP_WIDTH);
+        }
+        if input.is_down(SDL_Scancode::RIGHT) {
+            self.wrap_width = (self.wrap_width + 4.0).min(max_wrap_width);
+        }
+        if input.just_pressed(SDL_Scancode::SPACE) {
+            self.align = match self.align {
+                TextAlign::Left => TextAlign::Center,
+                TextAlign::Center => TextAlign::Right,
+                TextAlign::Right => TextAlign::Left,
+            };
+        }
+        if input.just_pressed(SDL_Scancode::UP) {
+            self.scale = (self.scale + 1.0).min(4.0);
+        }
+        if input.just_pressed(SDL_Scancode::DOWN) {
+            self.scale = (self.scale - 1.0).max(1.0);
+        }
+    }
+
+    fn render(&mut self, frame: &Frame) -> AppResult {
+        self.camera.update(frame);
+
+        let font = &self.font;
+        let title_style = TextStyle {
+            scale: 4.0,
+            ..Default::default()
+        };
+        let help_style = TextStyle {
+            scale: 2.0,
+            color: SDL_FColor {
+                r: 0.6,
+                g: 0.8,
+                b: 1.0,
+                a: 1.0,
+            },
+            max_width: Some(self.camera.width() - MARGIN * 2.0),
+            ..Default::default()
+        };
+        let kerning_style = TextStyle {
+            scale: 3.0,
+            ..Default::default()
+        };
+        let paragraph_style = TextStyle {
+            scale: self.scale,
+            align: self.align,
+            max_width: Some(self.wrap_width),
+            ..Default::default()
+        };
+
+        let mut y = MARGIN;
+        font.push_text(&mut self.text_batch, "Bitmap Text", MARGIN, y, &title_style);
+        y += font.line_height(&title_style) + MARGIN;
+
+        font.push_text(&mut self.text_batch, HELP, MARGIN, y, &help_style);
+        y += font.measure(HELP, &help_style).1 + MARGIN;
+
+        font.push_text(
+            &mut self.text_batch,
+            KERNING_SAMPLE,
+            MARGIN,
+            y,
+            &kerning_style,
+        );
+        y += font.line_height(&kerning_style) + MARGIN;
+
+        // the box the paragraph wraps and aligns within
+        let (_, paragraph_height) = font.measure(PARAGRAPH, &paragraph_style);
+        self.panel_batch.push(GPUSprite {
+            x: MARGIN - 4.0,
+            y: y - 4.0,
+            w: self.wrap_width + 8.0,
+            h: paragraph_height + 8.0,
+            r: 0.2,
+            g: 0.2,
+            b: 0.3,
+            ..Default::default()
+        });
+        font.push_text(&mut self.text_batch, PARAGRAPH, MARGIN, y, &paragraph_style);
+
+        let status = format!(
+            "width {:.0}  {:?}  x{}",
+            self.wrap_width, self.align, self.scale
+        );
+        let status_style = TextStyle {
+            scale: 2.0,
+            align: TextAlign::Right,
+            max_width: Some(self.camera.width() - MARGIN * 2.0),
+            ..Default::default()
+        };
+        let status_y = self.camera.height() - MARGIN - font.line_height(&status_style);
+        font.push_text(
+            &mut self.text_batch,
+            &status,
+            MARGIN,
+            status_y,
+            &status_style,
+        );
+
+        unsafe {
+            if !self.panel_batch.upload(frame.device, frame.command_buffer)
+                || !self.text_batch.upload(frame.device, frame.command_buffer)
+            {
+                return AppResult::Failure;
+            }
+
+            let render_pass = SDL_BeginGPURenderPass(
+                frame.command_buffer,
+                &SDL_GPUColorTargetInfo {
+                    texture: frame.target,
+                    load_op: SDL_GPULoadOp::CLEAR,
+                    store_op: SDL_GPUStoreOp::STORE,
+                    clear_color: SDL_FColor {
+                        r: 0.05,
+                        g: 0.05,
+                        b: 0.1,
+                        a: 1.0,
+                    },
+                    ..Default::default()
+                },
+                1,
+                null_mut(),
+            );
+
+            self.panel_batch.draw(
+                frame.command_buffer,
+                render_pass,
+                self.white_texture,
+                self.sampler,
+                self.camera.matrix(),
+            );
+            self.text_batch.draw(
+                frame.command_buffer,
+                render_pass,
+                self.font.texture(),
+                self.sampler,
+                self.camera.matrix(),
+            );
+
+            SDL_EndGPURenderPass(render_pass);
+        }
+
+        AppResult::Continue
+    }
+}
+
+run_game!(TextExample);
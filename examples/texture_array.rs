@@ -0,0 +1,280 @@
+use std::ffi::{CStr, c_void};
+use std::ptr::null_mut;
+
+use sdl3_main::AppResult;
+use sdl3_sys::everything::*;
+
+use sdl3_experiment::app::{Frame, Game};
+use sdl3_experiment::camera::ScreenCamera;
+use sdl3_experiment::common::*;
+use sdl3_experiment::gpu::GpuContext;
+use sdl3_experiment::image::Image;
+use sdl3_experiment::input::Input;
+use sdl3_experiment::run_game;
+use sdl3_experiment::sprite_batch::{GPUSprite, SpriteBatch};
+
+/// The atlas is 2x2 raviolis; the array texture gets one ravioli per layer.
+const ATLAS: &str = "ravioli_atlas.bmp";
+const ATLAS_CELLS: [(u32, u32); 4] = [(0, 0), (1, 0), (0, 1), (1, 1)];
+
+const SPRITE_SIZE: f32 = 80.0;
+const SPRITE_SPACING: f32 = 104.0;
+const ATLAS_COLUMN_X: f32 = 40.0;
+const ARRAY_COLUMN_X: f32 = 180.0;
+
+/// A quad on the right for TexturedQuadArray.frag, which picks the layer from
+/// the texture coordinate: the top half samples layer 0, the bottom half layer 1.
+const SPLIT_QUAD_VERTICES: [PositionTextureVertex; 4] = [
+    PositionTextureVertex {
+        position: [0.3, 0.6, 0.0],
+        uv: [0.0, 0.0],
+    },
+    PositionTextureVertex {
+        position: [0.9, 0.6, 0.0],
+        uv: [1.0, 0.0],
+    },
+    PositionTextureVertex {
+        position: [0.9, -0.6, 0.0],
+        uv: [1.0, 1.0],
+    },
+    PositionTextureVertex {
+        position: [0.3, -0.6, 0.0],
+        uv: [0.0, 1.0],
+    },
+];
+const SPLIT_QUAD_INDICES: [u16; 6] = [0, 1, 2, 0, 2, 3];
+const SPLIT_LAYERS: [&str; 2] = ["ravioli.bmp", "ravioli_inverted.bmp"];
+
+struct TextureArray {
+    atlas_batch: SpriteBatch,
+    array_batch: SpriteBatch,
+    atlas_texture: *mut SDL_GPUTexture,
+    array_texture: *mut SDL_GPUTexture,
+
+    split_pipeline: *mut SDL_GPUGraphicsPipeline,
+    split_vertices: VertexBuffer<PositionTextureVertex>,
+    split_indices: IndexBuffer<u16>,
+    split_texture: *mut SDL_GPUTexture,
+
+    linear_sampler: *mut SDL_GPUSampler,
+    point_sampler: *mut SDL_GPUSampler,
+    use_linear_filter: bool,
+    camera: ScreenCamera,
+}
+
+impl Game for TextureArray {
+    const TITLE: &'static CStr = c"Texture Array";
+
+    fn init(gpu: &GpuContext) -> Option<Self> {
+        let device = gpu.device;
+        let format = gpu.target_format();
+
+        unsafe {
+            let atlas_batch = SpriteBatch::new(device, format, ATLAS_CELLS.len() as u32)?;
+            let array_batch = SpriteBatch::new_array(device, format, ATLAS_CELLS.len() as u32)?;
+
+            // both sides come from the same pixels, so any difference is down to sampling
+            let Some(atlas) = load_image(ATLAS) else {
+                println!("failed to load {ATLAS}");
+                return None;
+            };
+            let atlas_texture = create_texture_from_pixels(
+                device,
+                atlas.width,
+                atlas.height,
+                atlas.pixels.as_ptr() as *const c_void,
+            );
+            if atlas_texture.is_null() {
+                return None;
+            }
+
+            // the same raviolis as the atlas, cut apart into layers
+            let cell_size = atlas.width / 2;
+            let layers: Vec<Image> = ATLAS_CELLS
+                .iter()
+                .map(|(x, y)| atlas.crop(x * cell_size, y * cell_size, cell_size, cell_size))
+                .collect();
+            let array_texture = create_texture_array_from_images(device, &layers);
+            if array_texture.is_null() {
+                return None;
+            }
+
+            let split_pipeline = create_graphics_pipeline(
+                device,
+                format,
+                "TexturedQuad.vert",
+                "TexturedQuadArray.frag",
+                &VertexInputLayout::new().with_vertices::<PositionTextureVertex>(),
+            );
+            if split_pipeline.is_null() {
+                return None;
+            }
+            let split_vertices = VertexBuffer::new(device, &SPLIT_QUAD_VERTICES)?;
+            let split_indices = IndexBuffer::new(device, &SPLIT_QUAD_INDICES)?;
+            let split_texture = load_texture_array(device, &SPLIT_LAYERS);
+            if split_texture.is_null() {
+                return None;
+            }
+
+            let linear_sampler = create_sampler(
+                device,
+                SDL_GPU_FILTER_LINEAR,
+                SDL_GPU_SAMPLERADDRESSMODE_CLAMP_TO_EDGE,
+            );
+            let point_sampler = create_sampler(
+                device,
+                SDL_GPU_FILTER_NEAREST,
+                SDL_GPU_SAMPLERADDRESSMODE_CLAMP_TO_EDGE,
+            );
+
+            println!(
+                "Left: sprites from an atlas, which bleed into each other with linear filtering"
+            );
+            println!("Middle: the same sprites from the layers of an array texture");
+            println!("Right: TexturedQuadArray.frag, layer 0 on top and layer 1 below");
+            println!("Press F to switch between linear and point filtering");
+
+            Some(Self {
+                atlas_batch,
+                array_batch,
+                atlas_texture,
+                array_texture,
+                split_pipeline,
+                split_vertices,
+                split_indices,
+                split_texture,
+                linear_sampler,
+                point_sampler,
+                use_linear_filter: true,
+                camera: ScreenCamera::new(Self::WINDOW_WIDTH as f32, Self::WINDOW_HEIGHT as f32),
+            })
+        }
+    }
+
+    fn quit(&mut self, device: *mut SDL_GPUDevice) {
+        unsafe {
+            self.atlas_batch.release(device);
+            self.array_batch.release(device);
+            if !self.split_pipeline.is_null() {
+                SDL_ReleaseGPUGraphicsPipeline(device, self.split_pipeline);
+            }
+            self.split_vertices.release(device);
+            self.split_indices.release(device);
+            for texture in [self.atlas_texture, self.array_texture, self.split_texture] {
+                if !texture.is_null() {
+                    SDL_ReleaseGPUTexture(device, texture);
+                }
+            }
+            for sampler in [self.linear_sampler, self.point_sampler] {
+                if !sampler.is_null() {
+                    SDL_ReleaseGPUSampler(device, sampler);
+                }
+            }
+        }
+    }
+
+    fn fixed_update(&mut self, input: &Input) {
+        if input.just_pressed(SDL_Scancode::F) {
+            self.use_linear_filter = !self.use_linear_filter;
+            let filter = if self.use_linear_filter {
+                "linear"
+            } else {
+                "point"
+            };
+            println!("filter: {filter}");
+        }
+    }
+
+    fn render(&mut self, frame: &Frame) -> AppResult {
+        self.camera.update(frame);
+
+        unsafe {
+            for (i, (cell_x, cell_y)) in ATLAS_CELLS.iter().enumerate() {
+                let y = 16.0 + i as f32 * SPRITE_SPACING;
+                self.atlas_batch.push(GPUSprite {
+                    x: ATLAS_COLUMN_X,
+                    y,
+                    w: SPRITE_SIZE,
+                    h: SPRITE_SIZE,
+                    tex_u: *cell_x as f32 * 0.5,
+                    tex_v: *cell_y as f32 * 0.5,
+                    tex_w: 0.5,
+                    tex_h: 0.5,
+                    ..Default::default()
+                });
+                self.array_batch.push(GPUSprite {
+                    x: ARRAY_COLUMN_X,
+                    y,
+                    w: SPRITE_SIZE,
+                    h: SPRITE_SIZE,
+                    layer: i as f32,
+                    ..Default::default()
+                });
+            }
+            if !self.atlas_batch.upload(frame.device, frame.command_buffer)
+                || !self.array_batch.upload(frame.device, frame.command_buffer)
+            {
+                return AppResult::Failure;
+            }
+
+            let render_pass = SDL_BeginGPURenderPass(
+                frame.command_buffer,
+                &SDL_GPUColorTargetInfo {
+                    texture: frame.target,
+                    load_op: SDL_GPULoadOp::CLEAR,
+                    store_op: SDL_GPUStoreOp::STORE,
+                    clear_color: SDL_FColor {
+                        r: 0.1,
+                        g: 0.1,
+                        b: 0.1,
+                        a: 1.0,
+                    },
+                    ..Default::default()
+                },
+                1,
+                null_mut(),
+            );
+
+            let sampler = if self.use_linear_filter {
+                self.linear_sampler
+            } else {
+                self.point_sampler
+            };
+
+            self.atlas_batch.draw(
+                frame.command_buffer,
+                render_pass,
+                self.atlas_texture,
+                sampler,
+                self.camera.matrix(),
+            );
+            self.array_batch.draw(
+                frame.command_buffer,
+                render_pass,
+                self.array_texture,
+                sampler,
+                self.camera.matrix(),
+            );
+
+            SDL_BindGPUGraphicsPipeline(render_pass, self.split_pipeline);
+            self.split_vertices.bind(render_pass, 0);
+            self.split_indices.bind(render_pass);
+            SDL_BindGPUFragmentSamplers(
+                render_pass,
+                0,
+                &SDL_GPUTextureSamplerBinding {
+                    texture: self.split_texture,
+                    sampler,
+                },
+                1,
+            );
+            self.split_indices.draw(render_pass, 1);
+
+            SDL_EndGPURenderPass(render_pass);
+        }
+
+        AppResult::Continue
+    }
+}
+
+run_game!(TextureArray);
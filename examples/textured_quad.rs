@@ -0,0 +1,271 @@
+use std::ffi::{CStr, c_void};
+use std::ptr::null_mut;
+
+use sdl3_main::AppResult;
+use sdl3_sys::everything::*;
+
+use sdl3_experiment::app::{Frame, Game};
+use sdl3_experiment::common::*;
+use sdl3_experiment::gpu::GpuContext;
+use sdl3_experiment::input::Input;
+use sdl3_experiment::run_game;
+
+const fn rgb(r: f32, g: f32, b: f32) -> SDL_FColor {
+    SDL_FColor { r, g, b, a: 1.0 }
+}
+
+/// A full screen quad with a different color in each corner, in clip space.
+const BACKGROUND_VERTICES: [PositionColorVertex; 4] = [
+    PositionColorVertex {
+        position: [-1.0, 1.0, 0.0],
+        color: rgb(0.1, 0.1, 0.3),
+    },
+    PositionColorVertex {
+        position: [1.0, 1.0, 0.0],
+        color: rgb(0.3, 0.1, 0.3),
+    },
+    PositionColorVertex {
+        position: [1.0, -1.0, 0.0],
+        color: rgb(0.1, 0.3, 0.3),
+    },
+    PositionColorVertex {
+        position: [-1.0, -1.0, 0.0],
+        color: rgb(0.1, 0.1, 0.1),
+    },
+];
+
+/// The texture coordinates run past 0..1 so the address mode is visible around the middle tile.
+const QUAD_VERTICES: [PositionTextureVertex; 4] = [
+    PositionTextureVertex {
+        position: [-0.75, 0.75, 0.0],
+        uv: [-1.0, -1.0],
+    },
+    PositionTextureVertex {
+        position: [0.75, 0.75, 0.0],
+        uv: [2.0, -1.0],
+    },
+    PositionTextureVertex {
+        position: [0.75, -0.75, 0.0],
+        uv: [2.0, 2.0],
+    },
+    PositionTextureVertex {
+        position: [-0.75, -0.75, 0.0],
+        uv: [-1.0, 2.0],
+    },
+];
+
+/// Two triangles, shared by both quads.
+const QUAD_INDICES: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+const FILTERS: [(SDL_GPUFilter, &str); 2] = [
+    (SDL_GPU_FILTER_NEAREST, "point"),
+    (SDL_GPU_FILTER_LINEAR, "linear"),
+];
+const ADDRESS_MODES: [(SDL_GPUSamplerAddressMode, &str); 2] = [
+    (SDL_GPU_SAMPLERADDRESSMODE_CLAMP_TO_EDGE, "clamp"),
+    (SDL_GPU_SAMPLERADDRESSMODE_REPEAT, "wrap"),
+];
+
+struct TexturedQuad {
+    background_pipeline: *mut SDL_GPUGraphicsPipeline,
+    quad_pipeline: *mut SDL_GPUGraphicsPipeline,
+    multiply_pipeline: *mut SDL_GPUGraphicsPipeline,
+    background_vertices: VertexBuffer<PositionColorVertex>,
+    quad_vertices: VertexBuffer<PositionTextureVertex>,
+    indices: IndexBuffer<u16>,
+    texture: *mut SDL_GPUTexture,
+    /// Indexed by filter, then address mode.
+    samplers: [[*mut SDL_GPUSampler; ADDRESS_MODES.len()]; FILTERS.len()],
+
+    filter: usize,
+    address_mode: usize,
+    use_multiply_color: bool,
+    time: f32,
+}
+
+impl TexturedQuad {
+    fn print_sampler(&self) {
+        println!(
+            "sampler: {} {}",
+            FILTERS[self.filter].1, ADDRESS_MODES[self.address_mode].1
+        );
+    }
+}
+
+impl Game for TexturedQuad {
+    const TITLE: &'static CStr = c"Textured Quad";
+
+    fn init(gpu: &GpuContext) -> Option<Self> {
+        let device = gpu.device;
+        let format = gpu.target_format();
+
+        unsafe {
+            let color_layout = VertexInputLayout::new().with_vertices::<PositionColorVertex>();
+            let texture_layout = VertexInputLayout::new().with_vertices::<PositionTextureVertex>();
+
+            let background_pipeline = create_graphics_pipeline(
+                device,
+                format,
+                "PositionColor.vert",
+                "SolidColor.frag",
+                &color_layout,
+            );
+            let quad_pipeline = create_graphics_pipeline(
+                device,
+                format,
+                "TexturedQuad.vert",
+                "TexturedQuad.frag",
+                &texture_layout,
+            );
+            let multiply_pipeline = create_graphics_pipeline(
+                device,
+                format,
+                "TexturedQuad.vert",
+                "TexturedQuadWithMultiplyColor.frag",
+                &texture_layout,
+            );
+            if background_pipeline.is_null()
+                || quad_pipeline.is_null()
+                || multiply_pipeline.is_null()
+            {
+                return None;
+            }
+
+            let background_vertices = VertexBuffer::new(device, &BACKGROUND_VERTICES)?;
+            let quad_vertices = VertexBuffer::new(device, &QUAD_VERTICES)?;
+            let indices = IndexBuffer::new(device, &QUAD_INDICES)?;
+
+            let texture = load_texture(device, "ravioli.bmp");
+            if texture.is_null() {
+                return None;
+            }
+
+            let samplers = FILTERS.map(|(filter, _)| {
+                ADDRESS_MODES.map(|(address_mode, _)| create_sampler(device, filter, address_mode))
+            });
+            if samplers.iter().flatten().any(|sampler| sampler.is_null()) {
+                dbg_sdl_error("failed to create samplers");
+                return None;
+            }
+
+            println!("Press F to switch between point and linear filtering");
+            println!("Press W to switch between clamp and wrap addressing");
+            println!("Press M to toggle the multiply color");
+
+            let example = Self {
+                background_pipeline,
+                quad_pipeline,
+                multiply_pipeline,
+                background_vertices,
+                quad_vertices,
+                indices,
+                texture,
+                samplers,
+                filter: 0,
+                address_mode: 0,
+                use_multiply_color: false,
+                time: 0.0,
+            };
+            example.print_sampler();
+
+            Some(example)
+        }
+    }
+
+    fn quit(&mut self, device: *mut SDL_GPUDevice) {
+        unsafe {
+            for pipeline in [
+                self.background_pipeline,
+                self.quad_pipeline,
+                self.multiply_pipeline,
+            ] {
+                if !pipeline.is_null() {
+                    SDL_ReleaseGPUGraphicsPipeline(device, pipeline);
+                }
+            }
+            self.background_vertices.release(device);
+            self.quad_vertices.release(device);
+            self.indices.release(device);
+            if !self.texture.is_null() {
+                SDL_ReleaseGPUTexture(device, self.texture);
+            }
+            for sampler in self.samplers.into_iter().flatten() {
+                SDL_ReleaseGPUSampler(device, sampler);
+            }
+        }
+    }
+
+    fn fixed_update(&mut self, input: &Input) {
+        if input.just_pressed(SDL_Scancode::F) {
+            self.filter = (self.filter + 1) % FILTERS.len();
+            self.print_sampler();
+        }
+        if input.just_pressed(SDL_Scancode::W) {
+            self.address_mode = (self.address_mode + 1) % ADDRESS_MODES.len();
+            self.print_sampler();
+        }
+        if input.just_pressed(SDL_Scancode::M) {
+            self.use_multiply_color = !self.use_multiply_color;
+        }
+
+        self.time += 1.0 / Self::STEPS_PER_SECOND as f32;
+    }
+
+    fn render(&mut self, frame: &Frame) -> AppResult {
+        unsafe {
+            let render_pass = SDL_BeginGPURenderPass(
+                frame.command_buffer,
+                &SDL_GPUColorTargetInfo {
+                    texture: frame.target,
+                    load_op: SDL_GPULoadOp::CLEAR,
+                    store_op: SDL_GPUStoreOp::STORE,
+                    clear_color: rgb(0.0, 0.0, 0.0),
+                    ..Default::default()
+                },
+                1,
+                null_mut(),
+            );
+
+            SDL_BindGPUGraphicsPipeline(render_pass, self.background_pipeline);
+            self.background_vertices.bind(render_pass, 0);
+            self.indices.bind(render_pass);
+            self.indices.draw(render_pass, 1);
+
+            if self.use_multiply_color {
+                SDL_BindGPUGraphicsPipeline(render_pass, self.multiply_pipeline);
+                let pulse = 0.5 + self.time.sin() * 0.5;
+                let multiply_color = SDL_FColor {
+                    r: 1.0,
+                    g: pulse,
+                    b: 1.0 - pulse,
+                    a: 1.0,
+                };
+                SDL_PushGPUFragmentUniformData(
+                    frame.command_buffer,
+                    0,
+                    &multiply_color as *const SDL_FColor as *const c_void,
+                    std::mem::size_of::<SDL_FColor>() as u32,
+                );
+            } else {
+                SDL_BindGPUGraphicsPipeline(render_pass, self.quad_pipeline);
+            }
+            self.quad_vertices.bind(render_pass, 0);
+            SDL_BindGPUFragmentSamplers(
+                render_pass,
+                0,
+                &SDL_GPUTextureSamplerBinding {
+                    texture: self.texture,
+                    sampler: self.samplers[self.filter][self.address_mode],
+                },
+                1,
+            );
+            self.indices.draw(render_pass, 1);
+
+            SDL_EndGPURenderPass(render_pass);
+        }
+
+        AppResult::Continue
+    }
+}
+
+run_game!(TexturedQuad);
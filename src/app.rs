@@ -0,0 +1,609 @@
+use std::ffi::CStr;
+use std::path::PathBuf;
+
+use sdl3_main::AppResult;
+use sdl3_sys::everything::*;
+
+use crate::capture::FrameCapture;
+use crate::common::*;
+use crate::frame_manager::FrameManager;
+use crate::gpu::{GpuContext, TextureDownload, present_mode_name};
+use crate::input::Input;
+use crate::replay::InputRecording;
+use crate::time::{FixedTimestep, ManualClock, SdlClock};
+
+/// The distinctive parts of an example or game; `App` does the rest.
+///
+/// Use `run_game!` to hook an implementation up to sdl3_main's callbacks.
+pub trait Game: Sized {
+    const TITLE: &'static CStr;
+    const STEPS_PER_SECOND: u32 = 60;
+    const WINDOW_WIDTH: i32 = 640;
+    const WINDOW_HEIGHT: i32 = 480;
+    /// How many frames the cpu can submit before waiting for the gpu to finish the oldest.
+    /// More smooths over uneven frames, at the cost of input latency.
+    const MAX_FRAMES_IN_FLIGHT: u32 = 2;
+
+    fn window_flags() -> SDL_WindowFlags {
+        SDL_WindowFlags::default()
+    }
+
+    /// Applied to the window before `init`, so pipelines can target the resulting format.
+    fn swapchain_settings() -> SwapchainSettings {
+        SwapchainSettings::VSYNC
+    }
+
+    /// Create pipelines and other GPU resources. Returning None ends the app.
+    /// Pipelines should target `gpu.target_format()`; `gpu.window` is null when headless.
+    fn init(gpu: &GpuContext) -> Option<Self>;
+
+    /// Release GPU resources; called before the device is destroyed.
+    fn quit(&mut self, _device: *mut SDL_GPUDevice) {}
+
+    /// Advance the simulation by one fixed step.
+    fn fixed_update(&mut self, input: &Input);
+
+    /// Record draw commands for the current frame. Called once per iteration,
+    /// regardless of how many fixed steps ran.
+    fn render(&mut self, frame: &Frame) -> AppResult;
+
+    /// Handle an event before the default handling of keys and quit.
+    fn handle_event(&mut self, _event: &SDL_Event) -> AppResult {
+        AppResult::Continue
+    }
+}
+
+/// The render target and command buffer for one frame.
+pub struct Frame {
+    pub device: *mut SDL_GPUDevice,
+    /// Null when headless.
+    pub window: *mut SDL_Window,
+    pub command_buffer: *mut SDL_GPUCommandBuffer,
+    pub target: *mut SDL_GPUTexture,
+    pub width: u32,
+    pub height: u32,
+    /// Target pixels per logical window coordinate; above 1 on scaled displays.
+    pub pixel_density: f32,
+    /// How the target will be presented; None when headless.
+    pub present_mode: Option<SDL_GPUPresentMode>,
+    /// How far between the last two fixed steps this frame is, for interpolation.
+    pub alpha: f32,
+}
+
+// debug time controls; these act immediately, even while paused, and are never recorded
+const PAUSE_KEY: SDL_Scancode = SDL_Scancode::P;
+const SINGLE_STEP_KEY: SDL_Scancode = SDL_Scancode::PERIOD;
+const SLOWER_KEY: SDL_Scancode = SDL_Scancode::MINUS;
+const FASTER_KEY: SDL_Scancode = SDL_Scancode::EQUALS;
+
+/// Saves the next frame to `SCREENSHOTS_DIR`.
+const SCREENSHOT_KEY: SDL_Scancode = SDL_Scancode::F12;
+const SCREENSHOTS_DIR: &str = "./screenshots";
+
+/// Cycles between windowed, borderless fullscreen and exclusive fullscreen.
+const FULLSCREEN_KEY: SDL_Scancode = SDL_Scancode::F11;
+
+/// Switches between vsync and the game's preferred present mode.
+const VSYNC_KEY: SDL_Scancode = SDL_Scancode::F10;
+
+/// Seeds `SDL_rand` for headless runs and captures that don't replay a seeded recording.
+const DETERMINISTIC_SEED: u64 = 1;
+
+/// The speeds cycled through by the time scale debug keys.
+const TIME_SCALES: [f32; 7] = [0.1, 0.25, 0.5, 1.0, 2.0, 3.0, 4.0];
+
+/// What to do with a frame once the gpu has finished it.
+#[derive(Default)]
+struct PendingFrame {
+    download: Option<TextureDownload>,
+    screenshot: bool,
+    /// The last headless frame, which `--capture` saves.
+    capture: bool,
+}
+
+/// Owns the gpu context and fixed-step loop for a `Game`.
+///
+/// Command line options:
+///   `--record <path>` saves each fixed step's input, and the `SDL_rand` seed, to a json file on quit
+///   `--replay <path>` plays back a recording with its seed, ignoring live key input
+///   `--headless <frames>` renders offscreen without a window, exiting after that many frames;
+///     time advances exactly one fixed step per frame, and `SDL_rand` gets a fixed seed
+///   `--capture <path>` with `--headless`, saves the last frame as a bmp
+///   `--capture-frames <count> <path>` saves every frame until `count`, then exits; see `FrameCapture`
+///     for the output formats. Time advances one fixed step per frame like `--headless`, so
+///     no frames are dropped however slow the capture is
+pub struct App<G: Game> {
+    input: Input,
+    timestep: FixedTimestep,
+    /// Replaces the real clock when headless.
+    manual_clock: Option<ManualClock>,
+    remaining_frames: Option<u32>,
+    /// Set when there was no target to render to, e.g. while the window is minimized.
+    frame_skipped: bool,
+    capture_path: Option<String>,
+    frame_capture: Option<FrameCapture>,
+    screenshot_requested: bool,
+    frames: FrameManager<PendingFrame>,
+    record_path: Option<PathBuf>,
+    game: G,
+    // dropped last
+    gpu: GpuContext,
+}
+
+unsafe impl<G: Game> Send for App<G> {}
+
+impl<G: Game> Drop for App<G> {
+    fn drop(&mut self) {
+        self.finish_all_frames();
+
+        if let (Some(path), Some(recording)) = (&self.record_path, self.input.finish_recording()) {
+            match recording.save(path) {
+                Ok(()) => println!("saved input recording: {}", path.display()),
+                Err(e) => println!("failed to save input recording: {e} {}", path.display()),
+            }
+        }
+
+        if let Some(capture) = &mut self.frame_capture {
+            if let Err(e) = capture.finish() {
+                println!("failed to finish frame capture: {e}");
+            }
+            println!(
+                "captured {} frames: {}",
+                capture.frames_written(),
+                capture.path().display()
+            );
+            if let (true, Some((width, height))) = (capture.is_raw_stream(), capture.frame_size()) {
+                println!(
+                    "encode with: ffmpeg -f rawvideo -pix_fmt rgba -s {width}x{height} -r {} -i {} out.mp4",
+                    self.timestep.steps_per_second(),
+                    capture.path().display()
+                );
+            }
+        }
+
+        self.game.quit(self.gpu.device);
+    }
+}
+
+impl<G: Game> App<G> {
+    pub fn init() -> Option<Self> {
+        let args = Args::parse();
+
+        // SDL_srand(0) seeds from the clock, so headless runs use a fixed seed instead
+        let deterministic = args.headless_frames.is_some() || args.capture_frames.is_some();
+        let mut seed = deterministic.then_some(DETERMINISTIC_SEED);
+
+        let mut input = Input::new();
+        let mut steps_per_second = G::STEPS_PER_SECOND;
+        if let Some(path) = &args.replay_path {
+            let recording = match InputRecording::load(path) {
+                Ok(recording) => recording,
+                Err(e) => {
+                    println!("failed to load input recording: {e} {}", path.display());
+                    return None;
+                }
+            };
+            steps_per_second = recording.steps_per_second;
+            match recording.seed {
+                Some(recorded_seed) => seed = Some(recorded_seed),
+                None if !deterministic => {
+                    println!("the recording has no seed, so random numbers won't match it")
+                }
+                None => {}
+            }
+            input = Input::from_recording(recording);
+        } else if args.record_path.is_some() {
+            let recorded_seed =
+                seed.unwrap_or_else(|| unsafe { SDL_GetPerformanceCounter() }.max(1));
+            seed = Some(recorded_seed);
+            input.start_recording(steps_per_second, recorded_seed);
+        }
+        if let Some(seed) = seed {
+            unsafe { SDL_srand(seed) };
+        }
+
+        let mut frame_capture = None;
+        let mut capture_frame_count = None;
+        if let Some((count, path)) = args.capture_frames {
+            match FrameCapture::new(&path) {
+                Ok(capture) => frame_capture = Some(capture),
+                Err(e) => {
+                    println!("failed to start frame capture: {e} {}", path.display());
+                    return None;
+                }
+            }
+            capture_frame_count = Some(count);
+        }
+
+        let mut gpu = match args.headless_frames {
+            Some(_) => GpuContext::headless(G::WINDOW_WIDTH as u32, G::WINDOW_HEIGHT as u32)?,
+            None => GpuContext::windowed(
+                G::TITLE,
+                G::WINDOW_WIDTH,
+                G::WINDOW_HEIGHT,
+                G::window_flags(),
+            )?,
+        };
+        if !gpu.is_headless() {
+            if gpu.set_swapchain_settings(G::swapchain_settings()) {
+                print_present_mode(&gpu);
+            } else {
+                println!("keeping the default swapchain settings");
+            }
+        }
+        let game = G::init(&gpu)?;
+
+        let mut timestep = FixedTimestep::new(steps_per_second);
+        let manual_clock = deterministic.then(ManualClock::default);
+        if let Some(clock) = &manual_clock {
+            timestep.advance(clock);
+        }
+
+        Some(Self {
+            input,
+            timestep,
+            manual_clock,
+            remaining_frames: capture_frame_count.or(args.headless_frames),
+            frame_skipped: false,
+            capture_path: args.capture_path,
+            frame_capture,
+            screenshot_requested: false,
+            frames: FrameManager::new(G::MAX_FRAMES_IN_FLIGHT),
+            record_path: args.record_path,
+            game,
+            gpu,
+        })
+    }
+
+    pub fn iterate(&mut self) -> AppResult {
+        if let Some(remaining_frames) = &mut self.remaining_frames {
+            if *remaining_frames == 0 {
+                return if self.finish_all_frames() {
+                    AppResult::Success
+                } else {
+                    AppResult::Failure
+                };
+            }
+        }
+
+        // waits while too many frames are in flight
+        while let Some(pending) = unsafe { self.frames.pop_finished(self.gpu.device) } {
+            if !self.finish_frame(pending) {
+                return AppResult::Failure;
+            }
+        }
+
+        let steps = match &mut self.manual_clock {
+            Some(clock) => {
+                // a frame that wasn't rendered is retried at the same time, so none are missing
+                if !self.frame_skipped {
+                    clock.advance_ns(self.timestep.step_ns());
+                }
+                self.timestep.advance(clock)
+            }
+            None => self.timestep.advance(&SdlClock),
+        };
+        for _ in 0..steps {
+            self.input.begin_step();
+            self.game.fixed_update(&self.input);
+            self.input.end_step();
+        }
+
+        unsafe {
+            let command_buffer = SDL_AcquireGPUCommandBuffer(self.gpu.device);
+            if command_buffer.is_null() {
+                dbg_sdl_error("failed to acquire command buffer");
+                return AppResult::Failure;
+            }
+
+            // the only swapchain texture this iteration, however many fixed steps ran
+            let Some(target) = self.gpu.acquire_target(command_buffer) else {
+                SDL_CancelGPUCommandBuffer(command_buffer);
+                return AppResult::Failure;
+            };
+
+            let mut pending = PendingFrame::default();
+            self.frame_skipped = target.texture.is_null();
+            if !target.texture.is_null() {
+                // only frames that are rendered count towards --headless and --capture-frames
+                if let Some(remaining_frames) = &mut self.remaining_frames {
+                    *remaining_frames -= 1;
+                }
+
+                let frame = Frame {
+                    device: self.gpu.device,
+                    window: self.gpu.window,
+                    command_buffer,
+                    target: target.texture,
+                    width: target.width,
+                    height: target.height,
+                    pixel_density: self.gpu.pixel_density(),
+                    present_mode: self.gpu.present_mode(),
+                    alpha: self.timestep.alpha(),
+                };
+                let result = self.game.render(&frame);
+                if result != AppResult::Continue {
+                    if !self.submit_frame(command_buffer, pending) {
+                        return AppResult::Failure;
+                    }
+                    return result;
+                }
+
+                if self.screenshot_requested
+                    || self.is_capture_frame()
+                    || self.frame_capture.is_some()
+                {
+                    pending = PendingFrame {
+                        download: TextureDownload::record(
+                            self.gpu.device,
+                            command_buffer,
+                            target.texture,
+                            self.gpu.target_format(),
+                            target.width,
+                            target.height,
+                        ),
+                        screenshot: std::mem::take(&mut self.screenshot_requested),
+                        capture: self.is_capture_frame(),
+                    };
+                    if pending.download.is_none() && self.frame_capture.is_some() {
+                        println!("failed to capture a frame");
+                        self.submit_frame(command_buffer, pending);
+                        return AppResult::Failure;
+                    }
+                }
+            }
+
+            if !self.submit_frame(command_buffer, pending) {
+                return AppResult::Failure;
+            }
+        }
+
+        AppResult::Continue
+    }
+
+    /// Submits the frame's command buffer, releasing its download if that fails.
+    unsafe fn submit_frame(
+        &mut self,
+        command_buffer: *mut SDL_GPUCommandBuffer,
+        pending: PendingFrame,
+    ) -> bool {
+        match self.frames.submit(command_buffer, pending) {
+            Ok(()) => true,
+            Err(pending) => {
+                if let Some(download) = pending.download {
+                    download.release(self.gpu.device);
+                }
+                false
+            }
+        }
+    }
+
+    /// Saves a finished frame's screenshot and captures. Returns false if a capture failed.
+    fn finish_frame(&mut self, pending: PendingFrame) -> bool {
+        let Some(download) = pending.download else {
+            return true;
+        };
+        let Some(image) = (unsafe { download.finish(self.gpu.device) }) else {
+            return false;
+        };
+
+        if pending.screenshot {
+            let path = format!("{SCREENSHOTS_DIR}/{}.bmp", timestamp());
+            if image.save_bmp(&path) {
+                println!("saved screenshot {path}");
+            }
+        }
+
+        if let Some(capture) = &mut self.frame_capture {
+            if let Err(e) = capture.write_frame(&image) {
+                println!("failed to capture frame {}: {e}", capture.frames_written());
+                return false;
+            }
+        }
+
+        if pending.capture {
+            if let Some(path) = &self.capture_path {
+                if !image.save_bmp(path) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Waits for the gpu to finish every submitted frame, and saves whatever they captured.
+    fn finish_all_frames(&mut self) -> bool {
+        let finished = unsafe { self.frames.wait_idle(self.gpu.device) };
+        // keep going after a failure, so every download is released
+        let mut ok = true;
+        for pending in finished {
+            ok &= self.finish_frame(pending);
+        }
+        ok
+    }
+
+    pub fn event(&mut self, event: &SDL_Event) -> AppResult {
+        let result = self.game.handle_event(event);
+        if result != AppResult::Continue {
+            return result;
+        }
+
+        unsafe {
+            match SDL_EventType(event.r#type) {
+                SDL_EVENT_QUIT => AppResult::Success,
+
+                SDL_EVENT_KEY_DOWN => {
+                    if event.key.repeat {
+                        return AppResult::Continue;
+                    }
+                    if event.key.scancode == SCREENSHOT_KEY {
+                        self.screenshot_requested = true;
+                    } else if event.key.scancode == FULLSCREEN_KEY {
+                        let mode = self.gpu.fullscreen_mode().next();
+                        if self.gpu.set_fullscreen_mode(mode) {
+                            println!("fullscreen: {mode:?}");
+                        }
+                    } else if event.key.scancode == VSYNC_KEY {
+                        if self.gpu.set_vsync(!self.gpu.is_vsync()) {
+                            print_present_mode(&self.gpu);
+                        } else {
+                            println!("no other present mode is available");
+                        }
+                    } else if !self.handle_time_control_key(event.key.scancode) {
+                        self.input.key_pressed(event.key.scancode);
+                    }
+                    AppResult::Continue
+                }
+
+                SDL_EVENT_KEY_UP => {
+                    self.input.key_released(event.key.scancode);
+                    AppResult::Continue
+                }
+
+                // displays can differ in which present modes they support
+                SDL_EVENT_WINDOW_DISPLAY_CHANGED => {
+                    if self.gpu.revalidate_present_mode() {
+                        print_present_mode(&self.gpu);
+                    }
+                    AppResult::Continue
+                }
+
+                _ => AppResult::Continue,
+            }
+        }
+    }
+
+    /// Whether this is the last headless frame, which `--capture` saves.
+    fn is_capture_frame(&self) -> bool {
+        self.capture_path.is_some() && self.remaining_frames == Some(0)
+    }
+
+    /// Returns true if the key was one of the debug time controls.
+    fn handle_time_control_key(&mut self, scan_code: SDL_Scancode) -> bool {
+        match scan_code {
+            PAUSE_KEY => self.timestep.toggle_paused(),
+            SINGLE_STEP_KEY => self.timestep.step_once(),
+            SLOWER_KEY | FASTER_KEY => {
+                let current = self.timestep.time_scale();
+                let next = if scan_code == SLOWER_KEY {
+                    TIME_SCALES.iter().rev().find(|s| **s < current)
+                } else {
+                    TIME_SCALES.iter().find(|s| **s > current)
+                };
+                if let Some(next) = next {
+                    self.timestep.set_time_scale(*next);
+                }
+            }
+            _ => return false,
+        }
+
+        if self.timestep.is_paused() {
+            println!("time: paused ({}x)", self.timestep.time_scale());
+        } else {
+            println!("time: running ({}x)", self.timestep.time_scale());
+        }
+
+        true
+    }
+}
+
+fn print_present_mode(gpu: &GpuContext) {
+    if let Some(present_mode) = gpu.present_mode() {
+        println!("present mode: {}", present_mode_name(present_mode));
+    }
+}
+
+/// The local time as `YYYY-MM-DD_HH-MM-SS-mmm`, for file names.
+fn timestamp() -> String {
+    unsafe {
+        let mut now = 0;
+        let mut time = SDL_DateTime::default();
+        if !SDL_GetCurrentTime(&mut now) || !SDL_TimeToDateTime(now, &mut time, true) {
+            dbg_sdl_error("failed to get the current time");
+        }
+
+        format!(
+            "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}-{:03}",
+            time.year,
+            time.month,
+            time.day,
+            time.hour,
+            time.minute,
+            time.second,
+            time.nanosecond / 1_000_000
+        )
+    }
+}
+
+#[derive(Default)]
+struct Args {
+    record_path: Option<PathBuf>,
+    replay_path: Option<PathBuf>,
+    headless_frames: Option<u32>,
+    capture_path: Option<String>,
+    capture_frames: Option<(u32, PathBuf)>,
+}
+
+impl Args {
+    fn parse() -> Self {
+        let mut parsed = Self::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--record" => parsed.record_path = args.next().map(PathBuf::from),
+                "--replay" => parsed.replay_path = args.next().map(PathBuf::from),
+                "--capture" => parsed.capture_path = args.next(),
+                "--capture-frames" => {
+                    let count = args.next().and_then(|count| count.parse().ok());
+                    let path = args.next().map(PathBuf::from);
+                    parsed.capture_frames = count.zip(path);
+                    if parsed.capture_frames.is_none() {
+                        println!("--capture-frames expects a frame count and a path");
+                    }
+                }
+                "--headless" => {
+                    parsed.headless_frames = args.next().and_then(|frames| frames.parse().ok());
+                    if parsed.headless_frames.is_none() {
+                        println!("--headless expects a frame count");
+                    }
+                }
+                other => println!("ignoring unrecognized argument: {other}"),
+            }
+        }
+
+        parsed
+    }
+}
+
+/// Generates the sdl3_main callbacks for a `Game`.
+///
+/// ```ignore
+/// run_game!(MyGame);
+/// ```
+#[macro_export]
+macro_rules! run_game {
+    ($game:ty) => {
+        #[::sdl3_main::app_init]
+        fn app_init() -> Option<Box<::std::sync::Mutex<$crate::app::App<$game>>>> {
+            let app = $crate::app::App::<$game>::init()?;
+            Some(Box::new(::std::sync::Mutex::new(app)))
+        }
+
+        #[::sdl3_main::app_iterate]
+        fn app_iterate(app: &mut $crate::app::App<$game>) -> ::sdl3_main::AppResult {
+            app.iterate()
+        }
+
+        #[::sdl3_main::app_event]
+        fn app_event(
+            app: &mut $crate::app::App<$game>,
+            event: &::sdl3_sys::everything::SDL_Event,
+        ) -> ::sdl3_main::AppResult {
+            app.event(event)
+        }
+
+        #[::sdl3_main::app_quit]
+        fn app_quit() {}
+    };
+}
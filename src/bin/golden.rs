@@ -0,0 +1,181 @@
+use std::process::Command;
+
+use sdl3_experiment::image::Image;
+
+const GOLDEN_DIR: &str = "./content/golden";
+const INPUTS_DIR: &str = "./content/golden/inputs";
+const OUTPUT_DIR: &str = "./target/golden";
+
+/// How far a color channel may drift from the reference before a pixel counts as different.
+/// Software and hardware rasterizers disagree slightly on edges and blending.
+const DEFAULT_TOLERANCE: u8 = 2;
+
+struct GoldenCase {
+    name: &'static str,
+    example: &'static str,
+    frames: u32,
+    /// An input recording in `INPUTS_DIR` that puts the example in the state under test.
+    replay: Option<&'static str>,
+}
+
+const CASES: [GoldenCase; 5] = [
+    GoldenCase {
+        name: "basic_triangle_fill",
+        example: "basic_triangle",
+        frames: 2,
+        replay: None,
+    },
+    GoldenCase {
+        name: "basic_triangle_wireframe",
+        example: "basic_triangle",
+        frames: 2,
+        replay: Some("basic_triangle_wireframe.json"),
+    },
+    GoldenCase {
+        name: "basic_triangle_small_viewport",
+        example: "basic_triangle",
+        frames: 2,
+        replay: Some("basic_triangle_small_viewport.json"),
+    },
+    GoldenCase {
+        name: "basic_triangle_scissor",
+        example: "basic_triangle",
+        frames: 2,
+        replay: Some("basic_triangle_scissor.json"),
+    },
+    GoldenCase {
+        name: "pull_sprite_batch",
+        example: "pull_sprite_batch",
+        frames: 3,
+        replay: None,
+    },
+];
+
+/// Renders each example headless and compares the last frame against its reference bmp
+/// in `content/golden`. On a mismatch, the actual frame and a diff image are written to
+/// `target/golden`.
+///
+/// Options:
+///   `--bless` overwrites the references with the current output
+///   `--tolerance <0-255>` the allowed per-channel difference, default 2
+///
+/// Needs a Vulkan driver; set `VK_ICD_FILENAMES` to use lavapipe on machines without a GPU.
+pub fn main() {
+    let mut bless = false;
+    let mut tolerance = DEFAULT_TOLERANCE;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bless" => bless = true,
+            "--tolerance" => {
+                tolerance = args
+                    .next()
+                    .and_then(|t| t.parse().ok())
+                    .expect("--tolerance needs a value from 0 to 255");
+            }
+            _ => println!("unknown argument: {arg}"),
+        }
+    }
+
+    let cargo = std::env::var("CARGO").unwrap_or("cargo".to_string());
+    let status = Command::new(&cargo)
+        .args(["build", "--examples"])
+        .status()
+        .expect("failed to run cargo");
+    if !status.success() {
+        std::process::exit(1);
+    }
+
+    let mut failures = Vec::new();
+    for case in &CASES {
+        let actual_path = format!("{OUTPUT_DIR}/{}.bmp", case.name);
+        let reference_path = format!("{GOLDEN_DIR}/{}.bmp", case.name);
+
+        let mut command = Command::new(&cargo);
+        command
+            .args(["run", "--quiet", "--example", case.example, "--"])
+            .args(["--headless", &case.frames.to_string()])
+            .args(["--capture", &actual_path]);
+        if let Some(replay) = case.replay {
+            command.args(["--replay", &format!("{INPUTS_DIR}/{replay}")]);
+        }
+        let status = command
+            .status()
+            .unwrap_or_else(|e| panic!("failed to run example: {} {e}", case.example));
+        if !status.success() {
+            println!("FAIL {}: example exited with {status}", case.name);
+            failures.push(case.name);
+            continue;
+        }
+
+        if bless {
+            std::fs::copy(&actual_path, &reference_path)
+                .unwrap_or_else(|e| panic!("failed to bless {reference_path}: {e}"));
+            println!("blessed {}", case.name);
+            continue;
+        }
+
+        let (Some(actual), Some(reference)) = (
+            Image::load_bmp(&actual_path),
+            Image::load_bmp(&reference_path),
+        ) else {
+            println!(
+                "FAIL {}: missing image, run with --bless to create it",
+                case.name
+            );
+            failures.push(case.name);
+            continue;
+        };
+
+        if actual.width != reference.width || actual.height != reference.height {
+            println!(
+                "FAIL {}: size {}x{}, expected {}x{}",
+                case.name, actual.width, actual.height, reference.width, reference.height
+            );
+            failures.push(case.name);
+            continue;
+        }
+
+        let (diff, different_pixels) = diff_images(&actual, &reference, tolerance);
+        if different_pixels == 0 {
+            println!("ok {}", case.name);
+        } else {
+            let diff_path = format!("{OUTPUT_DIR}/{}.diff.bmp", case.name);
+            diff.save_bmp(&diff_path);
+            println!(
+                "FAIL {}: {different_pixels} pixels differ, see {diff_path}",
+                case.name
+            );
+            failures.push(case.name);
+        }
+    }
+
+    if !failures.is_empty() {
+        println!("{} of {} golden images failed", failures.len(), CASES.len());
+        std::process::exit(1);
+    }
+}
+
+/// Pixels outside the tolerance are red; the rest are a faded grayscale of the actual image.
+fn diff_images(actual: &Image, reference: &Image, tolerance: u8) -> (Image, usize) {
+    let mut pixels = Vec::with_capacity(actual.pixels.len());
+    let mut different_pixels = 0;
+
+    for (a, r) in actual.pixels.chunks(4).zip(reference.pixels.chunks(4)) {
+        let differs = a.iter().zip(r).any(|(a, r)| a.abs_diff(*r) > tolerance);
+        if differs {
+            different_pixels += 1;
+            pixels.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let gray = ((a[0] as u32 + a[1] as u32 + a[2] as u32) / 12) as u8;
+            pixels.extend_from_slice(&[gray, gray, gray, 255]);
+        }
+    }
+
+    let diff = Image {
+        width: actual.width,
+        height: actual.height,
+        pixels,
+    };
+    (diff, different_pixels)
+}
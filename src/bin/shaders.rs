@@ -1,33 +1,95 @@
 use std::process::Command;
 
-const SHADERCROSS: &'static str = "./bin/shadercross";
+const SHADERCROSS: &str = "./bin/shadercross";
 
-const SHADERS_SOURCE_DIR: &'static str = "./content/shaders/source";
-const SHADERS_COMPILED_DIR: &'static str = "./content/shaders/compiled";
+const SHADERS_SOURCE_DIR: &str = "./content/shaders/source";
+const SHADERS_COMPILED_DIR: &str = "./content/shaders/compiled";
+
+const OUT_FORMATS: [&str; 4] = ["spv", "dxil", "json", "msl"];
+
+/// Each source is copied here once it compiles, so `--check` can tell when the compiled
+/// outputs are missing or older than the source.
+const COMPILED_SOURCE_FORMAT: &str = "hlsl";
 
 /// Compiles each hlsl source shader into the 3 supported output formats and json metadata.
 /// Depends on the precompiled shadercross cli in `/bin`.
+///
+/// Options:
+///   `--check` compiles nothing, and fails if any source has changed since it was compiled
 pub fn main() {
-    let shader_source_dir = std::fs::read_dir(SHADERS_SOURCE_DIR).unwrap();
+    let check = std::env::args().skip(1).any(|arg| arg == "--check");
 
-    for entry in shader_source_dir {
-        let entry = entry.unwrap();
+    let mut source_paths: Vec<_> = std::fs::read_dir(SHADERS_SOURCE_DIR)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    source_paths.sort();
 
-        let in_path = entry.path().display().to_string();
+    let mut failures = Vec::new();
+    for in_path in source_paths {
+        let file_name = in_path.file_name().unwrap().to_string_lossy().into_owned();
+        let compiled_source_path =
+            format!("{SHADERS_COMPILED_DIR}/{COMPILED_SOURCE_FORMAT}/{file_name}");
 
-        let file_name = entry.file_name();
-        let file_name = file_name.to_string_lossy();
+        if check {
+            if !is_up_to_date(&in_path, &compiled_source_path, &file_name) {
+                println!("out of date: {file_name}");
+                failures.push(file_name);
+            }
+            continue;
+        }
 
-        for out_format in ["spv", "dxil", "json", "msl"] {
+        let mut compiled = true;
+        for out_format in OUT_FORMATS {
             let out_file_name = file_name.replace("hlsl", out_format);
             let out_path = format!("{SHADERS_COMPILED_DIR}/{out_format}/{out_file_name}");
 
-            Command::new(SHADERCROSS)
+            let output = Command::new(SHADERCROSS)
                 .arg(&in_path)
                 .arg("--output")
                 .arg(&out_path)
                 .output()
-                .expect(&format!("failed to compile shader: {in_path}"));
+                .unwrap_or_else(|e| panic!("failed to run {SHADERCROSS}: {e}"));
+            if !output.status.success() {
+                println!(
+                    "failed to compile {file_name} to {out_format}:\n{}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                compiled = false;
+            }
+        }
+
+        if compiled {
+            std::fs::create_dir_all(format!("{SHADERS_COMPILED_DIR}/{COMPILED_SOURCE_FORMAT}"))
+                .unwrap();
+            std::fs::copy(&in_path, &compiled_source_path).unwrap();
+        } else {
+            failures.push(file_name);
         }
     }
+
+    if !failures.is_empty() {
+        if check {
+            println!(
+                "{} shaders need compiling, run `just shaders`",
+                failures.len()
+            );
+        } else {
+            println!("{} shaders failed to compile", failures.len());
+        }
+        std::process::exit(1);
+    }
+}
+
+/// Whether every output exists, and the source matches the copy made when it was compiled.
+fn is_up_to_date(in_path: &std::path::Path, compiled_source_path: &str, file_name: &str) -> bool {
+    let outputs_exist = OUT_FORMATS.iter().all(|out_format| {
+        let out_file_name = file_name.replace("hlsl", out_format);
+        std::path::Path::new(&format!(
+            "{SHADERS_COMPILED_DIR}/{out_format}/{out_file_name}"
+        ))
+        .exists()
+    });
+
+    outputs_exist && std::fs::read(in_path).ok() == std::fs::read(compiled_source_path).ok()
 }
@@ -0,0 +1,124 @@
+use std::ptr::null_mut;
+
+use sdl3_sys::everything::*;
+
+use crate::app::Frame;
+use crate::common::*;
+use crate::grid::Grid;
+use crate::sprite_batch::{GPUSprite, SpriteBatch};
+
+/// Draws a `Grid` as solid colored blocks in a single sprite batch draw.
+pub struct BlockRenderer {
+    batch: SpriteBatch,
+    texture: *mut SDL_GPUTexture,
+    sampler: *mut SDL_GPUSampler,
+    block_size: i32,
+    /// Pixels left empty between neighboring blocks.
+    pub gap: f32,
+}
+
+impl BlockRenderer {
+    pub unsafe fn new(
+        device: *mut SDL_GPUDevice,
+        target_format: SDL_GPUTextureFormat,
+        block_size: i32,
+        max_blocks: u32,
+    ) -> Option<Self> {
+        let batch = SpriteBatch::new(device, target_format, max_blocks)?;
+
+        let texture = create_white_texture(device);
+        if texture.is_null() {
+            return None;
+        }
+
+        let sampler = create_sampler(
+            device,
+            SDL_GPU_FILTER_NEAREST,
+            SDL_GPU_SAMPLERADDRESSMODE_CLAMP_TO_EDGE,
+        );
+
+        Some(Self {
+            batch,
+            texture,
+            sampler,
+            block_size,
+            gap: 1.0,
+        })
+    }
+
+    pub unsafe fn release(&mut self, device: *mut SDL_GPUDevice) {
+        self.batch.release(device);
+        if !self.texture.is_null() {
+            SDL_ReleaseGPUTexture(device, self.texture);
+        }
+        if !self.sampler.is_null() {
+            SDL_ReleaseGPUSampler(device, self.sampler);
+        }
+    }
+
+    /// Clears the frame's target and draws every cell that `color_of` gives a color.
+    pub unsafe fn draw_grid<T>(
+        &mut self,
+        frame: &Frame,
+        grid: &Grid<T>,
+        clear_color: SDL_FColor,
+        color_of: impl Fn(&T) -> Option<SDL_FColor>,
+    ) -> bool {
+        let size = self.block_size as f32 - self.gap;
+        for (pos, cell) in grid.iter() {
+            let Some(color) = color_of(cell) else {
+                continue;
+            };
+
+            let (x, y) = grid.cell_to_pixel(pos, self.block_size);
+            self.batch.push(GPUSprite {
+                x: x + self.gap / 2.0,
+                y: y + self.gap / 2.0,
+                w: size,
+                h: size,
+                r: color.r,
+                g: color.g,
+                b: color.b,
+                a: color.a,
+                ..Default::default()
+            });
+        }
+
+        if !self.batch.upload(frame.device, frame.command_buffer) {
+            return false;
+        }
+
+        let render_pass = SDL_BeginGPURenderPass(
+            frame.command_buffer,
+            &SDL_GPUColorTargetInfo {
+                texture: frame.target,
+                load_op: SDL_GPULoadOp::CLEAR,
+                store_op: SDL_GPUStoreOp::STORE,
+                clear_color,
+                ..Default::default()
+            },
+            1,
+            null_mut(),
+        );
+
+        let camera = Matrix4x4::create_orthographic_off_center(
+            0.0,
+            (grid.width() * self.block_size) as f32,
+            (grid.height() * self.block_size) as f32,
+            0.0,
+            0.0,
+            -1.0,
+        );
+        self.batch.draw(
+            frame.command_buffer,
+            render_pass,
+            self.texture,
+            self.sampler,
+            &camera,
+        );
+
+        SDL_EndGPURenderPass(render_pass);
+
+        true
+    }
+}
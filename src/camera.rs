@@ -0,0 +1,353 @@
+use sdl3_sys::everything::*;
+
+use crate::app::Frame;
+use crate::common::Matrix4x4;
+
+/// An orthographic projection with the origin at the top left, measured in logical window
+/// coordinates (points) rather than pixels, so things keep their size on scaled displays.
+///
+/// Call `update` with each frame: the projection is rebuilt whenever the target's size or
+/// pixel density changes, e.g. after the window is resized or goes fullscreen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScreenCamera {
+    width: f32,
+    height: f32,
+    pixel_density: f32,
+    matrix: Matrix4x4,
+}
+
+impl ScreenCamera {
+    /// `width` and `height` are the expected size until the first `update`, e.g. the window size.
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            pixel_density: 1.0,
+            matrix: Self::projection(width, height),
+        }
+    }
+
+    /// Returns true if the projection was rebuilt.
+    pub fn update(&mut self, frame: &Frame) -> bool {
+        let pixel_density = frame.pixel_density.max(f32::EPSILON);
+        let width = frame.width as f32 / pixel_density;
+        let height = frame.height as f32 / pixel_density;
+        if width == self.width && height == self.height && pixel_density == self.pixel_density {
+            return false;
+        }
+
+        self.width = width;
+        self.height = height;
+        self.pixel_density = pixel_density;
+        self.matrix = Self::projection(width, height);
+        true
+    }
+
+    pub fn matrix(&self) -> &Matrix4x4 {
+        &self.matrix
+    }
+
+    /// The visible width in points.
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// The visible height in points.
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn pixel_density(&self) -> f32 {
+        self.pixel_density
+    }
+
+    fn projection(width: f32, height: f32) -> Matrix4x4 {
+        Matrix4x4::create_orthographic_off_center(0.0, width, height, 0.0, 0.0, -1.0)
+    }
+}
+
+/// A view of a 2D world that can pan, zoom, rotate, follow a target and shake.
+///
+/// `x` and `y` are the world position at the center of the screen. At a zoom of 1, one world
+/// unit is one point. Call `update` with each frame to track the target size like `ScreenCamera`,
+/// and `fixed_update` once per fixed step for following and shake, so both stay deterministic.
+#[derive(Clone, Copy, Debug)]
+pub struct Camera2D {
+    pub x: f32,
+    pub y: f32,
+    pub zoom: f32,
+    /// Radians, clockwise on screen.
+    pub rotation: f32,
+
+    /// The world position to move towards each fixed step.
+    pub target: Option<(f32, f32)>,
+    /// The fraction of the distance to the target covered each fixed step; 1 snaps to it.
+    pub follow_smoothing: f32,
+    /// The area the view is kept inside of, if any. A view larger than the bounds is centered on them.
+    pub bounds: Option<SDL_FRect>,
+
+    /// How much the camera is shaking, from 0 to 1. The shake grows with the square of this.
+    pub trauma: f32,
+    /// Trauma lost per fixed step.
+    pub trauma_decay: f32,
+    /// The largest shake offset, in points.
+    pub max_shake_offset: f32,
+    /// The largest shake rotation, in radians.
+    pub max_shake_angle: f32,
+    shake_x: f32,
+    shake_y: f32,
+    shake_angle: f32,
+
+    screen: ScreenCamera,
+}
+
+impl Camera2D {
+    /// `width` and `height` are the expected screen size until the first `update`.
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            x: width * 0.5,
+            y: height * 0.5,
+            zoom: 1.0,
+            rotation: 0.0,
+            target: None,
+            follow_smoothing: 0.1,
+            bounds: None,
+            trauma: 0.0,
+            trauma_decay: 0.02,
+            max_shake_offset: 16.0,
+            max_shake_angle: 0.1,
+            shake_x: 0.0,
+            shake_y: 0.0,
+            shake_angle: 0.0,
+            screen: ScreenCamera::new(width, height),
+        }
+    }
+
+    /// Returns true if the screen size changed.
+    pub fn update(&mut self, frame: &Frame) -> bool {
+        let resized = self.screen.update(frame);
+        if resized {
+            self.clamp_to_bounds();
+        }
+        resized
+    }
+
+    /// Moves towards the target, keeps the view inside the bounds, and rolls a new shake.
+    pub fn fixed_update(&mut self) {
+        if let Some((target_x, target_y)) = self.target {
+            let t = self.follow_smoothing.clamp(0.0, 1.0);
+            self.x += (target_x - self.x) * t;
+            self.y += (target_y - self.y) * t;
+        }
+        self.clamp_to_bounds();
+
+        self.trauma = (self.trauma - self.trauma_decay).max(0.0);
+        let shake = self.trauma * self.trauma;
+        if shake > 0.0 {
+            unsafe {
+                self.shake_x = self.max_shake_offset * shake * (SDL_randf() * 2.0 - 1.0);
+                self.shake_y = self.max_shake_offset * shake * (SDL_randf() * 2.0 - 1.0);
+                self.shake_angle = self.max_shake_angle * shake * (SDL_randf() * 2.0 - 1.0);
+            }
+        } else {
+            self.shake_x = 0.0;
+            self.shake_y = 0.0;
+            self.shake_angle = 0.0;
+        }
+    }
+
+    /// Adds to the trauma, up to 1.
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    /// Moves straight to the target, e.g. after a level loads.
+    pub fn snap_to_target(&mut self) {
+        if let Some((target_x, target_y)) = self.target {
+            self.x = target_x;
+            self.y = target_y;
+        }
+        self.clamp_to_bounds();
+    }
+
+    pub fn screen(&self) -> &ScreenCamera {
+        &self.screen
+    }
+
+    /// The world to clip space transform to push as a vertex uniform, shake included.
+    pub fn view_projection(&self) -> Matrix4x4 {
+        self.view().multiply(self.screen.matrix())
+    }
+
+    /// Converts a position in points, e.g. the mouse, to world coordinates. Ignores shake.
+    pub fn screen_to_world(&self, x: f32, y: f32) -> (f32, f32) {
+        let (sin, cos) = self.rotation.sin_cos();
+        let zoom = self.zoom.max(f32::EPSILON);
+        let rx = (x - self.screen.width() * 0.5) / zoom;
+        let ry = (y - self.screen.height() * 0.5) / zoom;
+        (self.x + rx * cos - ry * sin, self.y + rx * sin + ry * cos)
+    }
+
+    /// Converts a world position to points on the screen. Ignores shake.
+    pub fn world_to_screen(&self, x: f32, y: f32) -> (f32, f32) {
+        let (sin, cos) = self.rotation.sin_cos();
+        let dx = x - self.x;
+        let dy = y - self.y;
+        (
+            (dx * cos + dy * sin) * self.zoom + self.screen.width() * 0.5,
+            (dy * cos - dx * sin) * self.zoom + self.screen.height() * 0.5,
+        )
+    }
+
+    /// Half the size of the world area the screen covers, including any extra from rotation.
+    pub fn visible_half_extents(&self) -> (f32, f32) {
+        let zoom = self.zoom.max(f32::EPSILON);
+        let half_width = self.screen.width() * 0.5 / zoom;
+        let half_height = self.screen.height() * 0.5 / zoom;
+        let (sin, cos) = self.rotation.sin_cos();
+        let (sin, cos) = (sin.abs(), cos.abs());
+        (
+            half_width * cos + half_height * sin,
+            half_width * sin + half_height * cos,
+        )
+    }
+
+    fn clamp_to_bounds(&mut self) {
+        let Some(bounds) = self.bounds else {
+            return;
+        };
+
+        let (half_width, half_height) = self.visible_half_extents();
+        self.x = clamp_to_range(self.x, bounds.x, bounds.w, half_width);
+        self.y = clamp_to_range(self.y, bounds.y, bounds.h, half_height);
+    }
+
+    /// World to screen points; the inverse of `screen_to_world`, plus shake.
+    fn view(&self) -> Matrix4x4 {
+        let (sin, cos) = (self.rotation + self.shake_angle).sin_cos();
+        let zoom = self.zoom;
+        let center_x = self.screen.width() * 0.5 + self.shake_x;
+        let center_y = self.screen.height() * 0.5 + self.shake_y;
+
+        Matrix4x4 {
+            m11: zoom * cos,
+            m12: -zoom * sin,
+            m21: zoom * sin,
+            m22: zoom * cos,
+            m41: center_x - zoom * (self.x * cos + self.y * sin),
+            m42: center_y - zoom * (self.y * cos - self.x * sin),
+            ..Matrix4x4::IDENTITY
+        }
+    }
+}
+
+/// Keeps a view of `half_size` around `center` inside `start..start + length`,
+/// or centers it when it doesn't fit.
+fn clamp_to_range(center: f32, start: f32, length: f32, half_size: f32) -> f32 {
+    if length <= half_size * 2.0 {
+        start + length * 0.5
+    } else {
+        center.clamp(start + half_size, start + length - half_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: (f32, f32), expected: (f32, f32)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-3 && (actual.1 - expected.1).abs() < 1e-3,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    /// A 640x480 screen looking at (1000, -250), zoomed in and rotated.
+    fn moved_camera() -> Camera2D {
+        let mut camera = Camera2D::new(640.0, 480.0);
+        camera.x = 1000.0;
+        camera.y = -250.0;
+        camera.zoom = 2.5;
+        camera.rotation = 0.3;
+        camera
+    }
+
+    #[test]
+    fn screen_center_is_the_camera_position() {
+        let camera = moved_camera();
+        assert_near(camera.screen_to_world(320.0, 240.0), (1000.0, -250.0));
+        assert_near(camera.world_to_screen(1000.0, -250.0), (320.0, 240.0));
+    }
+
+    #[test]
+    fn screen_world_round_trip() {
+        let mut camera = moved_camera();
+        for rotation in [0.0, 0.3, -2.0] {
+            camera.rotation = rotation;
+            for (x, y) in [(0.0, 0.0), (640.0, 480.0), (17.0, 391.5), (-50.0, 900.0)] {
+                let (world_x, world_y) = camera.screen_to_world(x, y);
+                assert_near(camera.world_to_screen(world_x, world_y), (x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn zoom_scales_distances_from_the_center() {
+        let mut camera = moved_camera();
+        camera.rotation = 0.0;
+        // 100 points right of center is 40 world units at a zoom of 2.5
+        assert_near(camera.screen_to_world(420.0, 240.0), (1040.0, -250.0));
+        assert_near(camera.world_to_screen(1000.0, -210.0), (320.0, 340.0));
+    }
+
+    #[test]
+    fn view_matrix_matches_world_to_screen() {
+        let camera = moved_camera();
+        let view = camera.view();
+        let (x, y) = (1100.0, -300.0);
+        let transformed = (
+            x * view.m11 + y * view.m21 + view.m41,
+            x * view.m12 + y * view.m22 + view.m42,
+        );
+        assert_near(transformed, camera.world_to_screen(x, y));
+    }
+
+    #[test]
+    fn clamp_keeps_the_view_inside_the_bounds() {
+        let mut camera = Camera2D::new(640.0, 480.0);
+        camera.zoom = 2.0;
+        camera.bounds = Some(SDL_FRect {
+            x: 0.0,
+            y: 0.0,
+            w: 1000.0,
+            h: 800.0,
+        });
+
+        // the view is 320x240 world units
+        camera.x = -500.0;
+        camera.y = 5000.0;
+        camera.clamp_to_bounds();
+        assert_near((camera.x, camera.y), (160.0, 680.0));
+
+        camera.x = 500.0;
+        camera.y = 400.0;
+        camera.clamp_to_bounds();
+        assert_near((camera.x, camera.y), (500.0, 400.0));
+    }
+
+    #[test]
+    fn bounds_smaller_than_the_view_are_centered() {
+        let mut camera = Camera2D::new(640.0, 480.0);
+        camera.bounds = Some(SDL_FRect {
+            x: 100.0,
+            y: 50.0,
+            w: 200.0,
+            h: 1000.0,
+        });
+        camera.target = Some((0.0, 0.0));
+        camera.snap_to_target();
+
+        // too narrow to scroll across, but tall enough to scroll down
+        assert_near((camera.x, camera.y), (200.0, 290.0));
+    }
+}
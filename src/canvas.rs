@@ -0,0 +1,252 @@
+use std::ptr::null_mut;
+
+use sdl3_sys::everything::*;
+
+use crate::app::Frame;
+use crate::common::*;
+
+/// Where a `VirtualCanvas` lands on a target, in target pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CanvasViewport {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+    /// Target pixels per canvas pixel; a whole number unless the target is smaller than the canvas.
+    pub scale: f32,
+}
+
+/// A fixed, usually low, resolution texture to render a game into, which is then scaled up
+/// to the frame's target by a whole number, centered, with bars filling the space around it.
+///
+/// Each frame: render into `frame(frame)` instead of the frame itself, then `present`
+/// outside of any pass. Whatever the window size, pixel art is never stretched or blurred.
+pub struct VirtualCanvas {
+    texture: *mut SDL_GPUTexture,
+    width: u32,
+    height: u32,
+    /// The color of the bars around the canvas.
+    pub letterbox_color: SDL_FColor,
+}
+
+impl VirtualCanvas {
+    /// `format` is usually `gpu.target_format()`, so the same pipelines can draw to either.
+    pub unsafe fn new(
+        device: *mut SDL_GPUDevice,
+        format: SDL_GPUTextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Option<Self> {
+        let texture = SDL_CreateGPUTexture(
+            device,
+            &SDL_GPUTextureCreateInfo {
+                r#type: SDL_GPUTextureType::_2D,
+                format,
+                width,
+                height,
+                layer_count_or_depth: 1,
+                num_levels: 1,
+                usage: SDL_GPU_TEXTUREUSAGE_COLOR_TARGET | SDL_GPU_TEXTUREUSAGE_SAMPLER,
+                ..Default::default()
+            },
+        );
+        if texture.is_null() {
+            dbg_sdl_error("failed to create canvas texture");
+            return None;
+        }
+
+        Some(Self {
+            texture,
+            width,
+            height,
+            letterbox_color: SDL_FColor {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: 1.0,
+            },
+        })
+    }
+
+    pub unsafe fn release(&mut self, device: *mut SDL_GPUDevice) {
+        if !self.texture.is_null() {
+            SDL_ReleaseGPUTexture(device, self.texture);
+            self.texture = null_mut();
+        }
+    }
+
+    pub fn texture(&self) -> *mut SDL_GPUTexture {
+        self.texture
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// A copy of `frame` that renders into the canvas instead.
+    pub fn frame(&self, frame: &Frame) -> Frame {
+        Frame {
+            target: self.texture,
+            width: self.width,
+            height: self.height,
+            ..*frame
+        }
+    }
+
+    /// The largest whole-number scale of the canvas that fits the target, centered.
+    /// A target smaller than the canvas gets it shrunk to fit instead, keeping its aspect ratio.
+    pub fn viewport(&self, target_width: u32, target_height: u32) -> CanvasViewport {
+        let whole_scale = (target_width / self.width).min(target_height / self.height);
+        let scale = if whole_scale > 0 {
+            whole_scale as f32
+        } else {
+            (target_width as f32 / self.width as f32).min(target_height as f32 / self.height as f32)
+        };
+
+        let w = ((self.width as f32 * scale) as u32).min(target_width);
+        let h = ((self.height as f32 * scale) as u32).min(target_height);
+        CanvasViewport {
+            x: (target_width - w) / 2,
+            y: (target_height - h) / 2,
+            w,
+            h,
+            scale,
+        }
+    }
+
+    /// Converts a position on the target to canvas pixels. None if it's over the letterbox bars.
+    /// Window coordinates like the mouse position need multiplying by `Frame::pixel_density` first.
+    pub fn target_to_canvas(
+        &self,
+        x: f32,
+        y: f32,
+        target_width: u32,
+        target_height: u32,
+    ) -> Option<(f32, f32)> {
+        let viewport = self.viewport(target_width, target_height);
+        let canvas_x = (x - viewport.x as f32) / viewport.scale;
+        let canvas_y = (y - viewport.y as f32) / viewport.scale;
+        let inside = (0.0..self.width as f32).contains(&canvas_x)
+            && (0.0..self.height as f32).contains(&canvas_y);
+        inside.then_some((canvas_x, canvas_y))
+    }
+
+    /// Clears `target` to the letterbox color and draws the canvas over it with nearest filtering.
+    /// Must be called outside of any pass, after the canvas is rendered.
+    pub unsafe fn present(
+        &self,
+        command_buffer: *mut SDL_GPUCommandBuffer,
+        target: *mut SDL_GPUTexture,
+        target_width: u32,
+        target_height: u32,
+    ) {
+        let viewport = self.viewport(target_width, target_height);
+        if viewport.w == 0 || viewport.h == 0 {
+            return;
+        }
+
+        SDL_BlitGPUTexture(
+            command_buffer,
+            &SDL_GPUBlitInfo {
+                source: SDL_GPUBlitRegion {
+                    texture: self.texture,
+                    w: self.width,
+                    h: self.height,
+                    ..Default::default()
+                },
+                destination: SDL_GPUBlitRegion {
+                    texture: target,
+                    x: viewport.x,
+                    y: viewport.y,
+                    w: viewport.w,
+                    h: viewport.h,
+                    ..Default::default()
+                },
+                load_op: SDL_GPULoadOp::CLEAR,
+                clear_color: self.letterbox_color,
+                filter: SDL_GPU_FILTER_NEAREST,
+                ..Default::default()
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Only the size matters for the viewport math.
+    fn canvas(width: u32, height: u32) -> VirtualCanvas {
+        VirtualCanvas {
+            texture: null_mut(),
+            width,
+            height,
+            letterbox_color: SDL_FColor::default(),
+        }
+    }
+
+    fn viewport(x: u32, y: u32, w: u32, h: u32, scale: f32) -> CanvasViewport {
+        CanvasViewport { x, y, w, h, scale }
+    }
+
+    #[test]
+    fn integer_scale_target_fills_exactly() {
+        let canvas = canvas(320, 180);
+        assert_eq!(canvas.viewport(320, 180), viewport(0, 0, 320, 180, 1.0));
+        assert_eq!(canvas.viewport(1280, 720), viewport(0, 0, 1280, 720, 4.0));
+    }
+
+    #[test]
+    fn wider_target_is_pillarboxed() {
+        let canvas = canvas(320, 180);
+        // 3x fits the height, with 200 pixels of bars either side
+        assert_eq!(canvas.viewport(1360, 540), viewport(200, 0, 960, 540, 3.0));
+    }
+
+    #[test]
+    fn taller_target_is_letterboxed() {
+        let canvas = canvas(320, 180);
+        // 2x fits the width, with 90 pixels of bars above and below
+        assert_eq!(canvas.viewport(640, 540), viewport(0, 90, 640, 360, 2.0));
+    }
+
+    #[test]
+    fn uneven_target_is_bordered_on_both_axes() {
+        let canvas = canvas(320, 180);
+        assert_eq!(canvas.viewport(1000, 600), viewport(20, 30, 960, 540, 3.0));
+    }
+
+    #[test]
+    fn smaller_target_shrinks_the_canvas() {
+        let canvas = canvas(320, 180);
+        assert_eq!(canvas.viewport(160, 120), viewport(0, 15, 160, 90, 0.5));
+    }
+
+    #[test]
+    fn target_to_canvas_skips_the_bars() {
+        let canvas = canvas(320, 180);
+        // pillarboxed at 3x, with the canvas starting at x 200
+        assert_eq!(
+            canvas.target_to_canvas(200.0, 0.0, 1360, 540),
+            Some((0.0, 0.0))
+        );
+        assert_eq!(
+            canvas.target_to_canvas(680.0, 270.0, 1360, 540),
+            Some((160.0, 90.0))
+        );
+        assert_eq!(canvas.target_to_canvas(199.0, 270.0, 1360, 540), None);
+        assert_eq!(canvas.target_to_canvas(1160.0, 270.0, 1360, 540), None);
+
+        // letterboxed at 2x, with the canvas starting at y 90
+        assert_eq!(canvas.target_to_canvas(0.0, 89.0, 640, 540), None);
+        assert_eq!(
+            canvas.target_to_canvas(639.0, 449.0, 640, 540),
+            Some((319.5, 179.5))
+        );
+        assert_eq!(canvas.target_to_canvas(0.0, 450.0, 640, 540), None);
+    }
+}
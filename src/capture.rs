@@ -0,0 +1,101 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::image::Image;
+
+/// Writes a run of frames to disk, either as numbered bmps or as one raw RGBA stream.
+///
+/// A path ending in `.rgba` gets the raw stream, which ffmpeg reads with e.g.
+/// `ffmpeg -f rawvideo -pix_fmt rgba -s 640x480 -r 60 -i frames.rgba frames.mp4`.
+/// Any other path is a directory for `frame_00000.bmp`, `frame_00001.bmp`, ...
+pub struct FrameCapture {
+    path: PathBuf,
+    raw_stream: Option<BufWriter<File>>,
+    frame_size: Option<(u32, u32)>,
+    frames_written: u32,
+}
+
+impl FrameCapture {
+    pub fn new(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+
+        let raw_stream = if path.extension().is_some_and(|ext| ext == "rgba") {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            Some(BufWriter::new(File::create(&path)?))
+        } else {
+            std::fs::create_dir_all(&path)?;
+            None
+        };
+
+        Ok(Self {
+            path,
+            raw_stream,
+            frame_size: None,
+            frames_written: 0,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_raw_stream(&self) -> bool {
+        self.raw_stream.is_some()
+    }
+
+    pub fn frames_written(&self) -> u32 {
+        self.frames_written
+    }
+
+    /// The size of the captured frames, once the first has been written.
+    pub fn frame_size(&self) -> Option<(u32, u32)> {
+        self.frame_size
+    }
+
+    /// Every frame of a raw stream must be the size of the first.
+    pub fn write_frame(&mut self, image: &Image) -> std::io::Result<()> {
+        let size = (image.width, image.height);
+        let first_size = *self.frame_size.get_or_insert(size);
+
+        match &mut self.raw_stream {
+            Some(stream) => {
+                if size != first_size {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "frame is {}x{}, but the stream is {}x{}",
+                            size.0, size.1, first_size.0, first_size.1
+                        ),
+                    ));
+                }
+                stream.write_all(&image.pixels)?;
+            }
+            None => {
+                let frame_path = self
+                    .path
+                    .join(format!("frame_{:05}.bmp", self.frames_written));
+                if !image.save_bmp(&frame_path) {
+                    return Err(std::io::Error::other(format!(
+                        "failed to save {}",
+                        frame_path.display()
+                    )));
+                }
+            }
+        }
+
+        self.frames_written += 1;
+
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> std::io::Result<()> {
+        if let Some(stream) = &mut self.raw_stream {
+            stream.flush()?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,31 +1,27 @@
-use std::ffi::{CStr, CString, c_char};
+use std::ffi::{CStr, CString, c_char, c_void};
+use std::marker::PhantomData;
 use std::ptr::{null, null_mut};
 
 use sdl3_sys::everything::*;
 
 use serde::Deserialize;
 
+use crate::image::Image;
+
 /// Load a precompiled shader based on file name.
 /// Relies on the structure of the content directory, json metadata, and the file name suffix.
 pub unsafe fn load_shader(
     device: *mut SDL_GPUDevice,
     shader_name: &'static str,
 ) -> *mut SDL_GPUShader {
-    const COMPILED_SHADERS_DIR: &'static str = "./content/shaders/compiled";
+    const COMPILED_SHADERS_DIR: &str = "./content/shaders/compiled";
 
-    let backend_formats = SDL_GetGPUShaderFormats(device);
-    let (format, entrypoint) = if backend_formats & SDL_GPU_SHADERFORMAT_SPIRV != 0 {
-        (SDL_GPU_SHADERFORMAT_SPIRV, c"main".as_ptr())
-    } else if backend_formats & SDL_GPU_SHADERFORMAT_MSL != 0 {
-        (SDL_GPU_SHADERFORMAT_MSL, c"main0".as_ptr())
-    } else if backend_formats & SDL_GPU_SHADERFORMAT_DXIL != 0 {
-        (SDL_GPU_SHADERFORMAT_DXIL, c"main".as_ptr())
-    } else {
+    let Some((format, entrypoint, extension)) = choose_shader_format(device) else {
         println!("unrecognized backend shader format");
         return null_mut();
     };
 
-    let full_path = format!("{COMPILED_SHADERS_DIR}/spv/{shader_name}.spv");
+    let full_path = format!("{COMPILED_SHADERS_DIR}/{extension}/{shader_name}.{extension}");
     let full_path = CString::new(full_path).unwrap();
     let mut code_size = 0;
     let loaded_code = SDL_LoadFile(full_path.as_ptr(), &mut code_size);
@@ -79,6 +75,70 @@ pub unsafe fn load_shader(
     shader
 }
 
+/// Load a precompiled compute shader into a pipeline based on file name.
+/// shadercross doesn't emit resource counts for compute shaders, so `create_info` must
+/// already have its resource counts and thread counts filled in.
+pub unsafe fn load_compute_pipeline(
+    device: *mut SDL_GPUDevice,
+    shader_name: &'static str,
+    create_info: SDL_GPUComputePipelineCreateInfo,
+) -> *mut SDL_GPUComputePipeline {
+    const COMPILED_SHADERS_DIR: &str = "./content/shaders/compiled";
+
+    if !shader_name.ends_with(".comp") {
+        panic!("expected a file name ending in '.comp'")
+    }
+
+    let Some((format, entrypoint, extension)) = choose_shader_format(device) else {
+        println!("unrecognized backend shader format");
+        return null_mut();
+    };
+
+    let full_path = format!("{COMPILED_SHADERS_DIR}/{extension}/{shader_name}.{extension}");
+    let full_path = CString::new(full_path).unwrap();
+    let mut code_size = 0;
+    let loaded_code = SDL_LoadFile(full_path.as_ptr(), &mut code_size);
+    if loaded_code.is_null() {
+        dbg_sdl_error(&format!("failed to load compute shader: {shader_name}"));
+        return null_mut();
+    }
+
+    let pipeline = SDL_CreateGPUComputePipeline(
+        device,
+        &SDL_GPUComputePipelineCreateInfo {
+            code: loaded_code as *const u8,
+            code_size,
+            entrypoint,
+            format,
+            ..create_info
+        },
+    );
+    SDL_free(loaded_code);
+
+    if pipeline.is_null() {
+        dbg_sdl_error(&format!("failed to create compute pipeline: {shader_name}"));
+    }
+
+    pipeline
+}
+
+/// The compiled format the device's backend takes, its entrypoint name, and the extension
+/// shadercross compiles that format to, which is also the name of its directory.
+unsafe fn choose_shader_format(
+    device: *mut SDL_GPUDevice,
+) -> Option<(SDL_GPUShaderFormat, *const c_char, &'static str)> {
+    let backend_formats = SDL_GetGPUShaderFormats(device);
+    if backend_formats & SDL_GPU_SHADERFORMAT_SPIRV != 0 {
+        Some((SDL_GPU_SHADERFORMAT_SPIRV, c"main".as_ptr(), "spv"))
+    } else if backend_formats & SDL_GPU_SHADERFORMAT_MSL != 0 {
+        Some((SDL_GPU_SHADERFORMAT_MSL, c"main0".as_ptr(), "msl"))
+    } else if backend_formats & SDL_GPU_SHADERFORMAT_DXIL != 0 {
+        Some((SDL_GPU_SHADERFORMAT_DXIL, c"main".as_ptr(), "dxil"))
+    } else {
+        None
+    }
+}
+
 pub unsafe fn dbg_sdl_error(msg: &str) {
     #[cfg(debug_assertions)]
     {
@@ -99,7 +159,9 @@ struct ShaderMeta {
 }
 
 pub fn init_gpu_window(
-    window_title: *const c_char,
+    window_title: &CStr,
+    width: i32,
+    height: i32,
     window_flags: SDL_WindowFlags,
 ) -> Option<(*mut SDL_Window, *mut SDL_GPUDevice)> {
     unsafe {
@@ -108,7 +170,9 @@ pub fn init_gpu_window(
             return None;
         }
 
-        let window = SDL_CreateWindow(window_title, 640, 480, window_flags);
+        // render at the display's full resolution on scaled displays, rather than upscaling
+        let window_flags = window_flags | SDL_WINDOW_HIGH_PIXEL_DENSITY;
+        let window = SDL_CreateWindow(window_title.as_ptr(), width, height, window_flags);
         if window.is_null() {
             dbg_sdl_error("SDL_CreateWindow failed");
             return None;
@@ -131,7 +195,7 @@ pub fn init_gpu_window(
 }
 
 pub unsafe fn deinit_gpu_window(device: *mut SDL_GPUDevice, window: *mut SDL_Window) {
-    if !device.is_null() && !device.is_null() {
+    if !device.is_null() && !window.is_null() {
         SDL_ReleaseWindowFromGPUDevice(device, window);
     }
     if !window.is_null() {
@@ -142,9 +206,71 @@ pub unsafe fn deinit_gpu_window(device: *mut SDL_GPUDevice, window: *mut SDL_Win
     }
 }
 
-pub unsafe fn load_bmp(file_name: &str) -> *mut SDL_Surface {
-    const IMAGES_DIR: &'static str = "./content/images";
+/// How a window's swapchain presents frames.
+///
+/// `present_modes` are in order of preference: the first the window supports is used, falling
+/// back to VSYNC, which every window supports. The composition determines the swapchain's format,
+/// so it must be set before any pipelines are created; see `Game::swapchain_settings`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapchainSettings {
+    pub present_modes: &'static [SDL_GPUPresentMode],
+    pub composition: SDL_GPUSwapchainComposition,
+}
+
+impl SwapchainSettings {
+    /// Waits for vertical blank, without tearing; SDL's default.
+    pub const VSYNC: Self = Self {
+        present_modes: &[],
+        composition: SDL_GPUSwapchainComposition::SDR,
+    };
+
+    /// As many frames as possible, tearing if need be, or else without tearing.
+    pub const UNCAPPED: Self = Self {
+        present_modes: &[SDL_GPUPresentMode::IMMEDIATE, SDL_GPUPresentMode::MAILBOX],
+        composition: SDL_GPUSwapchainComposition::SDR,
+    };
+
+    /// The first of `present_modes` the window supports, or VSYNC.
+    pub unsafe fn choose_present_mode(
+        &self,
+        device: *mut SDL_GPUDevice,
+        window: *mut SDL_Window,
+    ) -> SDL_GPUPresentMode {
+        self.present_modes
+            .iter()
+            .copied()
+            .find(|mode| SDL_WindowSupportsGPUPresentMode(device, window, *mode))
+            .unwrap_or(SDL_GPUPresentMode::VSYNC)
+    }
+
+    /// Configures the window's swapchain, returning the present mode that was chosen,
+    /// or None if the composition isn't supported or SDL fails.
+    pub unsafe fn apply(
+        &self,
+        device: *mut SDL_GPUDevice,
+        window: *mut SDL_Window,
+    ) -> Option<SDL_GPUPresentMode> {
+        if !SDL_WindowSupportsGPUSwapchainComposition(device, window, self.composition) {
+            println!(
+                "swapchain composition {:?} is not supported",
+                self.composition
+            );
+            return None;
+        }
 
+        let present_mode = self.choose_present_mode(device, window);
+        if !SDL_SetGPUSwapchainParameters(device, window, self.composition, present_mode) {
+            dbg_sdl_error("failed to set swapchain parameters");
+            return None;
+        }
+
+        Some(present_mode)
+    }
+}
+
+const IMAGES_DIR: &str = "./content/images";
+
+pub unsafe fn load_bmp(file_name: &str) -> *mut SDL_Surface {
     let full_path = format!("{IMAGES_DIR}/{file_name}");
     let full_path = CString::new(full_path).unwrap();
 
@@ -154,7 +280,8 @@ pub unsafe fn load_bmp(file_name: &str) -> *mut SDL_Surface {
     }
 
     // NOTE this is only the '4 channels' path of the original example
-    let format = SDL_PixelFormat::ARGB8888;
+    // ABGR8888 is R, G, B, A in memory on little endian, matching the R8G8B8A8 textures it's uploaded to
+    let format = SDL_PixelFormat::ABGR8888;
     if (*result).format != format {
         let next = SDL_ConvertSurface(result, format);
         SDL_DestroySurface(result);
@@ -164,6 +291,932 @@ pub unsafe fn load_bmp(file_name: &str) -> *mut SDL_Surface {
     result
 }
 
+/// Loads a bmp from the images directory into an RGBA8 `Image`.
+pub fn load_image(file_name: &str) -> Option<Image> {
+    Image::load_bmp(format!("{IMAGES_DIR}/{file_name}"))
+}
+
+/// Creates a sampler-usable 2D texture from tightly packed 4-byte pixels,
+/// uploading them on a separate command buffer.
+pub unsafe fn create_texture_from_pixels(
+    device: *mut SDL_GPUDevice,
+    width: u32,
+    height: u32,
+    pixels: *const c_void,
+) -> *mut SDL_GPUTexture {
+    let size = width * height * 4;
+
+    let texture = SDL_CreateGPUTexture(
+        device,
+        &SDL_GPUTextureCreateInfo {
+            r#type: SDL_GPUTextureType::_2D,
+            format: SDL_GPUTextureFormat::R8G8B8A8_UNORM,
+            width,
+            height,
+            layer_count_or_depth: 1,
+            num_levels: 1,
+            usage: SDL_GPU_TEXTUREUSAGE_SAMPLER,
+            ..Default::default()
+        },
+    );
+    if texture.is_null() {
+        dbg_sdl_error("failed to create texture");
+        return texture;
+    }
+
+    if !upload_texture_data(
+        device,
+        texture,
+        width,
+        height,
+        1,
+        std::slice::from_raw_parts(pixels as *const u8, size as usize),
+    ) {
+        SDL_ReleaseGPUTexture(device, texture);
+        return null_mut();
+    }
+
+    texture
+}
+
+/// Uploads tightly packed 4-byte texels into the whole of `texture` on a separate command buffer:
+/// `depth` slices for a 3D texture, 1 for a 2D texture. Returns false if nothing was uploaded.
+pub unsafe fn upload_texture_data(
+    device: *mut SDL_GPUDevice,
+    texture: *mut SDL_GPUTexture,
+    width: u32,
+    height: u32,
+    depth: u32,
+    texels: &[u8],
+) -> bool {
+    let size = width * height * depth * 4;
+    debug_assert_eq!(texels.len(), size as usize);
+
+    let transfer_buffer = SDL_CreateGPUTransferBuffer(
+        device,
+        &SDL_GPUTransferBufferCreateInfo {
+            usage: SDL_GPUTransferBufferUsage::UPLOAD,
+            size,
+            ..Default::default()
+        },
+    );
+    if transfer_buffer.is_null() {
+        dbg_sdl_error("failed to create transfer buffer");
+        return false;
+    }
+
+    let transfer_ptr = SDL_MapGPUTransferBuffer(device, transfer_buffer, false);
+    if transfer_ptr.is_null() {
+        dbg_sdl_error("failed to map gpu transfer buffer");
+        SDL_ReleaseGPUTransferBuffer(device, transfer_buffer);
+        return false;
+    }
+    SDL_memcpy(
+        transfer_ptr,
+        texels.as_ptr() as *const c_void,
+        size as usize,
+    );
+    SDL_UnmapGPUTransferBuffer(device, transfer_buffer);
+
+    let upload_command_buf = SDL_AcquireGPUCommandBuffer(device);
+    if upload_command_buf.is_null() {
+        dbg_sdl_error("failed to acquire command buffer");
+        SDL_ReleaseGPUTransferBuffer(device, transfer_buffer);
+        return false;
+    }
+    let copy_pass = SDL_BeginGPUCopyPass(upload_command_buf);
+    SDL_UploadToGPUTexture(
+        copy_pass,
+        &SDL_GPUTextureTransferInfo {
+            transfer_buffer,
+            offset: 0,
+            ..Default::default()
+        },
+        &SDL_GPUTextureRegion {
+            texture,
+            w: width,
+            h: height,
+            d: depth,
+            ..Default::default()
+        },
+        false,
+    );
+    SDL_EndGPUCopyPass(copy_pass);
+    let submitted = SDL_SubmitGPUCommandBuffer(upload_command_buf);
+    if !submitted {
+        dbg_sdl_error("failed to submit command buffer");
+    }
+
+    SDL_ReleaseGPUTransferBuffer(device, transfer_buffer);
+
+    submitted
+}
+
+/// Loads a bmp from the images directory into a sampler-usable 2D texture.
+pub unsafe fn load_texture(device: *mut SDL_GPUDevice, file_name: &str) -> *mut SDL_GPUTexture {
+    let image_ptr = load_bmp(file_name);
+    if image_ptr.is_null() {
+        dbg_sdl_error(&format!("failed to load image: {file_name}"));
+        return null_mut();
+    }
+
+    let image = &*image_ptr;
+    let texture = create_texture_from_pixels(device, image.w as u32, image.h as u32, image.pixels);
+    SDL_DestroySurface(image_ptr);
+
+    texture
+}
+
+/// Loads six same-size square bmps from the images directory into a cube texture.
+/// Faces are in `SDL_GPUCubeMapFace` order: +X, -X, +Y, -Y, +Z, -Z.
+pub unsafe fn load_cubemap(
+    device: *mut SDL_GPUDevice,
+    face_file_names: [&str; 6],
+) -> *mut SDL_GPUTexture {
+    let mut faces = Vec::with_capacity(6);
+    for file_name in face_file_names {
+        let Some(face) = load_image(file_name) else {
+            return null_mut();
+        };
+        if face.width != face.height || faces.first().is_some_and(|f: &Image| f.width != face.width)
+        {
+            println!("cubemap faces must be square and the same size: {file_name}");
+            return null_mut();
+        }
+        faces.push(face);
+    }
+
+    create_cubemap_from_faces(device, &faces)
+}
+
+/// Loads a horizontal cross layout bmp from the images directory into a cube texture:
+///
+/// ```text
+///       +Y
+///   -X  +Z  +X  -Z
+///       -Y
+/// ```
+pub unsafe fn load_cubemap_cross(
+    device: *mut SDL_GPUDevice,
+    file_name: &str,
+) -> *mut SDL_GPUTexture {
+    // the cell of each face in the 4x3 grid, in `SDL_GPUCubeMapFace` order
+    const FACE_CELLS: [(u32, u32); 6] = [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (3, 1)];
+
+    let Some(cross) = load_image(file_name) else {
+        return null_mut();
+    };
+    let size = cross.width / 4;
+    if size == 0 || cross.width != size * 4 || cross.height != size * 3 {
+        println!("cubemap cross must be 4 by 3 square faces: {file_name}");
+        return null_mut();
+    }
+
+    let faces: Vec<Image> = FACE_CELLS
+        .iter()
+        .map(|(cell_x, cell_y)| cross.crop(cell_x * size, cell_y * size, size, size))
+        .collect();
+
+    create_cubemap_from_faces(device, &faces)
+}
+
+/// Creates a sampler-usable cube texture from six same-size square RGBA8 faces,
+/// uploading them on a separate command buffer.
+pub unsafe fn create_cubemap_from_faces(
+    device: *mut SDL_GPUDevice,
+    faces: &[Image],
+) -> *mut SDL_GPUTexture {
+    if faces.len() != 6 || faces[0].width != faces[0].height {
+        println!("a cubemap needs 6 square faces, got {}", faces.len());
+        return null_mut();
+    }
+
+    create_layered_texture(device, SDL_GPUTextureType::CUBE, faces)
+}
+
+/// Loads same-size bmps from the images directory into the layers of a
+/// sampler-usable 2D array texture, in order.
+pub unsafe fn load_texture_array(
+    device: *mut SDL_GPUDevice,
+    file_names: &[&str],
+) -> *mut SDL_GPUTexture {
+    let mut layers = Vec::with_capacity(file_names.len());
+    for file_name in file_names {
+        let Some(layer) = load_image(file_name) else {
+            return null_mut();
+        };
+        layers.push(layer);
+    }
+
+    create_texture_array_from_images(device, &layers)
+}
+
+/// Creates a sampler-usable 2D array texture with one layer per same-size RGBA8 image,
+/// uploading them on a separate command buffer.
+/// Unlike an atlas, filtering never bleeds from one layer into another.
+pub unsafe fn create_texture_array_from_images(
+    device: *mut SDL_GPUDevice,
+    layers: &[Image],
+) -> *mut SDL_GPUTexture {
+    create_layered_texture(device, SDL_GPUTextureType::_2D_ARRAY, layers)
+}
+
+/// Creates a cube or array texture with one layer per image, uploading each layer separately.
+unsafe fn create_layered_texture(
+    device: *mut SDL_GPUDevice,
+    texture_type: SDL_GPUTextureType,
+    layers: &[Image],
+) -> *mut SDL_GPUTexture {
+    let Some(first) = layers.first() else {
+        println!("a layered texture needs at least one image");
+        return null_mut();
+    };
+    let (width, height) = (first.width, first.height);
+    if layers
+        .iter()
+        .any(|layer| layer.width != width || layer.height != height)
+    {
+        println!("every layer of a texture must be {width}x{height}");
+        return null_mut();
+    }
+    let layer_size = width * height * 4;
+    let layer_count = layers.len() as u32;
+
+    let texture = SDL_CreateGPUTexture(
+        device,
+        &SDL_GPUTextureCreateInfo {
+            r#type: texture_type,
+            format: SDL_GPUTextureFormat::R8G8B8A8_UNORM,
+            width,
+            height,
+            layer_count_or_depth: layer_count,
+            num_levels: 1,
+            usage: SDL_GPU_TEXTUREUSAGE_SAMPLER,
+            ..Default::default()
+        },
+    );
+    if texture.is_null() {
+        dbg_sdl_error("failed to create layered texture");
+        return texture;
+    }
+
+    let transfer_buffer = SDL_CreateGPUTransferBuffer(
+        device,
+        &SDL_GPUTransferBufferCreateInfo {
+            usage: SDL_GPUTransferBufferUsage::UPLOAD,
+            size: layer_size * layer_count,
+            ..Default::default()
+        },
+    );
+    let transfer_ptr = SDL_MapGPUTransferBuffer(device, transfer_buffer, false) as *mut u8;
+    for (i, layer) in layers.iter().enumerate() {
+        std::ptr::copy_nonoverlapping(
+            layer.pixels.as_ptr(),
+            transfer_ptr.add(i * layer_size as usize),
+            layer_size as usize,
+        );
+    }
+    SDL_UnmapGPUTransferBuffer(device, transfer_buffer);
+
+    let upload_command_buf = SDL_AcquireGPUCommandBuffer(device);
+    let copy_pass = SDL_BeginGPUCopyPass(upload_command_buf);
+    for layer in 0..layer_count {
+        SDL_UploadToGPUTexture(
+            copy_pass,
+            &SDL_GPUTextureTransferInfo {
+                transfer_buffer,
+                offset: layer * layer_size,
+                ..Default::default()
+            },
+            &SDL_GPUTextureRegion {
+                texture,
+                layer,
+                w: width,
+                h: height,
+                d: 1,
+                ..Default::default()
+            },
+            false,
+        );
+    }
+    SDL_EndGPUCopyPass(copy_pass);
+    SDL_SubmitGPUCommandBuffer(upload_command_buf);
+
+    SDL_ReleaseGPUTransferBuffer(device, transfer_buffer);
+
+    texture
+}
+
+/// A 1x1 opaque white texture, for drawing solid colored quads with textured pipelines.
+pub unsafe fn create_white_texture(device: *mut SDL_GPUDevice) -> *mut SDL_GPUTexture {
+    let pixel: [u8; 4] = [255, 255, 255, 255];
+    create_texture_from_pixels(device, 1, 1, pixel.as_ptr() as *const c_void)
+}
+
+pub unsafe fn create_sampler(
+    device: *mut SDL_GPUDevice,
+    filter: SDL_GPUFilter,
+    address_mode: SDL_GPUSamplerAddressMode,
+) -> *mut SDL_GPUSampler {
+    let mipmap_mode = if filter == SDL_GPU_FILTER_LINEAR {
+        SDL_GPU_SAMPLERMIPMAPMODE_LINEAR
+    } else {
+        SDL_GPU_SAMPLERMIPMAPMODE_NEAREST
+    };
+
+    SDL_CreateGPUSampler(
+        device,
+        &SDL_GPUSamplerCreateInfo {
+            min_filter: filter,
+            mag_filter: filter,
+            mipmap_mode,
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            address_mode_w: address_mode,
+            ..Default::default()
+        },
+    )
+}
+
+/// Creates an alpha blended triangle list pipeline from a vertex and fragment shader,
+/// logging and returning null on failure.
+pub unsafe fn create_graphics_pipeline(
+    device: *mut SDL_GPUDevice,
+    target_format: SDL_GPUTextureFormat,
+    vert_name: &'static str,
+    frag_name: &'static str,
+    layout: &VertexInputLayout,
+) -> *mut SDL_GPUGraphicsPipeline {
+    create_graphics_pipeline_with_primitive(
+        device,
+        target_format,
+        vert_name,
+        frag_name,
+        layout,
+        SDL_GPU_PRIMITIVETYPE_TRIANGLELIST,
+    )
+}
+
+/// Like `create_graphics_pipeline`, for other primitive types such as line lists.
+pub unsafe fn create_graphics_pipeline_with_primitive(
+    device: *mut SDL_GPUDevice,
+    target_format: SDL_GPUTextureFormat,
+    vert_name: &'static str,
+    frag_name: &'static str,
+    layout: &VertexInputLayout,
+    primitive_type: SDL_GPUPrimitiveType,
+) -> *mut SDL_GPUGraphicsPipeline {
+    let vert_shader = load_shader(device, vert_name);
+    if vert_shader.is_null() {
+        dbg_sdl_error("failed to load vert shader");
+        return null_mut();
+    }
+
+    let frag_shader = load_shader(device, frag_name);
+    if frag_shader.is_null() {
+        dbg_sdl_error("failed to load frag shader");
+        SDL_ReleaseGPUShader(device, vert_shader);
+        return null_mut();
+    }
+
+    let pipeline = SDL_CreateGPUGraphicsPipeline(
+        device,
+        &SDL_GPUGraphicsPipelineCreateInfo {
+            vertex_shader: vert_shader,
+            fragment_shader: frag_shader,
+            vertex_input_state: layout.state(),
+            primitive_type,
+            target_info: SDL_GPUGraphicsPipelineTargetInfo {
+                num_color_targets: 1,
+                color_target_descriptions: [SDL_GPUColorTargetDescription {
+                    format: target_format,
+                    blend_state: SDL_GPUColorTargetBlendState {
+                        enable_blend: true,
+                        color_blend_op: SDL_GPUBlendOp::ADD,
+                        alpha_blend_op: SDL_GPUBlendOp::ADD,
+                        src_color_blendfactor: SDL_GPUBlendFactor::SRC_ALPHA,
+                        dst_color_blendfactor: SDL_GPUBlendFactor::ONE_MINUS_SRC_ALPHA,
+                        src_alpha_blendfactor: SDL_GPUBlendFactor::SRC_ALPHA,
+                        dst_alpha_blendfactor: SDL_GPUBlendFactor::ONE_MINUS_SRC_ALPHA,
+                        ..Default::default()
+                    },
+                }]
+                .as_ptr(),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    SDL_ReleaseGPUShader(device, vert_shader);
+    SDL_ReleaseGPUShader(device, frag_shader);
+
+    if pipeline.is_null() {
+        dbg_sdl_error(&format!(
+            "failed to create pipeline: {vert_name} {frag_name}"
+        ));
+    }
+
+    pipeline
+}
+
+/// Creates a gpu buffer filled with `data`, uploading on a separate command buffer.
+pub unsafe fn create_buffer_with_data<T: Copy>(
+    device: *mut SDL_GPUDevice,
+    usage: SDL_GPUBufferUsageFlags,
+    data: &[T],
+) -> *mut SDL_GPUBuffer {
+    let size = std::mem::size_of_val(data) as u32;
+
+    let buffer = SDL_CreateGPUBuffer(
+        device,
+        &SDL_GPUBufferCreateInfo {
+            usage,
+            size,
+            ..Default::default()
+        },
+    );
+    if buffer.is_null() {
+        dbg_sdl_error("failed to create buffer");
+        return buffer;
+    }
+
+    let transfer_buffer = SDL_CreateGPUTransferBuffer(
+        device,
+        &SDL_GPUTransferBufferCreateInfo {
+            usage: SDL_GPUTransferBufferUsage::UPLOAD,
+            size,
+            ..Default::default()
+        },
+    );
+    let transfer_ptr = SDL_MapGPUTransferBuffer(device, transfer_buffer, false);
+    SDL_memcpy(transfer_ptr, data.as_ptr() as *const c_void, size as usize);
+    SDL_UnmapGPUTransferBuffer(device, transfer_buffer);
+
+    let upload_command_buf = SDL_AcquireGPUCommandBuffer(device);
+    let copy_pass = SDL_BeginGPUCopyPass(upload_command_buf);
+    SDL_UploadToGPUBuffer(
+        copy_pass,
+        &SDL_GPUTransferBufferLocation {
+            transfer_buffer,
+            offset: 0,
+        },
+        &SDL_GPUBufferRegion {
+            buffer,
+            offset: 0,
+            size,
+        },
+        false,
+    );
+    SDL_EndGPUCopyPass(copy_pass);
+    SDL_SubmitGPUCommandBuffer(upload_command_buf);
+
+    SDL_ReleaseGPUTransferBuffer(device, transfer_buffer);
+
+    buffer
+}
+
+/// A `#[repr(C)]` type that can be read by a vertex shader from a vertex buffer.
+pub trait Vertex: Copy {
+    /// One attribute per shader input, in `TEXCOORDn` order starting at location 0.
+    /// `buffer_slot` is ignored; `VertexInputLayout` assigns slots and locations.
+    const ATTRIBUTES: &'static [SDL_GPUVertexAttribute];
+}
+
+/// The input of PositionColor.vert.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PositionColorVertex {
+    pub position: [f32; 3],
+    pub color: SDL_FColor,
+}
+
+impl Vertex for PositionColorVertex {
+    const ATTRIBUTES: &'static [SDL_GPUVertexAttribute] = &[
+        SDL_GPUVertexAttribute {
+            location: 0,
+            buffer_slot: 0,
+            format: SDL_GPU_VERTEXELEMENTFORMAT_FLOAT3,
+            offset: std::mem::offset_of!(PositionColorVertex, position) as u32,
+        },
+        SDL_GPUVertexAttribute {
+            location: 1,
+            buffer_slot: 0,
+            format: SDL_GPU_VERTEXELEMENTFORMAT_FLOAT4,
+            offset: std::mem::offset_of!(PositionColorVertex, color) as u32,
+        },
+    ];
+}
+
+/// The input of TexturedQuad.vert.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PositionTextureVertex {
+    pub position: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+impl Vertex for PositionTextureVertex {
+    const ATTRIBUTES: &'static [SDL_GPUVertexAttribute] = &[
+        SDL_GPUVertexAttribute {
+            location: 0,
+            buffer_slot: 0,
+            format: SDL_GPU_VERTEXELEMENTFORMAT_FLOAT3,
+            offset: std::mem::offset_of!(PositionTextureVertex, position) as u32,
+        },
+        SDL_GPUVertexAttribute {
+            location: 1,
+            buffer_slot: 0,
+            format: SDL_GPU_VERTEXELEMENTFORMAT_FLOAT2,
+            offset: std::mem::offset_of!(PositionTextureVertex, uv) as u32,
+        },
+    ];
+}
+
+/// The vertex buffer descriptions and attributes for a graphics pipeline,
+/// built from `Vertex` types bound to consecutive slots.
+///
+/// Must outlive the `SDL_GPUVertexInputState` returned by `state`.
+#[derive(Clone, Debug, Default)]
+pub struct VertexInputLayout {
+    buffers: Vec<SDL_GPUVertexBufferDescription>,
+    attributes: Vec<SDL_GPUVertexAttribute>,
+}
+
+impl VertexInputLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a per-vertex buffer of `V` at the next slot. Its attributes follow
+    /// the locations of any buffers added before it.
+    pub fn with_vertices<V: Vertex>(self) -> Self {
+        self.with_buffer::<V>(SDL_GPU_VERTEXINPUTRATE_VERTEX)
+    }
+
+    /// Adds a buffer of `V` that advances once per instance rather than once per vertex,
+    /// e.g. for per-instance transforms. Its attributes follow the locations of any
+    /// buffers added before it.
+    pub fn with_instances<V: Vertex>(self) -> Self {
+        self.with_buffer::<V>(SDL_GPU_VERTEXINPUTRATE_INSTANCE)
+    }
+
+    fn with_buffer<V: Vertex>(mut self, input_rate: SDL_GPUVertexInputRate) -> Self {
+        let slot = self.buffers.len() as u32;
+        let first_location = self.attributes.len() as u32;
+
+        self.buffers.push(SDL_GPUVertexBufferDescription {
+            slot,
+            pitch: std::mem::size_of::<V>() as u32,
+            input_rate,
+            // reserved by SDL, must be 0
+            instance_step_rate: 0,
+        });
+        self.attributes.extend(
+            V::ATTRIBUTES
+                .iter()
+                .map(|attribute| SDL_GPUVertexAttribute {
+                    location: first_location + attribute.location,
+                    buffer_slot: slot,
+                    ..*attribute
+                }),
+        );
+
+        self
+    }
+
+    pub fn state(&self) -> SDL_GPUVertexInputState {
+        SDL_GPUVertexInputState {
+            vertex_buffer_descriptions: self.buffers.as_ptr(),
+            num_vertex_buffers: self.buffers.len() as u32,
+            vertex_attributes: self.attributes.as_ptr(),
+            num_vertex_attributes: self.attributes.len() as u32,
+        }
+    }
+}
+
+/// An immutable gpu buffer of `V`s, uploaded once on creation.
+pub struct VertexBuffer<V: Vertex> {
+    buffer: *mut SDL_GPUBuffer,
+    len: u32,
+    _vertex: PhantomData<V>,
+}
+
+impl<V: Vertex> VertexBuffer<V> {
+    pub unsafe fn new(device: *mut SDL_GPUDevice, vertices: &[V]) -> Option<Self> {
+        let buffer = create_buffer_with_data(device, SDL_GPU_BUFFERUSAGE_VERTEX, vertices);
+        if buffer.is_null() {
+            return None;
+        }
+
+        Some(Self {
+            buffer,
+            len: vertices.len() as u32,
+            _vertex: PhantomData,
+        })
+    }
+
+    pub unsafe fn release(&mut self, device: *mut SDL_GPUDevice) {
+        if !self.buffer.is_null() {
+            SDL_ReleaseGPUBuffer(device, self.buffer);
+            self.buffer = null_mut();
+        }
+    }
+
+    pub fn buffer(&self) -> *mut SDL_GPUBuffer {
+        self.buffer
+    }
+
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub unsafe fn bind(&self, render_pass: *mut SDL_GPURenderPass, slot: u32) {
+        SDL_BindGPUVertexBuffers(
+            render_pass,
+            slot,
+            &SDL_GPUBufferBinding {
+                buffer: self.buffer,
+                offset: 0,
+            },
+            1,
+        );
+    }
+}
+
+/// A gpu buffer of up to `capacity` `V`s that is rewritten every frame,
+/// e.g. per-instance data for `VertexInputLayout::with_instances`.
+///
+/// Each frame: `upload` before the render pass, then `bind` inside it.
+pub struct DynamicVertexBuffer<V: Vertex> {
+    transfer_buffer: *mut SDL_GPUTransferBuffer,
+    buffer: *mut SDL_GPUBuffer,
+    capacity: u32,
+    uploaded_count: u32,
+    _vertex: PhantomData<V>,
+}
+
+impl<V: Vertex> DynamicVertexBuffer<V> {
+    pub unsafe fn new(device: *mut SDL_GPUDevice, capacity: u32) -> Option<Self> {
+        let size = capacity * std::mem::size_of::<V>() as u32;
+
+        let transfer_buffer = SDL_CreateGPUTransferBuffer(
+            device,
+            &SDL_GPUTransferBufferCreateInfo {
+                usage: SDL_GPUTransferBufferUsage::UPLOAD,
+                size,
+                ..Default::default()
+            },
+        );
+        if transfer_buffer.is_null() {
+            dbg_sdl_error("failed to create transfer buffer");
+            return None;
+        }
+
+        let buffer = SDL_CreateGPUBuffer(
+            device,
+            &SDL_GPUBufferCreateInfo {
+                usage: SDL_GPU_BUFFERUSAGE_VERTEX,
+                size,
+                ..Default::default()
+            },
+        );
+        if buffer.is_null() {
+            dbg_sdl_error("failed to create vertex buffer");
+            SDL_ReleaseGPUTransferBuffer(device, transfer_buffer);
+            return None;
+        }
+
+        Some(Self {
+            transfer_buffer,
+            buffer,
+            capacity,
+            uploaded_count: 0,
+            _vertex: PhantomData,
+        })
+    }
+
+    pub unsafe fn release(&mut self, device: *mut SDL_GPUDevice) {
+        if !self.transfer_buffer.is_null() {
+            SDL_ReleaseGPUTransferBuffer(device, self.transfer_buffer);
+            self.transfer_buffer = null_mut();
+        }
+        if !self.buffer.is_null() {
+            SDL_ReleaseGPUBuffer(device, self.buffer);
+            self.buffer = null_mut();
+        }
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// How many `V`s the last `upload` copied.
+    pub fn len(&self) -> u32 {
+        self.uploaded_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.uploaded_count == 0
+    }
+
+    /// Copies `data` to the gpu, dropping anything past the buffer's capacity.
+    /// Must be called outside of a render pass.
+    pub unsafe fn upload(
+        &mut self,
+        device: *mut SDL_GPUDevice,
+        command_buffer: *mut SDL_GPUCommandBuffer,
+        data: &[V],
+    ) -> bool {
+        self.uploaded_count = data.len().min(self.capacity as usize) as u32;
+        if self.uploaded_count == 0 {
+            return true;
+        }
+
+        let data_ptr = SDL_MapGPUTransferBuffer(device, self.transfer_buffer, true) as *mut V;
+        if data_ptr.is_null() {
+            dbg_sdl_error("failed to map gpu transfer buffer");
+            return false;
+        }
+
+        std::ptr::copy_nonoverlapping(data.as_ptr(), data_ptr, self.uploaded_count as usize);
+        SDL_UnmapGPUTransferBuffer(device, self.transfer_buffer);
+
+        let copy_pass = SDL_BeginGPUCopyPass(command_buffer);
+        SDL_UploadToGPUBuffer(
+            copy_pass,
+            &SDL_GPUTransferBufferLocation {
+                transfer_buffer: self.transfer_buffer,
+                offset: 0,
+            },
+            &SDL_GPUBufferRegion {
+                buffer: self.buffer,
+                offset: 0,
+                size: self.uploaded_count * std::mem::size_of::<V>() as u32,
+            },
+            true,
+        );
+        SDL_EndGPUCopyPass(copy_pass);
+
+        true
+    }
+
+    pub unsafe fn bind(&self, render_pass: *mut SDL_GPURenderPass, slot: u32) {
+        SDL_BindGPUVertexBuffers(
+            render_pass,
+            slot,
+            &SDL_GPUBufferBinding {
+                buffer: self.buffer,
+                offset: 0,
+            },
+            1,
+        );
+    }
+}
+
+/// An integer type usable as an index in an `IndexBuffer`.
+pub trait Index: Copy {
+    const ELEMENT_SIZE: SDL_GPUIndexElementSize;
+}
+
+impl Index for u16 {
+    const ELEMENT_SIZE: SDL_GPUIndexElementSize = SDL_GPU_INDEXELEMENTSIZE_16BIT;
+}
+
+impl Index for u32 {
+    const ELEMENT_SIZE: SDL_GPUIndexElementSize = SDL_GPU_INDEXELEMENTSIZE_32BIT;
+}
+
+/// An immutable gpu buffer of `u16` or `u32` indices, uploaded once on creation.
+pub struct IndexBuffer<I: Index> {
+    buffer: *mut SDL_GPUBuffer,
+    len: u32,
+    _index: PhantomData<I>,
+}
+
+impl<I: Index> IndexBuffer<I> {
+    pub unsafe fn new(device: *mut SDL_GPUDevice, indices: &[I]) -> Option<Self> {
+        let buffer = create_buffer_with_data(device, SDL_GPU_BUFFERUSAGE_INDEX, indices);
+        if buffer.is_null() {
+            return None;
+        }
+
+        Some(Self {
+            buffer,
+            len: indices.len() as u32,
+            _index: PhantomData,
+        })
+    }
+
+    pub unsafe fn release(&mut self, device: *mut SDL_GPUDevice) {
+        if !self.buffer.is_null() {
+            SDL_ReleaseGPUBuffer(device, self.buffer);
+            self.buffer = null_mut();
+        }
+    }
+
+    pub fn buffer(&self) -> *mut SDL_GPUBuffer {
+        self.buffer
+    }
+
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub unsafe fn bind(&self, render_pass: *mut SDL_GPURenderPass) {
+        SDL_BindGPUIndexBuffer(
+            render_pass,
+            &SDL_GPUBufferBinding {
+                buffer: self.buffer,
+                offset: 0,
+            },
+            I::ELEMENT_SIZE,
+        );
+    }
+
+    /// Draws every index, with `instance_count` instances.
+    pub unsafe fn draw(&self, render_pass: *mut SDL_GPURenderPass, instance_count: u32) {
+        SDL_DrawGPUIndexedPrimitives(render_pass, self.len, instance_count, 0, 0, 0);
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vector3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vector3 {
+    pub const ZERO: Vector3 = Vector3::new(0.0, 0.0, 0.0);
+    pub const UP: Vector3 = Vector3::new(0.0, 1.0, 0.0);
+
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn dot(self, other: Vector3) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Vector3) -> Vector3 {
+        Vector3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Returns the zero vector unchanged.
+    pub fn normalize(self) -> Vector3 {
+        let length = self.length();
+        if length == 0.0 {
+            self
+        } else {
+            self * (1.0 / length)
+        }
+    }
+}
+
+impl std::ops::Add for Vector3 {
+    type Output = Vector3;
+
+    fn add(self, rhs: Vector3) -> Vector3 {
+        Vector3::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl std::ops::Sub for Vector3 {
+    type Output = Vector3;
+
+    fn sub(self, rhs: Vector3) -> Vector3 {
+        Vector3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl std::ops::Mul<f32> for Vector3 {
+    type Output = Vector3;
+
+    fn mul(self, rhs: f32) -> Vector3 {
+        Vector3::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Matrix4x4 {
     pub m11: f32,
     pub m12: f32,
@@ -217,4 +1270,245 @@ impl Matrix4x4 {
             m44: 1.0,
         }
     }
+
+    pub const IDENTITY: Matrix4x4 = Matrix4x4 {
+        m11: 1.0,
+        m12: 0.0,
+        m13: 0.0,
+        m14: 0.0,
+
+        m21: 0.0,
+        m22: 1.0,
+        m23: 0.0,
+        m24: 0.0,
+
+        m31: 0.0,
+        m32: 0.0,
+        m33: 1.0,
+        m34: 0.0,
+
+        m41: 0.0,
+        m42: 0.0,
+        m43: 0.0,
+        m44: 1.0,
+    };
+
+    /// A right-handed perspective projection with depth mapped to 0..1.
+    /// `field_of_view` is vertical, in radians.
+    pub fn create_perspective_field_of_view(
+        field_of_view: f32,
+        aspect_ratio: f32,
+        z_near_plane: f32,
+        z_far_plane: f32,
+    ) -> Self {
+        let y_scale = 1.0 / (field_of_view * 0.5).tan();
+        let x_scale = y_scale / aspect_ratio;
+
+        Matrix4x4 {
+            m11: x_scale,
+            m12: 0.0,
+            m13: 0.0,
+            m14: 0.0,
+
+            m21: 0.0,
+            m22: y_scale,
+            m23: 0.0,
+            m24: 0.0,
+
+            m31: 0.0,
+            m32: 0.0,
+            m33: z_far_plane / (z_near_plane - z_far_plane),
+            m34: -1.0,
+
+            m41: 0.0,
+            m42: 0.0,
+            m43: z_near_plane * z_far_plane / (z_near_plane - z_far_plane),
+            m44: 0.0,
+        }
+    }
+
+    /// A right-handed view matrix looking from `camera_position` towards `camera_target`.
+    pub fn create_look_at(
+        camera_position: Vector3,
+        camera_target: Vector3,
+        camera_up_vector: Vector3,
+    ) -> Self {
+        let z_axis = (camera_position - camera_target).normalize();
+        let x_axis = camera_up_vector.cross(z_axis).normalize();
+        let y_axis = z_axis.cross(x_axis);
+
+        Matrix4x4 {
+            m11: x_axis.x,
+            m12: y_axis.x,
+            m13: z_axis.x,
+            m14: 0.0,
+
+            m21: x_axis.y,
+            m22: y_axis.y,
+            m23: z_axis.y,
+            m24: 0.0,
+
+            m31: x_axis.z,
+            m32: y_axis.z,
+            m33: z_axis.z,
+            m34: 0.0,
+
+            m41: -x_axis.dot(camera_position),
+            m42: -y_axis.dot(camera_position),
+            m43: -z_axis.dot(camera_position),
+            m44: 1.0,
+        }
+    }
+
+    /// The transform of `self` followed by `other`; e.g. `view.multiply(&projection)`.
+    pub fn multiply(&self, other: &Matrix4x4) -> Self {
+        let a = self;
+        let b = other;
+
+        Matrix4x4 {
+            m11: a.m11 * b.m11 + a.m12 * b.m21 + a.m13 * b.m31 + a.m14 * b.m41,
+            m12: a.m11 * b.m12 + a.m12 * b.m22 + a.m13 * b.m32 + a.m14 * b.m42,
+            m13: a.m11 * b.m13 + a.m12 * b.m23 + a.m13 * b.m33 + a.m14 * b.m43,
+            m14: a.m11 * b.m14 + a.m12 * b.m24 + a.m13 * b.m34 + a.m14 * b.m44,
+
+            m21: a.m21 * b.m11 + a.m22 * b.m21 + a.m23 * b.m31 + a.m24 * b.m41,
+            m22: a.m21 * b.m12 + a.m22 * b.m22 + a.m23 * b.m32 + a.m24 * b.m42,
+            m23: a.m21 * b.m13 + a.m22 * b.m23 + a.m23 * b.m33 + a.m24 * b.m43,
+            m24: a.m21 * b.m14 + a.m22 * b.m24 + a.m23 * b.m34 + a.m24 * b.m44,
+
+            m31: a.m31 * b.m11 + a.m32 * b.m21 + a.m33 * b.m31 + a.m34 * b.m41,
+            m32: a.m31 * b.m12 + a.m32 * b.m22 + a.m33 * b.m32 + a.m34 * b.m42,
+            m33: a.m31 * b.m13 + a.m32 * b.m23 + a.m33 * b.m33 + a.m34 * b.m43,
+            m34: a.m31 * b.m14 + a.m32 * b.m24 + a.m33 * b.m34 + a.m34 * b.m44,
+
+            m41: a.m41 * b.m11 + a.m42 * b.m21 + a.m43 * b.m31 + a.m44 * b.m41,
+            m42: a.m41 * b.m12 + a.m42 * b.m22 + a.m43 * b.m32 + a.m44 * b.m42,
+            m43: a.m41 * b.m13 + a.m42 * b.m23 + a.m43 * b.m33 + a.m44 * b.m43,
+            m44: a.m41 * b.m14 + a.m42 * b.m24 + a.m43 * b.m34 + a.m44 * b.m44,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Transforms a point as a row vector, the order `multiply` composes in.
+    fn transform(m: &Matrix4x4, p: Vector3) -> [f32; 4] {
+        [
+            p.x * m.m11 + p.y * m.m21 + p.z * m.m31 + m.m41,
+            p.x * m.m12 + p.y * m.m22 + p.z * m.m32 + m.m42,
+            p.x * m.m13 + p.y * m.m23 + p.z * m.m33 + m.m43,
+            p.x * m.m14 + p.y * m.m24 + p.z * m.m34 + m.m44,
+        ]
+    }
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-4, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn multiply_by_identity() {
+        let view = Matrix4x4::create_look_at(
+            Vector3::new(3.0, 4.0, 5.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+        );
+        let projection = Matrix4x4::create_perspective_field_of_view(1.0, 1.5, 0.1, 50.0);
+        let m = view.multiply(&projection);
+
+        assert_eq!(m.multiply(&Matrix4x4::IDENTITY), m);
+        assert_eq!(Matrix4x4::IDENTITY.multiply(&m), m);
+    }
+
+    #[test]
+    fn multiply_applies_self_first() {
+        let translate = Matrix4x4 {
+            m41: 1.0,
+            ..Matrix4x4::IDENTITY
+        };
+        let scale = Matrix4x4 {
+            m11: 2.0,
+            m22: 2.0,
+            m33: 2.0,
+            ..Matrix4x4::IDENTITY
+        };
+
+        let origin = Vector3::new(0.0, 0.0, 0.0);
+        assert_eq!(
+            transform(&translate.multiply(&scale), origin),
+            [2.0, 0.0, 0.0, 1.0]
+        );
+        assert_eq!(
+            transform(&scale.multiply(&translate), origin),
+            [1.0, 0.0, 0.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn look_at_moves_the_eye_to_the_origin() {
+        let eye = Vector3::new(3.0, 4.0, 5.0);
+        let target = Vector3::new(0.0, 1.0, 0.0);
+        let view = Matrix4x4::create_look_at(eye, target, Vector3::new(0.0, 1.0, 0.0));
+
+        let [x, y, z, w] = transform(&view, eye);
+        assert_near(x, 0.0);
+        assert_near(y, 0.0);
+        assert_near(z, 0.0);
+        assert_near(w, 1.0);
+
+        // right-handed: the target is straight ahead, down -z
+        let [x, y, z, _] = transform(&view, target);
+        assert_near(x, 0.0);
+        assert_near(y, 0.0);
+        assert_near(z, -(eye - target).length());
+    }
+
+    #[test]
+    fn look_at_keeps_up_on_screen() {
+        let view = Matrix4x4::create_look_at(
+            Vector3::new(0.0, 0.0, 10.0),
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+        );
+        let [x, y, _, _] = transform(&view, Vector3::new(1.0, 2.0, 0.0));
+        assert_near(x, 1.0);
+        assert_near(y, 2.0);
+    }
+
+    #[test]
+    fn perspective_maps_depth_to_zero_one() {
+        let (near, far) = (0.1, 100.0);
+        let projection = Matrix4x4::create_perspective_field_of_view(
+            std::f32::consts::FRAC_PI_2,
+            2.0,
+            near,
+            far,
+        );
+        let depth = |distance: f32| {
+            let [_, _, z, w] = transform(&projection, Vector3::new(0.0, 0.0, -distance));
+            assert_near(w, distance);
+            z / w
+        };
+
+        assert_near(depth(near), 0.0);
+        assert_near(depth(far), 1.0);
+        let middle = depth(10.0);
+        assert!(middle > 0.0 && middle < 1.0);
+        assert!(depth(20.0) > middle);
+    }
+
+    #[test]
+    fn perspective_frustum_edges() {
+        // a 90 degree vertical field of view, twice as wide as it is tall
+        let projection = Matrix4x4::create_perspective_field_of_view(
+            std::f32::consts::FRAC_PI_2,
+            2.0,
+            0.1,
+            100.0,
+        );
+        let [x, y, _, w] = transform(&projection, Vector3::new(10.0, 5.0, -5.0));
+        assert_near(x / w, 1.0);
+        assert_near(y / w, 1.0);
+    }
 }
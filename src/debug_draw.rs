@@ -0,0 +1,318 @@
+use std::f32::consts::TAU;
+use std::ffi::c_void;
+use std::ptr::null_mut;
+
+use sdl3_sys::everything::*;
+
+use crate::app::Frame;
+use crate::common::*;
+use crate::gpu::GpuContext;
+use crate::sprite_batch::SpriteBatch;
+use crate::text::{BitmapFont, TextStyle};
+
+const FONT: &str = "pixel.fnt";
+const MAX_LINES: u32 = 16384;
+const MAX_GLYPHS: u32 = 4096;
+const CIRCLE_SEGMENTS: u32 = 32;
+
+/// How long a primitive stays drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugLifetime {
+    /// Drawn by the next `n` flushes, at least one.
+    Frames(u32),
+    /// Drawn by every flush until the next `begin_step`, however many frames that is.
+    /// Use this for anything added during a fixed step, so it neither flickers when a frame
+    /// has no steps nor piles up when a frame has several.
+    UntilNextStep,
+}
+
+impl Default for DebugLifetime {
+    fn default() -> Self {
+        Self::Frames(1)
+    }
+}
+
+struct DebugLine {
+    from: (f32, f32),
+    to: (f32, f32),
+    color: SDL_FColor,
+    lifetime: DebugLifetime,
+}
+
+struct DebugText {
+    x: f32,
+    y: f32,
+    text: String,
+    color: SDL_FColor,
+    lifetime: DebugLifetime,
+}
+
+/// Immediate mode lines, shapes and labels for debugging, e.g. collision boxes and paths.
+///
+/// Primitives can be added at any time during a fixed step or a frame, and are drawn together
+/// by `flush` at the end of the frame: every line in one draw, every label in another.
+/// Positions are in whatever space `flush`'s transform expects, e.g. the world of a `Camera2D`.
+///
+/// Lines are drawn with PositionColor.vert's inputs through PositionColorTransform.vert,
+/// which adds the transform, and SolidColor.frag.
+pub struct DebugDraw {
+    /// The scale of `text_at` labels.
+    pub text_scale: f32,
+    lifetime: DebugLifetime,
+
+    lines: Vec<DebugLine>,
+    texts: Vec<DebugText>,
+    vertices: Vec<PositionColorVertex>,
+
+    pipeline: *mut SDL_GPUGraphicsPipeline,
+    vertex_buffer: DynamicVertexBuffer<PositionColorVertex>,
+    font: BitmapFont,
+    text_batch: SpriteBatch,
+    sampler: *mut SDL_GPUSampler,
+}
+
+impl DebugDraw {
+    pub unsafe fn new(gpu: &GpuContext) -> Option<Self> {
+        let device = gpu.device;
+        let format = gpu.target_format();
+
+        let pipeline = create_graphics_pipeline_with_primitive(
+            device,
+            format,
+            "PositionColorTransform.vert",
+            "SolidColor.frag",
+            &VertexInputLayout::new().with_vertices::<PositionColorVertex>(),
+            SDL_GPU_PRIMITIVETYPE_LINELIST,
+        );
+        if pipeline.is_null() {
+            return None;
+        }
+
+        let vertex_buffer = DynamicVertexBuffer::new(device, MAX_LINES * 2)?;
+        let font = BitmapFont::load(device, FONT)?;
+        let text_batch = SpriteBatch::new(device, format, MAX_GLYPHS)?;
+        let sampler = create_sampler(
+            device,
+            SDL_GPU_FILTER_NEAREST,
+            SDL_GPU_SAMPLERADDRESSMODE_CLAMP_TO_EDGE,
+        );
+
+        Some(Self {
+            text_scale: 1.0,
+            lifetime: DebugLifetime::default(),
+            lines: Vec::new(),
+            texts: Vec::new(),
+            vertices: Vec::with_capacity(MAX_LINES as usize * 2),
+            pipeline,
+            vertex_buffer,
+            font,
+            text_batch,
+            sampler,
+        })
+    }
+
+    pub unsafe fn release(&mut self, device: *mut SDL_GPUDevice) {
+        if !self.pipeline.is_null() {
+            SDL_ReleaseGPUGraphicsPipeline(device, self.pipeline);
+            self.pipeline = null_mut();
+        }
+        self.vertex_buffer.release(device);
+        self.font.release(device);
+        self.text_batch.release(device);
+        if !self.sampler.is_null() {
+            SDL_ReleaseGPUSampler(device, self.sampler);
+            self.sampler = null_mut();
+        }
+    }
+
+    /// Anything added within `f` is drawn with `lifetime` rather than for a single frame.
+    pub fn with_lifetime(&mut self, lifetime: DebugLifetime, f: impl FnOnce(&mut Self)) {
+        let previous = std::mem::replace(&mut self.lifetime, lifetime);
+        f(self);
+        self.lifetime = previous;
+    }
+
+    /// Anything added within `f` is drawn by the next `frames` flushes.
+    pub fn for_frames(&mut self, frames: u32, f: impl FnOnce(&mut Self)) {
+        self.with_lifetime(DebugLifetime::Frames(frames), f);
+    }
+
+    /// Anything added within `f` is drawn until the next `begin_step`.
+    pub fn until_next_step(&mut self, f: impl FnOnce(&mut Self)) {
+        self.with_lifetime(DebugLifetime::UntilNextStep, f);
+    }
+
+    /// Drops what the last fixed step added with `DebugLifetime::UntilNextStep`.
+    /// Call at the start of each fixed step.
+    pub fn begin_step(&mut self) {
+        self.lines
+            .retain(|line| line.lifetime != DebugLifetime::UntilNextStep);
+        self.texts
+            .retain(|text| text.lifetime != DebugLifetime::UntilNextStep);
+    }
+
+    /// How many lines will be drawn by the next flush.
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn line(&mut self, from: (f32, f32), to: (f32, f32), color: SDL_FColor) {
+        self.lines.push(DebugLine {
+            from,
+            to,
+            color,
+            lifetime: self.lifetime,
+        });
+    }
+
+    /// An outline from `(x, y)` to `(x + w, y + h)`.
+    pub fn rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: SDL_FColor) {
+        let corners = [(x, y), (x + w, y), (x + w, y + h), (x, y + h)];
+        for i in 0..corners.len() {
+            self.line(corners[i], corners[(i + 1) % corners.len()], color);
+        }
+    }
+
+    pub fn circle(&mut self, center: (f32, f32), radius: f32, color: SDL_FColor) {
+        let point = |i: u32| {
+            let (sin, cos) = (i as f32 / CIRCLE_SEGMENTS as f32 * TAU).sin_cos();
+            (center.0 + cos * radius, center.1 + sin * radius)
+        };
+        for i in 0..CIRCLE_SEGMENTS {
+            self.line(point(i), point(i + 1), color);
+        }
+    }
+
+    /// A line with a head at `to`, sized to the line but no larger than 12 units.
+    pub fn arrow(&mut self, from: (f32, f32), to: (f32, f32), color: SDL_FColor) {
+        self.line(from, to, color);
+
+        let dx = to.0 - from.0;
+        let dy = to.1 - from.1;
+        let length = (dx * dx + dy * dy).sqrt();
+        if length <= f32::EPSILON {
+            return;
+        }
+        let head = (length * 0.25).min(12.0);
+        let (back_x, back_y) = (-dx / length * head, -dy / length * head);
+        // the two sides of the head, 30 degrees either side of the line
+        let (sin, cos) = (TAU / 12.0).sin_cos();
+        self.line(
+            to,
+            (
+                to.0 + back_x * cos - back_y * sin,
+                to.1 + back_x * sin + back_y * cos,
+            ),
+            color,
+        );
+        self.line(
+            to,
+            (
+                to.0 + back_x * cos + back_y * sin,
+                to.1 - back_x * sin + back_y * cos,
+            ),
+            color,
+        );
+    }
+
+    /// A label with its top left at `(x, y)`, at `text_scale`.
+    pub fn text_at(&mut self, x: f32, y: f32, text: impl Into<String>, color: SDL_FColor) {
+        self.texts.push(DebugText {
+            x,
+            y,
+            text: text.into(),
+            color,
+            lifetime: self.lifetime,
+        });
+    }
+
+    /// Draws everything over `frame.target` with `transform`, then ages it, dropping
+    /// whatever has been drawn for its lifetime. Lines past the buffer's capacity are skipped.
+    /// Must be called outside of a render pass, after everything else is drawn.
+    pub unsafe fn flush(&mut self, frame: &Frame, transform: &Matrix4x4) -> bool {
+        self.vertices.clear();
+        for line in &self.lines {
+            for (x, y) in [line.from, line.to] {
+                self.vertices.push(PositionColorVertex {
+                    position: [x, y, 0.0],
+                    color: line.color,
+                });
+            }
+        }
+        let style = TextStyle {
+            scale: self.text_scale,
+            ..Default::default()
+        };
+        for text in &self.texts {
+            let style = TextStyle {
+                color: text.color,
+                ..style
+            };
+            self.font
+                .push_text(&mut self.text_batch, &text.text, text.x, text.y, &style);
+        }
+
+        if !self
+            .vertex_buffer
+            .upload(frame.device, frame.command_buffer, &self.vertices)
+            || !self.text_batch.upload(frame.device, frame.command_buffer)
+        {
+            return false;
+        }
+        self.age();
+
+        if self.vertex_buffer.is_empty() && self.text_batch.uploaded_len() == 0 {
+            return true;
+        }
+
+        let render_pass = SDL_BeginGPURenderPass(
+            frame.command_buffer,
+            &SDL_GPUColorTargetInfo {
+                texture: frame.target,
+                load_op: SDL_GPULoadOp::LOAD,
+                store_op: SDL_GPUStoreOp::STORE,
+                ..Default::default()
+            },
+            1,
+            null_mut(),
+        );
+
+        if !self.vertex_buffer.is_empty() {
+            SDL_BindGPUGraphicsPipeline(render_pass, self.pipeline);
+            self.vertex_buffer.bind(render_pass, 0);
+            SDL_PushGPUVertexUniformData(
+                frame.command_buffer,
+                0,
+                transform as *const Matrix4x4 as *const c_void,
+                std::mem::size_of::<Matrix4x4>() as u32,
+            );
+            SDL_DrawGPUPrimitives(render_pass, self.vertex_buffer.len(), 1, 0, 0);
+        }
+        self.text_batch.draw(
+            frame.command_buffer,
+            render_pass,
+            self.font.texture(),
+            self.sampler,
+            transform,
+        );
+
+        SDL_EndGPURenderPass(render_pass);
+
+        true
+    }
+
+    fn age(&mut self) {
+        fn tick(lifetime: &mut DebugLifetime) -> bool {
+            match lifetime {
+                DebugLifetime::Frames(frames) if *frames > 1 => {
+                    *frames -= 1;
+                    true
+                }
+                DebugLifetime::Frames(_) => false,
+                DebugLifetime::UntilNextStep => true,
+            }
+        }
+        self.lines.retain_mut(|line| tick(&mut line.lifetime));
+        self.texts.retain_mut(|text| tick(&mut text.lifetime));
+    }
+}
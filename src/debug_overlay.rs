@@ -0,0 +1,302 @@
+use std::collections::VecDeque;
+use std::ffi::CStr;
+use std::ptr::null_mut;
+
+use sdl3_sys::everything::*;
+
+use crate::app::Frame;
+use crate::camera::ScreenCamera;
+use crate::common::*;
+use crate::gpu::{GpuContext, present_mode_name};
+use crate::input::Input;
+use crate::sprite_batch::{GPUSprite, SpriteBatch};
+use crate::text::{BitmapFont, TextStyle};
+
+/// Shows and hides the overlay; see `DebugOverlay::fixed_update`.
+pub const TOGGLE_KEY: SDL_Scancode = SDL_Scancode::F3;
+
+const FONT: &str = "pixel.fnt";
+const MAX_GLYPHS: u32 = 512;
+
+/// About two seconds of frames at 60 fps.
+const HISTORY_LEN: usize = 120;
+
+const TEXT_SCALE: f32 = 2.0;
+const MARGIN: f32 = 8.0;
+const PADDING: f32 = 6.0;
+const GRAPH_HEIGHT: f32 = 48.0;
+const GRAPH_BAR_WIDTH: f32 = 2.0;
+/// Frame times at or above this fill the graph's height.
+const GRAPH_MAX_MS: f32 = 50.0;
+/// Lines across the graph at 60 and 30 fps.
+const GRAPH_REFERENCE_MS: [f32; 2] = [1000.0 / 60.0, 1000.0 / 30.0];
+
+/// What a game drew in one frame, for the overlay to show.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameStats {
+    pub sprites: u32,
+    pub draw_calls: u32,
+    pub bytes_uploaded: usize,
+}
+
+impl FrameStats {
+    /// Counts a sprite batch's last upload, and its draw if it has sprites.
+    pub fn add_batch(&mut self, batch: &SpriteBatch) {
+        self.sprites += batch.uploaded_len();
+        self.bytes_uploaded += batch.uploaded_bytes();
+        if batch.uploaded_len() > 0 {
+            self.draw_calls += 1;
+        }
+    }
+}
+
+/// Frame timing and gpu stats drawn over a frame, built from a bitmap font and sprite batches.
+///
+/// Each fixed step call `fixed_update`, which also toggles the overlay with `TOGGLE_KEY`.
+/// Each frame add to `stats` while rendering, then call `draw` last, outside of any pass.
+/// Timing is recorded while hidden too, so the graph is full as soon as it's shown.
+pub struct DebugOverlay {
+    pub visible: bool,
+    /// Filled in by the game during a frame and cleared by `draw`.
+    pub stats: FrameStats,
+    present_mode: Option<SDL_GPUPresentMode>,
+    driver: String,
+
+    font: BitmapFont,
+    text_batch: SpriteBatch,
+    shape_batch: SpriteBatch,
+    white_texture: *mut SDL_GPUTexture,
+    sampler: *mut SDL_GPUSampler,
+    camera: ScreenCamera,
+
+    frame_times_ms: VecDeque<f32>,
+    last_frame_ns: u64,
+    steps_since_draw: u32,
+}
+
+impl DebugOverlay {
+    pub unsafe fn new(gpu: &GpuContext) -> Option<Self> {
+        let device = gpu.device;
+        let format = gpu.target_format();
+
+        let font = BitmapFont::load(device, FONT)?;
+        let text_batch = SpriteBatch::new(device, format, MAX_GLYPHS)?;
+        // the panel, the graph's bars and its reference lines
+        let shape_batch = SpriteBatch::new(
+            device,
+            format,
+            HISTORY_LEN as u32 + 1 + GRAPH_REFERENCE_MS.len() as u32,
+        )?;
+
+        let white_texture = create_white_texture(device);
+        if white_texture.is_null() {
+            return None;
+        }
+        let sampler = create_sampler(
+            device,
+            SDL_GPU_FILTER_NEAREST,
+            SDL_GPU_SAMPLERADDRESSMODE_CLAMP_TO_EDGE,
+        );
+
+        let driver = SDL_GetGPUDeviceDriver(device);
+        let driver = if driver.is_null() {
+            "unknown".to_string()
+        } else {
+            CStr::from_ptr(driver).to_string_lossy().into_owned()
+        };
+
+        Some(Self {
+            visible: false,
+            stats: FrameStats::default(),
+            present_mode: None,
+            driver,
+            font,
+            text_batch,
+            shape_batch,
+            white_texture,
+            sampler,
+            camera: ScreenCamera::new(0.0, 0.0),
+            frame_times_ms: VecDeque::with_capacity(HISTORY_LEN),
+            last_frame_ns: 0,
+            steps_since_draw: 0,
+        })
+    }
+
+    pub unsafe fn release(&mut self, device: *mut SDL_GPUDevice) {
+        self.font.release(device);
+        self.text_batch.release(device);
+        self.shape_batch.release(device);
+        if !self.white_texture.is_null() {
+            SDL_ReleaseGPUTexture(device, self.white_texture);
+            self.white_texture = null_mut();
+        }
+        if !self.sampler.is_null() {
+            SDL_ReleaseGPUSampler(device, self.sampler);
+            self.sampler = null_mut();
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Counts fixed steps between frames, and toggles the overlay on `TOGGLE_KEY`.
+    pub fn fixed_update(&mut self, input: &Input) {
+        self.steps_since_draw += 1;
+        if input.just_pressed(TOGGLE_KEY) {
+            self.toggle();
+        }
+    }
+
+    /// Records this frame's timing, and draws over `frame.target` if visible.
+    /// Must be called outside of a render pass, after everything else is drawn.
+    pub unsafe fn draw(&mut self, frame: &Frame) -> bool {
+        let now = SDL_GetTicksNS();
+        if self.last_frame_ns != 0 {
+            if self.frame_times_ms.len() == HISTORY_LEN {
+                self.frame_times_ms.pop_front();
+            }
+            self.frame_times_ms
+                .push_back((now - self.last_frame_ns) as f32 / 1_000_000.0);
+        }
+        self.last_frame_ns = now;
+        self.present_mode = frame.present_mode;
+
+        let stats = std::mem::take(&mut self.stats);
+        let steps = std::mem::take(&mut self.steps_since_draw);
+        if !self.visible {
+            return true;
+        }
+
+        self.camera.update(frame);
+        self.push_overlay(&stats, steps);
+
+        if !self.shape_batch.upload(frame.device, frame.command_buffer)
+            || !self.text_batch.upload(frame.device, frame.command_buffer)
+        {
+            return false;
+        }
+
+        let render_pass = SDL_BeginGPURenderPass(
+            frame.command_buffer,
+            &SDL_GPUColorTargetInfo {
+                texture: frame.target,
+                load_op: SDL_GPULoadOp::LOAD,
+                store_op: SDL_GPUStoreOp::STORE,
+                ..Default::default()
+            },
+            1,
+            null_mut(),
+        );
+
+        self.shape_batch.draw(
+            frame.command_buffer,
+            render_pass,
+            self.white_texture,
+            self.sampler,
+            self.camera.matrix(),
+        );
+        self.text_batch.draw(
+            frame.command_buffer,
+            render_pass,
+            self.font.texture(),
+            self.sampler,
+            self.camera.matrix(),
+        );
+
+        SDL_EndGPURenderPass(render_pass);
+
+        true
+    }
+
+    fn push_overlay(&mut self, stats: &FrameStats, steps: u32) {
+        let frame_count = self.frame_times_ms.len().max(1) as f32;
+        let average_ms = self.frame_times_ms.iter().sum::<f32>() / frame_count;
+        let worst_ms = self.frame_times_ms.iter().copied().fold(0.0, f32::max);
+        let fps = if average_ms > 0.0 {
+            1000.0 / average_ms
+        } else {
+            0.0
+        };
+        let present_mode = self.present_mode.map_or("unknown", present_mode_name);
+
+        let text = format!(
+            "{fps:.0} fps  {average_ms:.2} ms  worst {worst_ms:.1} ms\n\
+             fixed steps {steps}\n\
+             sprites {}  draw calls {}\n\
+             uploaded {}\n\
+             present {present_mode}  driver {}",
+            stats.sprites,
+            stats.draw_calls,
+            format_bytes(stats.bytes_uploaded),
+            self.driver,
+        );
+
+        let style = TextStyle {
+            scale: TEXT_SCALE,
+            ..Default::default()
+        };
+        let (text_width, text_height) = self.font.measure(&text, &style);
+        let graph_width = HISTORY_LEN as f32 * GRAPH_BAR_WIDTH;
+
+        let x = MARGIN + PADDING;
+        let y = MARGIN + PADDING;
+        self.shape_batch.push(GPUSprite {
+            x: MARGIN,
+            y: MARGIN,
+            w: text_width.max(graph_width) + PADDING * 2.0,
+            h: text_height + PADDING * 3.0 + GRAPH_HEIGHT,
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 0.7,
+            ..Default::default()
+        });
+        self.font
+            .push_text(&mut self.text_batch, &text, x, y, &style);
+
+        let graph_bottom = y + text_height + PADDING + GRAPH_HEIGHT;
+        for (i, ms) in self.frame_times_ms.iter().enumerate() {
+            let height = (ms / GRAPH_MAX_MS).min(1.0) * GRAPH_HEIGHT;
+            let (r, g) = if *ms <= GRAPH_REFERENCE_MS[0] * 1.1 {
+                (0.2, 1.0)
+            } else if *ms <= GRAPH_REFERENCE_MS[1] * 1.1 {
+                (1.0, 0.9)
+            } else {
+                (1.0, 0.2)
+            };
+            self.shape_batch.push(GPUSprite {
+                x: x + i as f32 * GRAPH_BAR_WIDTH,
+                y: graph_bottom - height,
+                w: GRAPH_BAR_WIDTH,
+                h: height,
+                r,
+                g,
+                b: 0.2,
+                ..Default::default()
+            });
+        }
+        for ms in GRAPH_REFERENCE_MS {
+            self.shape_batch.push(GPUSprite {
+                x,
+                y: graph_bottom - ms / GRAPH_MAX_MS * GRAPH_HEIGHT,
+                w: graph_width,
+                h: 1.0,
+                a: 0.4,
+                ..Default::default()
+            });
+        }
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const KIB: f32 = 1024.0;
+    let bytes_f = bytes as f32;
+    if bytes_f >= KIB * KIB {
+        format!("{:.1} MiB", bytes_f / (KIB * KIB))
+    } else if bytes_f >= KIB {
+        format!("{:.1} KiB", bytes_f / KIB)
+    } else {
+        format!("{bytes} B")
+    }
+}
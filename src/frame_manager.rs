@@ -0,0 +1,89 @@
+use std::collections::VecDeque;
+
+use sdl3_sys::everything::*;
+
+use crate::common::dbg_sdl_error;
+
+/// Caps how many submitted frames the gpu can be working on, and holds on to each frame's `T`
+/// until the gpu is done with that frame, e.g. transfer buffers to read back or release.
+///
+/// Each frame: drain `pop_finished` before acquiring a command buffer, which waits while
+/// `is_full`, then `submit` the command buffer with the frame's data.
+/// Before releasing anything the frames use, drain with `wait_idle`.
+pub struct FrameManager<T> {
+    max_frames_in_flight: usize,
+    frames: VecDeque<(*mut SDL_GPUFence, T)>,
+}
+
+impl<T> FrameManager<T> {
+    /// At least one frame is always allowed.
+    pub fn new(max_frames_in_flight: u32) -> Self {
+        let max_frames_in_flight = max_frames_in_flight.max(1) as usize;
+        Self {
+            max_frames_in_flight,
+            frames: VecDeque::with_capacity(max_frames_in_flight),
+        }
+    }
+
+    pub fn max_frames_in_flight(&self) -> u32 {
+        self.max_frames_in_flight as u32
+    }
+
+    /// How many submitted frames haven't been popped yet, finished or not.
+    pub fn frames_in_flight(&self) -> u32 {
+        self.frames.len() as u32
+    }
+
+    /// Whether another frame can't be submitted until the oldest finishes.
+    pub fn is_full(&self) -> bool {
+        self.frames.len() >= self.max_frames_in_flight
+    }
+
+    /// Submits a frame's command buffer, keeping `data` until its fence signals.
+    /// If the command buffer couldn't be submitted, `data` is handed back to be released.
+    pub unsafe fn submit(
+        &mut self,
+        command_buffer: *mut SDL_GPUCommandBuffer,
+        data: T,
+    ) -> Result<(), T> {
+        let fence = SDL_SubmitGPUCommandBufferAndAcquireFence(command_buffer);
+        if fence.is_null() {
+            dbg_sdl_error("failed to submit command buffer");
+            return Err(data);
+        }
+
+        self.frames.push_back((fence, data));
+        Ok(())
+    }
+
+    /// Returns the oldest frame's data once the gpu has finished it, blocking while the
+    /// manager `is_full`. Call until it returns None to collect every finished frame, in order.
+    pub unsafe fn pop_finished(&mut self, device: *mut SDL_GPUDevice) -> Option<T> {
+        let wait = self.is_full();
+        self.pop(device, wait)
+    }
+
+    /// Blocks until the gpu has finished every frame, returning their data in order.
+    pub unsafe fn wait_idle(&mut self, device: *mut SDL_GPUDevice) -> Vec<T> {
+        let mut finished = Vec::with_capacity(self.frames.len());
+        while let Some(data) = self.pop(device, true) {
+            finished.push(data);
+        }
+        finished
+    }
+
+    unsafe fn pop(&mut self, device: *mut SDL_GPUDevice, wait: bool) -> Option<T> {
+        let (fence, _) = self.frames.front()?;
+        let fence = *fence;
+        if wait {
+            if !SDL_WaitForGPUFences(device, true, &fence, 1) {
+                dbg_sdl_error("failed to wait for gpu fence");
+            }
+        } else if !SDL_QueryGPUFence(device, fence) {
+            return None;
+        }
+
+        SDL_ReleaseGPUFence(device, fence);
+        self.frames.pop_front().map(|(_, data)| data)
+    }
+}
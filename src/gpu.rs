@@ -0,0 +1,486 @@
+use std::ffi::CStr;
+use std::ptr::{null, null_mut};
+
+use sdl3_sys::everything::*;
+
+use crate::common::*;
+use crate::image::Image;
+
+/// The format of the offscreen render target in headless mode.
+pub const OFFSCREEN_FORMAT: SDL_GPUTextureFormat = SDL_GPUTextureFormat::R8G8B8A8_UNORM;
+
+/// A gpu device and the place frames are rendered to:
+/// either a window's swapchain, or an offscreen color texture when headless.
+///
+/// Headless mode only asks for SPIR-V and the Vulkan driver, so it works with a
+/// software implementation like lavapipe on machines without a GPU, e.g.
+/// `VK_ICD_FILENAMES=/usr/share/vulkan/icd.d/lvp_icd.x86_64.json`.
+pub struct GpuContext {
+    pub device: *mut SDL_GPUDevice,
+    /// Null when headless.
+    pub window: *mut SDL_Window,
+    fullscreen_mode: FullscreenMode,
+    swapchain_settings: SwapchainSettings,
+    present_mode: SDL_GPUPresentMode,
+    offscreen_texture: *mut SDL_GPUTexture,
+    offscreen_width: u32,
+    offscreen_height: u32,
+}
+
+impl Drop for GpuContext {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.offscreen_texture.is_null() {
+                SDL_ReleaseGPUTexture(self.device, self.offscreen_texture);
+            }
+
+            deinit_gpu_window(self.device, self.window);
+        }
+    }
+}
+
+impl GpuContext {
+    pub fn windowed(
+        window_title: &CStr,
+        width: i32,
+        height: i32,
+        window_flags: SDL_WindowFlags,
+    ) -> Option<Self> {
+        let (window, device) = init_gpu_window(window_title, width, height, window_flags)?;
+
+        Some(Self {
+            device,
+            window,
+            fullscreen_mode: FullscreenMode::Windowed,
+            swapchain_settings: SwapchainSettings::VSYNC,
+            present_mode: SDL_GPUPresentMode::VSYNC,
+            offscreen_texture: null_mut(),
+            offscreen_width: 0,
+            offscreen_height: 0,
+        })
+    }
+
+    pub fn headless(width: u32, height: u32) -> Option<Self> {
+        unsafe {
+            let device = SDL_CreateGPUDevice(SDL_GPU_SHADERFORMAT_SPIRV, true, c"vulkan".as_ptr());
+            if device.is_null() {
+                dbg_sdl_error("SDL_CreateGPUDevice failed");
+                return None;
+            }
+
+            let offscreen_texture = SDL_CreateGPUTexture(
+                device,
+                &SDL_GPUTextureCreateInfo {
+                    r#type: SDL_GPUTextureType::_2D,
+                    format: OFFSCREEN_FORMAT,
+                    width,
+                    height,
+                    layer_count_or_depth: 1,
+                    num_levels: 1,
+                    usage: SDL_GPU_TEXTUREUSAGE_COLOR_TARGET | SDL_GPU_TEXTUREUSAGE_SAMPLER,
+                    ..Default::default()
+                },
+            );
+            if offscreen_texture.is_null() {
+                dbg_sdl_error("failed to create offscreen texture");
+                SDL_DestroyGPUDevice(device);
+                return None;
+            }
+
+            Some(Self {
+                device,
+                window: null_mut(),
+                fullscreen_mode: FullscreenMode::Windowed,
+                swapchain_settings: SwapchainSettings::VSYNC,
+                present_mode: SDL_GPUPresentMode::VSYNC,
+                offscreen_texture,
+                offscreen_width: width,
+                offscreen_height: height,
+            })
+        }
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.window.is_null()
+    }
+
+    /// The format pipelines should use for their color target.
+    pub fn target_format(&self) -> SDL_GPUTextureFormat {
+        if self.is_headless() {
+            OFFSCREEN_FORMAT
+        } else {
+            unsafe { SDL_GetGPUSwapchainTextureFormat(self.device, self.window) }
+        }
+    }
+
+    /// Pixels per logical window coordinate, e.g. 2 on a display scaled to 200%.
+    /// Frames are rendered at full pixel resolution; this is for converting window
+    /// coordinates like mouse positions to target pixels, and sizing things in points.
+    /// Always 1 when headless.
+    pub fn pixel_density(&self) -> f32 {
+        if self.is_headless() {
+            return 1.0;
+        }
+
+        let density = unsafe { SDL_GetWindowPixelDensity(self.window) };
+        if density > 0.0 { density } else { 1.0 }
+    }
+
+    pub fn fullscreen_mode(&self) -> FullscreenMode {
+        self.fullscreen_mode
+    }
+
+    /// Returns false if the window couldn't change mode, or when headless.
+    /// The new size arrives with the next swapchain texture.
+    pub fn set_fullscreen_mode(&mut self, mode: FullscreenMode) -> bool {
+        if self.is_headless() {
+            return false;
+        }
+
+        unsafe {
+            let changed = match mode {
+                FullscreenMode::Windowed => SDL_SetWindowFullscreen(self.window, false),
+                FullscreenMode::Borderless => {
+                    // a null mode means fullscreen at the desktop resolution, without a mode change
+                    SDL_SetWindowFullscreenMode(self.window, null())
+                        && SDL_SetWindowFullscreen(self.window, true)
+                }
+                FullscreenMode::Exclusive => {
+                    let display = SDL_GetDisplayForWindow(self.window);
+                    let desktop_mode = SDL_GetDesktopDisplayMode(display);
+                    !desktop_mode.is_null()
+                        && SDL_SetWindowFullscreenMode(self.window, desktop_mode)
+                        && SDL_SetWindowFullscreen(self.window, true)
+                }
+            };
+            if !changed {
+                dbg_sdl_error("failed to change fullscreen mode");
+                return false;
+            }
+        }
+
+        self.fullscreen_mode = mode;
+        true
+    }
+
+    pub fn swapchain_settings(&self) -> SwapchainSettings {
+        self.swapchain_settings
+    }
+
+    /// The present mode chosen from the swapchain settings, or None when headless.
+    pub fn present_mode(&self) -> Option<SDL_GPUPresentMode> {
+        (!self.is_headless()).then_some(self.present_mode)
+    }
+
+    /// Reconfigures the swapchain, keeping the current settings if the window doesn't support
+    /// the composition. Returns false on failure, or when headless.
+    /// Changing the composition changes `target_format`, which existing pipelines were built for.
+    pub fn set_swapchain_settings(&mut self, settings: SwapchainSettings) -> bool {
+        if self.is_headless() {
+            return false;
+        }
+
+        let Some(present_mode) = (unsafe { settings.apply(self.device, self.window) }) else {
+            return false;
+        };
+        self.swapchain_settings = settings;
+        self.present_mode = present_mode;
+        true
+    }
+
+    pub fn is_vsync(&self) -> bool {
+        self.present_mode == SDL_GPUPresentMode::VSYNC
+    }
+
+    /// Switches between VSYNC and the best of the settings' other present modes, leaving the
+    /// settings and the window's current composition as they are. Returns false if the present
+    /// mode didn't change, e.g. because the window only supports VSYNC, or when headless.
+    pub fn set_vsync(&mut self, vsync: bool) -> bool {
+        if self.is_headless() || vsync == self.is_vsync() {
+            return false;
+        }
+
+        let settings = SwapchainSettings {
+            present_modes: if vsync {
+                &[]
+            } else {
+                self.swapchain_settings.present_modes
+            },
+            composition: self.current_composition(),
+        };
+        match unsafe { settings.apply(self.device, self.window) } {
+            Some(present_mode) if present_mode != self.present_mode => {
+                self.present_mode = present_mode;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Chooses the present mode again if the window no longer supports the current one,
+    /// e.g. after moving to another display. Returns true if it changed.
+    pub fn revalidate_present_mode(&mut self) -> bool {
+        if self.is_headless() {
+            return false;
+        }
+
+        unsafe {
+            if SDL_WindowSupportsGPUPresentMode(self.device, self.window, self.present_mode) {
+                return false;
+            }
+
+            let settings = SwapchainSettings {
+                composition: self.current_composition(),
+                ..self.swapchain_settings
+            };
+            match settings.apply(self.device, self.window) {
+                Some(present_mode) => {
+                    self.present_mode = present_mode;
+                    true
+                }
+                None => false,
+            }
+        }
+    }
+
+    /// The window's composition, read back from its swapchain format, since something other
+    /// than the settings may have changed it, like `HdrPipeline::set_output`.
+    fn current_composition(&self) -> SDL_GPUSwapchainComposition {
+        match self.target_format() {
+            SDL_GPUTextureFormat::B8G8R8A8_UNORM_SRGB
+            | SDL_GPUTextureFormat::R8G8B8A8_UNORM_SRGB => SDL_GPUSwapchainComposition::SDR_LINEAR,
+            SDL_GPUTextureFormat::R16G16B16A16_FLOAT => {
+                SDL_GPUSwapchainComposition::HDR_EXTENDED_LINEAR
+            }
+            SDL_GPUTextureFormat::R10G10B10A2_UNORM => SDL_GPUSwapchainComposition::HDR10_ST2084,
+            _ => SDL_GPUSwapchainComposition::SDR,
+        }
+    }
+
+    /// The offscreen color texture; null unless headless.
+    pub fn offscreen_texture(&self) -> *mut SDL_GPUTexture {
+        self.offscreen_texture
+    }
+
+    /// Returns the texture to render this frame into, or None on failure.
+    /// The texture is null when there's nothing to draw to this frame (e.g. a minimized window).
+    pub unsafe fn acquire_target(
+        &self,
+        command_buffer: *mut SDL_GPUCommandBuffer,
+    ) -> Option<RenderTarget> {
+        if self.is_headless() {
+            return Some(RenderTarget {
+                texture: self.offscreen_texture,
+                width: self.offscreen_width,
+                height: self.offscreen_height,
+            });
+        }
+
+        let mut target = RenderTarget {
+            texture: null_mut(),
+            width: 0,
+            height: 0,
+        };
+        if !SDL_WaitAndAcquireGPUSwapchainTexture(
+            command_buffer,
+            self.window,
+            &mut target.texture,
+            &mut target.width,
+            &mut target.height,
+        ) {
+            dbg_sdl_error("failed to acquire swapchain texture");
+            return None;
+        }
+
+        Some(target)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FullscreenMode {
+    #[default]
+    Windowed,
+    /// A borderless window covering the display; quick to switch to and from.
+    Borderless,
+    /// Takes over the display with its own video mode, at the desktop resolution.
+    /// Can skip the compositor, at the cost of slower switching.
+    Exclusive,
+}
+
+impl FullscreenMode {
+    pub const ALL: [FullscreenMode; 3] = [Self::Windowed, Self::Borderless, Self::Exclusive];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|m| *m == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+pub fn present_mode_name(present_mode: SDL_GPUPresentMode) -> &'static str {
+    match present_mode {
+        SDL_GPUPresentMode::VSYNC => "vsync",
+        SDL_GPUPresentMode::IMMEDIATE => "immediate",
+        SDL_GPUPresentMode::MAILBOX => "mailbox",
+        _ => "unknown",
+    }
+}
+
+pub struct RenderTarget {
+    pub texture: *mut SDL_GPUTexture,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A texture copy recorded into a command buffer, waiting to be read back.
+///
+/// Use `download_texture` for a one-off readback; this is for reading back a frame's
+/// target in the same command buffer it was rendered with, e.g. the swapchain texture,
+/// which can't be used after that command buffer is submitted.
+pub struct TextureDownload {
+    transfer_buffer: *mut SDL_GPUTransferBuffer,
+    width: u32,
+    height: u32,
+    swap_red_blue: bool,
+}
+
+impl TextureDownload {
+    /// Records a copy of the texture into a download transfer buffer. Must be called outside of a pass.
+    /// Only 8 bit RGBA and BGRA formats can be read back.
+    pub unsafe fn record(
+        device: *mut SDL_GPUDevice,
+        command_buffer: *mut SDL_GPUCommandBuffer,
+        texture: *mut SDL_GPUTexture,
+        format: SDL_GPUTextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Option<Self> {
+        let swap_red_blue =
+            match format {
+                SDL_GPUTextureFormat::R8G8B8A8_UNORM
+                | SDL_GPUTextureFormat::R8G8B8A8_UNORM_SRGB => false,
+                SDL_GPUTextureFormat::B8G8R8A8_UNORM
+                | SDL_GPUTextureFormat::B8G8R8A8_UNORM_SRGB => true,
+                _ => {
+                    println!("can't download texture format {format:?}");
+                    return None;
+                }
+            };
+
+        let transfer_buffer = SDL_CreateGPUTransferBuffer(
+            device,
+            &SDL_GPUTransferBufferCreateInfo {
+                usage: SDL_GPUTransferBufferUsage::DOWNLOAD,
+                size: width * height * 4,
+                ..Default::default()
+            },
+        );
+        if transfer_buffer.is_null() {
+            dbg_sdl_error("failed to create download transfer buffer");
+            return None;
+        }
+
+        let copy_pass = SDL_BeginGPUCopyPass(command_buffer);
+        SDL_DownloadFromGPUTexture(
+            copy_pass,
+            &SDL_GPUTextureRegion {
+                texture,
+                w: width,
+                h: height,
+                d: 1,
+                ..Default::default()
+            },
+            &SDL_GPUTextureTransferInfo {
+                transfer_buffer,
+                offset: 0,
+                ..Default::default()
+            },
+        );
+        SDL_EndGPUCopyPass(copy_pass);
+
+        Some(Self {
+            transfer_buffer,
+            width,
+            height,
+            swap_red_blue,
+        })
+    }
+
+    /// Reads the pixels back once the fence from submitting the command buffer has signaled.
+    pub unsafe fn finish(self, device: *mut SDL_GPUDevice) -> Option<Image> {
+        let mut image = Image::new(self.width, self.height);
+
+        let mapped = SDL_MapGPUTransferBuffer(device, self.transfer_buffer, false);
+        if !mapped.is_null() {
+            std::ptr::copy_nonoverlapping(
+                mapped as *const u8,
+                image.pixels.as_mut_ptr(),
+                image.pixels.len(),
+            );
+            SDL_UnmapGPUTransferBuffer(device, self.transfer_buffer);
+        } else {
+            dbg_sdl_error("failed to map download transfer buffer");
+        }
+        SDL_ReleaseGPUTransferBuffer(device, self.transfer_buffer);
+
+        if mapped.is_null() {
+            return None;
+        }
+
+        if self.swap_red_blue {
+            for pixel in image.pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        Some(image)
+    }
+
+    /// Releases the transfer buffer without reading it back, e.g. when the command buffer
+    /// the copy was recorded into couldn't be submitted.
+    pub unsafe fn release(self, device: *mut SDL_GPUDevice) {
+        SDL_ReleaseGPUTransferBuffer(device, self.transfer_buffer);
+    }
+
+    /// Submits the command buffer, blocks until the gpu is done with it, and reads the pixels back.
+    pub unsafe fn submit_and_finish(
+        self,
+        device: *mut SDL_GPUDevice,
+        command_buffer: *mut SDL_GPUCommandBuffer,
+    ) -> Option<Image> {
+        let fence = SDL_SubmitGPUCommandBufferAndAcquireFence(command_buffer);
+        if fence.is_null() {
+            dbg_sdl_error("failed to submit command buffer");
+            self.release(device);
+            return None;
+        }
+        SDL_WaitForGPUFences(device, true, &fence, 1);
+        SDL_ReleaseGPUFence(device, fence);
+
+        self.finish(device)
+    }
+}
+
+/// Copies a texture back from the gpu, waiting for all previously submitted work that writes to it.
+/// Only 8 bit RGBA and BGRA formats can be read back.
+pub unsafe fn download_texture(
+    device: *mut SDL_GPUDevice,
+    texture: *mut SDL_GPUTexture,
+    format: SDL_GPUTextureFormat,
+    width: u32,
+    height: u32,
+) -> Option<Image> {
+    let command_buffer = SDL_AcquireGPUCommandBuffer(device);
+    if command_buffer.is_null() {
+        dbg_sdl_error("failed to acquire command buffer");
+        return None;
+    }
+
+    let Some(download) =
+        TextureDownload::record(device, command_buffer, texture, format, width, height)
+    else {
+        SDL_CancelGPUCommandBuffer(command_buffer);
+        return None;
+    };
+
+    download.submit_and_finish(device, command_buffer)
+}
@@ -0,0 +1,235 @@
+/// A cell position; may be outside the grid until checked with `Grid::contains`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GridPos {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl GridPos {
+    pub const UP: GridPos = GridPos::new(0, -1);
+    pub const DOWN: GridPos = GridPos::new(0, 1);
+    pub const LEFT: GridPos = GridPos::new(-1, 0);
+    pub const RIGHT: GridPos = GridPos::new(1, 0);
+
+    /// The four orthogonal offsets, in clockwise order from up.
+    pub const DIRECTIONS: [GridPos; 4] = [Self::UP, Self::RIGHT, Self::DOWN, Self::LEFT];
+
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+}
+
+impl std::ops::Add for GridPos {
+    type Output = GridPos;
+
+    fn add(self, rhs: GridPos) -> GridPos {
+        GridPos::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl std::ops::Neg for GridPos {
+    type Output = GridPos;
+
+    fn neg(self) -> GridPos {
+        GridPos::new(-self.x, -self.y)
+    }
+}
+
+/// A fixed-size 2D grid of cells, stored row-major.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T> {
+    width: i32,
+    height: i32,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: i32, height: i32, fill: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![fill; (width * height) as usize],
+        }
+    }
+
+    pub fn fill(&mut self, value: T) {
+        self.cells.fill(value);
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn contains(&self, pos: GridPos) -> bool {
+        pos.x >= 0 && pos.x < self.width && pos.y >= 0 && pos.y < self.height
+    }
+
+    pub fn get(&self, pos: GridPos) -> Option<&T> {
+        if self.contains(pos) {
+            self.cells.get(self.index(pos))
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: GridPos) -> Option<&mut T> {
+        if self.contains(pos) {
+            let index = self.index(pos);
+            self.cells.get_mut(index)
+        } else {
+            None
+        }
+    }
+
+    /// Does nothing if the position is outside the grid.
+    pub fn set(&mut self, pos: GridPos, value: T) {
+        if let Some(cell) = self.get_mut(pos) {
+            *cell = value;
+        }
+    }
+
+    /// Wraps a position around the edges, torus-style.
+    pub fn wrap(&self, pos: GridPos) -> GridPos {
+        GridPos::new(pos.x.rem_euclid(self.width), pos.y.rem_euclid(self.height))
+    }
+
+    /// The orthogonal neighbors of a cell that are inside the grid.
+    pub fn neighbors(&self, pos: GridPos) -> impl Iterator<Item = GridPos> + '_ {
+        GridPos::DIRECTIONS
+            .into_iter()
+            .map(move |dir| pos + dir)
+            .filter(|neighbor| self.contains(*neighbor))
+    }
+
+    /// The orthogonal neighbors of a cell, wrapping around the edges.
+    pub fn wrapping_neighbors(&self, pos: GridPos) -> impl Iterator<Item = GridPos> + '_ {
+        GridPos::DIRECTIONS
+            .into_iter()
+            .map(move |dir| self.wrap(pos + dir))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (GridPos, &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| (GridPos::new(i as i32 % width, i as i32 / width), cell))
+    }
+
+    /// The top-left pixel of a cell.
+    pub fn cell_to_pixel(&self, pos: GridPos, block_size: i32) -> (f32, f32) {
+        ((pos.x * block_size) as f32, (pos.y * block_size) as f32)
+    }
+
+    /// The cell containing a pixel, if it's inside the grid.
+    pub fn pixel_to_cell(&self, x: f32, y: f32, block_size: i32) -> Option<GridPos> {
+        let pos = GridPos::new(
+            (x / block_size as f32).floor() as i32,
+            (y / block_size as f32).floor() as i32,
+        );
+        self.contains(pos).then_some(pos)
+    }
+
+    fn index(&self, pos: GridPos) -> usize {
+        (pos.y * self.width + pos.x) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(positions: impl Iterator<Item = GridPos>) -> Vec<(i32, i32)> {
+        let mut positions: Vec<_> = positions.map(|pos| (pos.x, pos.y)).collect();
+        positions.sort();
+        positions
+    }
+
+    #[test]
+    fn get_and_set_stay_inside() {
+        let mut grid = Grid::new(3, 2, 0);
+        grid.set(GridPos::new(2, 1), 5);
+        grid.set(GridPos::new(3, 0), 7);
+        grid.set(GridPos::new(-1, 0), 7);
+
+        assert_eq!(grid.get(GridPos::new(2, 1)), Some(&5));
+        assert_eq!(grid.get(GridPos::new(3, 0)), None);
+        assert_eq!(grid.get(GridPos::new(0, 2)), None);
+        assert_eq!(grid.iter().filter(|(_, cell)| **cell != 0).count(), 1);
+        assert_eq!(
+            grid.iter().last().map(|(pos, _)| pos),
+            Some(GridPos::new(2, 1))
+        );
+    }
+
+    #[test]
+    fn neighbors_skip_outside_cells() {
+        let grid = Grid::new(4, 3, ());
+        assert_eq!(
+            sorted(grid.neighbors(GridPos::new(1, 1))),
+            [(0, 1), (1, 0), (1, 2), (2, 1)]
+        );
+        // edge
+        assert_eq!(
+            sorted(grid.neighbors(GridPos::new(1, 0))),
+            [(0, 0), (1, 1), (2, 0)]
+        );
+        // corners
+        assert_eq!(sorted(grid.neighbors(GridPos::new(0, 0))), [(0, 1), (1, 0)]);
+        assert_eq!(sorted(grid.neighbors(GridPos::new(3, 2))), [(2, 2), (3, 1)]);
+    }
+
+    #[test]
+    fn wrapping_neighbors_cross_the_edges() {
+        let grid = Grid::new(4, 3, ());
+        assert_eq!(
+            sorted(grid.wrapping_neighbors(GridPos::new(0, 0))),
+            [(0, 1), (0, 2), (1, 0), (3, 0)]
+        );
+        assert_eq!(
+            sorted(grid.wrapping_neighbors(GridPos::new(3, 2))),
+            [(0, 2), (2, 2), (3, 0), (3, 1)]
+        );
+    }
+
+    #[test]
+    fn wrap_negative_and_past_the_end() {
+        let grid = Grid::new(4, 3, ());
+        assert_eq!(grid.wrap(GridPos::new(-1, -1)), GridPos::new(3, 2));
+        assert_eq!(grid.wrap(GridPos::new(-5, -7)), GridPos::new(3, 2));
+        assert_eq!(grid.wrap(GridPos::new(4, 3)), GridPos::new(0, 0));
+        assert_eq!(grid.wrap(GridPos::new(9, 7)), GridPos::new(1, 1));
+        assert_eq!(grid.wrap(GridPos::new(2, 1)), GridPos::new(2, 1));
+        // a snake heading left off the first column comes back on the last
+        assert_eq!(
+            grid.wrap(GridPos::new(0, 1) + GridPos::LEFT),
+            GridPos::new(3, 1)
+        );
+    }
+
+    #[test]
+    fn pixel_cell_round_trip() {
+        let grid = Grid::new(4, 3, ());
+        for (pos, _) in grid.iter() {
+            let (x, y) = grid.cell_to_pixel(pos, 24);
+            assert_eq!(grid.pixel_to_cell(x, y, 24), Some(pos));
+            // anywhere inside the cell maps back to it
+            assert_eq!(grid.pixel_to_cell(x + 23.5, y + 23.5, 24), Some(pos));
+        }
+    }
+
+    #[test]
+    fn pixel_to_cell_outside_the_grid() {
+        let grid = Grid::new(4, 3, ());
+        assert_eq!(grid.pixel_to_cell(-0.5, 0.0, 24), None);
+        assert_eq!(grid.pixel_to_cell(0.0, -0.5, 24), None);
+        assert_eq!(grid.pixel_to_cell(96.0, 0.0, 24), None);
+        assert_eq!(grid.pixel_to_cell(0.0, 72.0, 24), None);
+    }
+}
@@ -0,0 +1,595 @@
+use std::ffi::c_void;
+use std::ptr::null_mut;
+
+use sdl3_sys::everything::*;
+
+use crate::common::*;
+
+/// The format scenes are rendered in before tone mapping.
+pub const SCENE_FORMAT: SDL_GPUTextureFormat = SDL_GPUTextureFormat::R16G16B16A16_FLOAT;
+const SCENE_PIXEL_SIZE: u32 = 8;
+
+/// The tone mapping and conversion shaders all run 8x8 thread groups.
+const THREAD_GROUP_SIZE: u32 = 8;
+
+/// The average luminance auto-exposure aims for, i.e. middle gray.
+const AUTO_EXPOSURE_KEY: f32 = 0.18;
+/// The fraction of the way to the target exposure moved each frame.
+const AUTO_EXPOSURE_ADAPTATION: f32 = 0.05;
+const MIN_EXPOSURE: f32 = 1.0 / 64.0;
+const MAX_EXPOSURE: f32 = 64.0;
+
+/// Controls for the tone map and encode passes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ToneMapSettings {
+    /// Scene values are multiplied by this before tone mapping.
+    /// Updated every frame while `auto_exposure` is on.
+    pub exposure: f32,
+    /// The scene value that maps to full white, after exposure.
+    /// None uses the operator's `default_white_point`, so each keeps its own look.
+    pub white_point: Option<f32>,
+    /// How bright SDR white is on an HDR10 display, in nits.
+    pub paper_white_nits: f32,
+    /// Adjust the exposure towards the average luminance of recent frames.
+    pub auto_exposure: bool,
+}
+
+impl Default for ToneMapSettings {
+    fn default() -> Self {
+        Self {
+            exposure: 1.0,
+            white_point: None,
+            paper_white_nits: 200.0,
+            auto_exposure: false,
+        }
+    }
+}
+
+impl ToneMapSettings {
+    pub fn set_exposure(&mut self, exposure: f32) {
+        self.exposure = exposure.clamp(MIN_EXPOSURE, MAX_EXPOSURE);
+    }
+
+    /// The white point `operator` will use, if it has one.
+    pub fn white_point(&self, operator: ToneMapOperator) -> Option<f32> {
+        self.white_point.or(operator.default_white_point())
+    }
+
+    fn uniforms(&self, operator: ToneMapOperator) -> ToneMapUniforms {
+        ToneMapUniforms {
+            exposure: self.exposure,
+            // the shaders skip white point scaling at 0
+            white_point: self.white_point(operator).unwrap_or(0.0).max(0.0),
+            paper_white_nits: self.paper_white_nits,
+            padding: 0.0,
+        }
+    }
+}
+
+/// see the ToneMapSettings cbuffer in the ToneMap*.comp and LinearToST2084.comp shaders
+#[repr(C)]
+struct ToneMapUniforms {
+    exposure: f32,
+    white_point: f32,
+    paper_white_nits: f32,
+    padding: f32,
+}
+
+/// How HDR scene values are squeezed into a displayable range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToneMapOperator {
+    Reinhard,
+    ExtendedReinhardLuminance,
+    Hable,
+    Aces,
+}
+
+impl ToneMapOperator {
+    pub const ALL: [ToneMapOperator; 4] = [
+        Self::Reinhard,
+        Self::ExtendedReinhardLuminance,
+        Self::Hable,
+        Self::Aces,
+    ];
+
+    fn shader_name(self) -> &'static str {
+        match self {
+            Self::Reinhard => "ToneMapReinhard.comp",
+            Self::ExtendedReinhardLuminance => "ToneMapExtendedReinhardLuminance.comp",
+            Self::Hable => "ToneMapHable.comp",
+            Self::Aces => "ToneMapACES.comp",
+        }
+    }
+
+    /// The white point each operator used before it was adjustable. Plain Reinhard and the
+    /// ACES fit have none: they approach white without reaching it.
+    pub fn default_white_point(self) -> Option<f32> {
+        match self {
+            Self::Reinhard | Self::Aces => None,
+            Self::ExtendedReinhardLuminance => Some(662.0),
+            Self::Hable => Some(11.2),
+        }
+    }
+
+    /// The next operator in `ALL`, wrapping around.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|op| *op == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// The swapchain composition the tone mapped image is presented with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HdrOutput {
+    /// Gamma encoded with LinearToSRGB into an 8 bit swapchain.
+    Sdr,
+    /// Linear values in a float swapchain, where 1.0 is SDR white and brighter values are HDR.
+    HdrExtendedLinear,
+    /// Rec. 2020 primaries encoded with the ST 2084 (PQ) curve into a 10 bit swapchain.
+    Hdr10,
+}
+
+impl HdrOutput {
+    pub const ALL: [HdrOutput; 3] = [Self::Sdr, Self::HdrExtendedLinear, Self::Hdr10];
+
+    pub fn composition(self) -> SDL_GPUSwapchainComposition {
+        match self {
+            Self::Sdr => SDL_GPUSwapchainComposition::SDR,
+            Self::HdrExtendedLinear => SDL_GPUSwapchainComposition::HDR_EXTENDED_LINEAR,
+            Self::Hdr10 => SDL_GPUSwapchainComposition::HDR10_ST2084,
+        }
+    }
+
+    /// The format the final image is encoded into before it's blitted to the swapchain,
+    /// or None when the tone mapped image is blitted directly.
+    fn encoded_format(self) -> Option<SDL_GPUTextureFormat> {
+        match self {
+            Self::Sdr => Some(SDL_GPUTextureFormat::R8G8B8A8_UNORM),
+            Self::HdrExtendedLinear => None,
+            Self::Hdr10 => Some(SDL_GPUTextureFormat::R10G10B10A2_UNORM),
+        }
+    }
+}
+
+/// Renders a scene in HDR and resolves it to the swapchain.
+///
+/// Each frame: `prepare` for the target size, render into `scene_texture` with pipelines
+/// targeting `SCENE_FORMAT`, then `resolve` to tone map the scene, encode it for the
+/// current `HdrOutput`, and blit it to the frame's target.
+pub struct HdrPipeline {
+    tone_map_pipelines: Vec<*mut SDL_GPUComputePipeline>,
+    srgb_pipeline: *mut SDL_GPUComputePipeline,
+    st2084_pipeline: *mut SDL_GPUComputePipeline,
+
+    scene_texture: *mut SDL_GPUTexture,
+    tone_mapped_texture: *mut SDL_GPUTexture,
+    encoded_texture: *mut SDL_GPUTexture,
+    width: u32,
+    height: u32,
+    scene_levels: u32,
+
+    /// Each receives the 1x1 mip of the scene texture, in turn. Auto-exposure reads back
+    /// the buffer from one more frame ago than can be in flight, so the gpu is always done
+    /// with it and the cpu never waits.
+    luminance_buffers: Vec<*mut SDL_GPUTransferBuffer>,
+    luminance_frames: usize,
+
+    operator: ToneMapOperator,
+    output: HdrOutput,
+    pub settings: ToneMapSettings,
+}
+
+impl HdrPipeline {
+    /// `max_frames_in_flight` must be at least the app's, e.g. `Game::MAX_FRAMES_IN_FLIGHT`.
+    pub unsafe fn new(device: *mut SDL_GPUDevice, max_frames_in_flight: u32) -> Option<Self> {
+        let mut tone_map_pipelines = Vec::with_capacity(ToneMapOperator::ALL.len());
+        for operator in ToneMapOperator::ALL {
+            let pipeline = create_image_pipeline(device, operator.shader_name(), 1);
+            if pipeline.is_null() {
+                return None;
+            }
+            tone_map_pipelines.push(pipeline);
+        }
+
+        let srgb_pipeline = create_image_pipeline(device, "LinearToSRGB.comp", 0);
+        let st2084_pipeline = create_image_pipeline(device, "LinearToST2084.comp", 1);
+        if srgb_pipeline.is_null() || st2084_pipeline.is_null() {
+            return None;
+        }
+
+        let auto_exposure_latency = max_frames_in_flight as usize + 1;
+        let luminance_buffers: Vec<_> = (0..auto_exposure_latency)
+            .map(|_| {
+                SDL_CreateGPUTransferBuffer(
+                    device,
+                    &SDL_GPUTransferBufferCreateInfo {
+                        usage: SDL_GPUTransferBufferUsage::DOWNLOAD,
+                        size: SCENE_PIXEL_SIZE,
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect();
+        // mapping a buffer the gpu may still be writing to would read a torn value
+        assert!(luminance_buffers.len() > max_frames_in_flight as usize);
+
+        Some(Self {
+            tone_map_pipelines,
+            srgb_pipeline,
+            st2084_pipeline,
+            scene_texture: null_mut(),
+            tone_mapped_texture: null_mut(),
+            encoded_texture: null_mut(),
+            width: 0,
+            height: 0,
+            scene_levels: 1,
+            luminance_buffers,
+            luminance_frames: 0,
+            operator: ToneMapOperator::Aces,
+            output: HdrOutput::Sdr,
+            settings: ToneMapSettings::default(),
+        })
+    }
+
+    pub unsafe fn release(&mut self, device: *mut SDL_GPUDevice) {
+        for pipeline in self.tone_map_pipelines.drain(..) {
+            SDL_ReleaseGPUComputePipeline(device, pipeline);
+        }
+        if !self.srgb_pipeline.is_null() {
+            SDL_ReleaseGPUComputePipeline(device, self.srgb_pipeline);
+        }
+        if !self.st2084_pipeline.is_null() {
+            SDL_ReleaseGPUComputePipeline(device, self.st2084_pipeline);
+        }
+        for buffer in self.luminance_buffers.drain(..) {
+            if !buffer.is_null() {
+                SDL_ReleaseGPUTransferBuffer(device, buffer);
+            }
+        }
+        self.release_textures(device);
+    }
+
+    unsafe fn release_textures(&mut self, device: *mut SDL_GPUDevice) {
+        for texture in [
+            &mut self.scene_texture,
+            &mut self.tone_mapped_texture,
+            &mut self.encoded_texture,
+        ] {
+            if !texture.is_null() {
+                SDL_ReleaseGPUTexture(device, *texture);
+                *texture = null_mut();
+            }
+        }
+        self.width = 0;
+        self.height = 0;
+        self.luminance_frames = 0;
+    }
+
+    /// (Re)creates the intermediate textures if the size or output changed.
+    pub unsafe fn prepare(&mut self, device: *mut SDL_GPUDevice, width: u32, height: u32) -> bool {
+        if !self.scene_texture.is_null() && self.width == width && self.height == height {
+            return true;
+        }
+
+        self.release_textures(device);
+
+        // a full mip chain, for averaging the scene down to 1x1 for auto-exposure
+        self.scene_levels = width.max(height).max(1).ilog2() + 1;
+        self.scene_texture = create_texture(
+            device,
+            SCENE_FORMAT,
+            width,
+            height,
+            self.scene_levels,
+            SDL_GPU_TEXTUREUSAGE_COLOR_TARGET
+                | SDL_GPU_TEXTUREUSAGE_SAMPLER
+                | SDL_GPU_TEXTUREUSAGE_COMPUTE_STORAGE_READ,
+        );
+        self.tone_mapped_texture = create_texture(
+            device,
+            SCENE_FORMAT,
+            width,
+            height,
+            1,
+            SDL_GPU_TEXTUREUSAGE_COMPUTE_STORAGE_WRITE
+                | SDL_GPU_TEXTUREUSAGE_COMPUTE_STORAGE_READ
+                | SDL_GPU_TEXTUREUSAGE_SAMPLER,
+        );
+        if let Some(format) = self.output.encoded_format() {
+            self.encoded_texture = create_texture(
+                device,
+                format,
+                width,
+                height,
+                1,
+                SDL_GPU_TEXTUREUSAGE_COMPUTE_STORAGE_WRITE | SDL_GPU_TEXTUREUSAGE_SAMPLER,
+            );
+        }
+
+        if self.scene_texture.is_null()
+            || self.tone_mapped_texture.is_null()
+            || (self.output.encoded_format().is_some() && self.encoded_texture.is_null())
+        {
+            self.release_textures(device);
+            return false;
+        }
+
+        self.width = width;
+        self.height = height;
+
+        true
+    }
+
+    /// The render target for the scene; null until `prepare` succeeds.
+    pub fn scene_texture(&self) -> *mut SDL_GPUTexture {
+        self.scene_texture
+    }
+
+    pub fn operator(&self) -> ToneMapOperator {
+        self.operator
+    }
+
+    pub fn set_operator(&mut self, operator: ToneMapOperator) {
+        self.operator = operator;
+    }
+
+    pub fn output(&self) -> HdrOutput {
+        self.output
+    }
+
+    /// Switches the window's swapchain composition. Returns false, keeping the current output,
+    /// if the window doesn't support it. Headless contexts (a null window) only support `Sdr`.
+    /// Pass the current `present_mode`, e.g. from the frame, to leave it unchanged.
+    pub unsafe fn set_output(
+        &mut self,
+        device: *mut SDL_GPUDevice,
+        window: *mut SDL_Window,
+        output: HdrOutput,
+        present_mode: SDL_GPUPresentMode,
+    ) -> bool {
+        if output == self.output {
+            return true;
+        }
+
+        if window.is_null() {
+            return false;
+        }
+
+        let composition = output.composition();
+        if !SDL_WindowSupportsGPUSwapchainComposition(device, window, composition) {
+            println!("swapchain composition {composition:?} is not supported");
+            return false;
+        }
+
+        if !SDL_SetGPUSwapchainParameters(device, window, composition, present_mode) {
+            dbg_sdl_error("failed to set swapchain parameters");
+            return false;
+        }
+
+        self.output = output;
+        // the encoded texture's format depends on the output
+        self.release_textures(device);
+
+        true
+    }
+
+    /// Tone maps the scene, encodes it for the output, and blits it over `target`.
+    /// Must be called outside of any pass, after the scene is rendered.
+    pub unsafe fn resolve(
+        &mut self,
+        device: *mut SDL_GPUDevice,
+        command_buffer: *mut SDL_GPUCommandBuffer,
+        target: *mut SDL_GPUTexture,
+        target_width: u32,
+        target_height: u32,
+    ) {
+        if self.scene_texture.is_null() {
+            return;
+        }
+
+        if self.settings.auto_exposure {
+            self.update_auto_exposure(device, command_buffer);
+        }
+
+        let uniforms = self.settings.uniforms(self.operator);
+        SDL_PushGPUComputeUniformData(
+            command_buffer,
+            0,
+            &uniforms as *const ToneMapUniforms as *const c_void,
+            std::mem::size_of::<ToneMapUniforms>() as u32,
+        );
+
+        let operator_index = ToneMapOperator::ALL
+            .iter()
+            .position(|op| *op == self.operator)
+            .unwrap();
+        self.dispatch(
+            command_buffer,
+            self.tone_map_pipelines[operator_index],
+            self.scene_texture,
+            self.tone_mapped_texture,
+        );
+
+        let encode_pipeline = match self.output {
+            HdrOutput::Sdr => self.srgb_pipeline,
+            HdrOutput::HdrExtendedLinear => null_mut(),
+            HdrOutput::Hdr10 => self.st2084_pipeline,
+        };
+        let final_texture = if encode_pipeline.is_null() {
+            self.tone_mapped_texture
+        } else {
+            self.dispatch(
+                command_buffer,
+                encode_pipeline,
+                self.tone_mapped_texture,
+                self.encoded_texture,
+            );
+            self.encoded_texture
+        };
+
+        SDL_BlitGPUTexture(
+            command_buffer,
+            &SDL_GPUBlitInfo {
+                source: SDL_GPUBlitRegion {
+                    texture: final_texture,
+                    w: self.width,
+                    h: self.height,
+                    ..Default::default()
+                },
+                destination: SDL_GPUBlitRegion {
+                    texture: target,
+                    w: target_width,
+                    h: target_height,
+                    ..Default::default()
+                },
+                load_op: SDL_GPULoadOp::DONT_CARE,
+                filter: SDL_GPU_FILTER_LINEAR,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Moves the exposure towards the average luminance from `luminance_buffers.len()` frames ago,
+    /// and queues this frame's average for a later frame.
+    unsafe fn update_auto_exposure(
+        &mut self,
+        device: *mut SDL_GPUDevice,
+        command_buffer: *mut SDL_GPUCommandBuffer,
+    ) {
+        let latency = self.luminance_buffers.len();
+        let buffer = self.luminance_buffers[self.luminance_frames % latency];
+        if buffer.is_null() {
+            return;
+        }
+
+        if self.luminance_frames >= latency {
+            let mapped = SDL_MapGPUTransferBuffer(device, buffer, false) as *const u16;
+            if !mapped.is_null() {
+                let [r, g, b, _] = std::array::from_fn(|i| f16_to_f32(*mapped.add(i)));
+                SDL_UnmapGPUTransferBuffer(device, buffer);
+
+                let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+                let target = AUTO_EXPOSURE_KEY / luminance.max(0.0001);
+                let exposure = self.settings.exposure
+                    + (target - self.settings.exposure) * AUTO_EXPOSURE_ADAPTATION;
+                self.settings.set_exposure(exposure);
+            }
+        }
+
+        SDL_GenerateMipmapsForGPUTexture(command_buffer, self.scene_texture);
+
+        let copy_pass = SDL_BeginGPUCopyPass(command_buffer);
+        SDL_DownloadFromGPUTexture(
+            copy_pass,
+            &SDL_GPUTextureRegion {
+                texture: self.scene_texture,
+                mip_level: self.scene_levels - 1,
+                w: 1,
+                h: 1,
+                d: 1,
+                ..Default::default()
+            },
+            &SDL_GPUTextureTransferInfo {
+                transfer_buffer: buffer,
+                offset: 0,
+                ..Default::default()
+            },
+        );
+        SDL_EndGPUCopyPass(copy_pass);
+
+        self.luminance_frames += 1;
+    }
+
+    /// Runs a whole-image compute pass reading `input` and writing `output`.
+    unsafe fn dispatch(
+        &self,
+        command_buffer: *mut SDL_GPUCommandBuffer,
+        pipeline: *mut SDL_GPUComputePipeline,
+        input: *mut SDL_GPUTexture,
+        output: *mut SDL_GPUTexture,
+    ) {
+        let compute_pass = SDL_BeginGPUComputePass(
+            command_buffer,
+            &SDL_GPUStorageTextureReadWriteBinding {
+                texture: output,
+                ..Default::default()
+            },
+            1,
+            null_mut(),
+            0,
+        );
+        SDL_BindGPUComputePipeline(compute_pass, pipeline);
+        SDL_BindGPUComputeStorageTextures(compute_pass, 0, &input, 1);
+        SDL_DispatchGPUCompute(
+            compute_pass,
+            self.width.div_ceil(THREAD_GROUP_SIZE),
+            self.height.div_ceil(THREAD_GROUP_SIZE),
+            1,
+        );
+        SDL_EndGPUComputePass(compute_pass);
+    }
+}
+
+/// The tone map and encode shaders all read one storage texture and write another;
+/// all but LinearToSRGB also take the `ToneMapUniforms`.
+unsafe fn create_image_pipeline(
+    device: *mut SDL_GPUDevice,
+    shader_name: &'static str,
+    num_uniform_buffers: u32,
+) -> *mut SDL_GPUComputePipeline {
+    load_compute_pipeline(
+        device,
+        shader_name,
+        SDL_GPUComputePipelineCreateInfo {
+            num_readonly_storage_textures: 1,
+            num_readwrite_storage_textures: 1,
+            num_uniform_buffers,
+            threadcount_x: THREAD_GROUP_SIZE,
+            threadcount_y: THREAD_GROUP_SIZE,
+            threadcount_z: 1,
+            ..Default::default()
+        },
+    )
+}
+
+unsafe fn create_texture(
+    device: *mut SDL_GPUDevice,
+    format: SDL_GPUTextureFormat,
+    width: u32,
+    height: u32,
+    num_levels: u32,
+    usage: SDL_GPUTextureUsageFlags,
+) -> *mut SDL_GPUTexture {
+    let texture = SDL_CreateGPUTexture(
+        device,
+        &SDL_GPUTextureCreateInfo {
+            r#type: SDL_GPUTextureType::_2D,
+            format,
+            width,
+            height,
+            layer_count_or_depth: 1,
+            num_levels,
+            usage,
+            ..Default::default()
+        },
+    );
+    if texture.is_null() {
+        dbg_sdl_error("failed to create hdr texture");
+    }
+
+    texture
+}
+
+/// Decodes an IEEE 754 half precision float.
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32;
+
+    match exponent {
+        0 => sign * mantissa * 2.0_f32.powi(-24),
+        0x1f if mantissa == 0.0 => sign * f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2.0_f32.powi(exponent - 15),
+    }
+}
@@ -0,0 +1,106 @@
+use std::ffi::{CString, c_void};
+use std::path::Path;
+
+use sdl3_sys::everything::*;
+
+use crate::common::dbg_sdl_error;
+
+/// An owned image with tightly packed RGBA8 rows, top row first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; (width * height * 4) as usize],
+        }
+    }
+
+    /// The 4 channels of the pixel at (x, y).
+    pub fn pixel(&self, x: u32, y: u32) -> &[u8] {
+        let index = ((y * self.width + x) * 4) as usize;
+        &self.pixels[index..index + 4]
+    }
+
+    /// Copies out the `width` x `height` region with its top left corner at (x, y),
+    /// which must lie within the image.
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Self {
+        let mut cropped = Self::new(width, height);
+        let row_size = (width * 4) as usize;
+        for (row_y, row) in cropped.pixels.chunks_exact_mut(row_size).enumerate() {
+            let start = (((y + row_y as u32) * self.width + x) * 4) as usize;
+            row.copy_from_slice(&self.pixels[start..start + row_size]);
+        }
+        cropped
+    }
+
+    /// Loads a bmp of any pixel format, converting it to RGBA8.
+    pub fn load_bmp(path: impl AsRef<Path>) -> Option<Self> {
+        let path = path.as_ref().display().to_string();
+        let c_path = CString::new(path.as_str()).unwrap();
+
+        unsafe {
+            let surface = SDL_LoadBMP(c_path.as_ptr());
+            if surface.is_null() {
+                dbg_sdl_error(&format!("failed to load bmp: {path}"));
+                return None;
+            }
+
+            let converted = SDL_ConvertSurface(surface, SDL_PixelFormat::RGBA32);
+            SDL_DestroySurface(surface);
+            if converted.is_null() {
+                dbg_sdl_error(&format!("failed to convert bmp: {path}"));
+                return None;
+            }
+
+            let mut image = Self::new((*converted).w as u32, (*converted).h as u32);
+            let row_size = image.width as usize * 4;
+            let pitch = (*converted).pitch as usize;
+            for (y, row) in image.pixels.chunks_mut(row_size).enumerate() {
+                let source = ((*converted).pixels as *const u8).add(y * pitch);
+                row.copy_from_slice(std::slice::from_raw_parts(source, row_size));
+            }
+            SDL_DestroySurface(converted);
+
+            Some(image)
+        }
+    }
+
+    /// Saves as a 32 bit bmp, creating parent directories as needed.
+    pub fn save_bmp(&self, path: impl AsRef<Path>) -> bool {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let path = path.display().to_string();
+        let c_path = CString::new(path.as_str()).unwrap();
+
+        unsafe {
+            let surface = SDL_CreateSurfaceFrom(
+                self.width as i32,
+                self.height as i32,
+                SDL_PixelFormat::RGBA32,
+                self.pixels.as_ptr() as *mut c_void,
+                self.width as i32 * 4,
+            );
+            if surface.is_null() {
+                dbg_sdl_error("failed to create surface for image");
+                return false;
+            }
+
+            let saved = SDL_SaveBMP(surface, c_path.as_ptr());
+            if !saved {
+                dbg_sdl_error(&format!("failed to save bmp: {path}"));
+            }
+            SDL_DestroySurface(surface);
+
+            saved
+        }
+    }
+}
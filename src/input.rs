@@ -0,0 +1,113 @@
+use sdl3_sys::scancode::SDL_Scancode;
+
+use crate::replay::{InputRecording, InputReplay, InputSnapshot};
+
+/// Keyboard state as seen by fixed steps.
+/// Key events arrive between steps; `keys_just_pressed` holds the ones since the last step.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Input {
+    keys_down: Vec<SDL_Scancode>,
+    keys_just_pressed: Vec<SDL_Scancode>,
+    mode: InputMode,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+enum InputMode {
+    #[default]
+    Live,
+    Recording(InputRecording),
+    Replaying(InputReplay),
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Input that ignores live key events and reads each step from a recording instead.
+    pub fn from_recording(recording: InputRecording) -> Self {
+        Self {
+            mode: InputMode::Replaying(InputReplay::new(recording)),
+            ..Default::default()
+        }
+    }
+
+    /// `seed` is what `SDL_srand` was seeded with, saved so a replay can repeat `SDL_rand`.
+    pub fn start_recording(&mut self, steps_per_second: u32, seed: u64) {
+        self.mode = InputMode::Recording(InputRecording::new(steps_per_second, seed));
+    }
+
+    /// Returns the recording made since `start_recording`, if one was in progress.
+    pub fn finish_recording(&mut self) -> Option<InputRecording> {
+        match std::mem::replace(&mut self.mode, InputMode::Live) {
+            InputMode::Recording(recording) => Some(recording),
+            other => {
+                self.mode = other;
+                None
+            }
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        matches!(self.mode, InputMode::Recording(_))
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        matches!(self.mode, InputMode::Replaying(_))
+    }
+
+    pub fn is_down(&self, scan_code: SDL_Scancode) -> bool {
+        self.keys_down.contains(&scan_code)
+    }
+
+    pub fn just_pressed(&self, scan_code: SDL_Scancode) -> bool {
+        self.keys_just_pressed.contains(&scan_code)
+    }
+
+    pub fn key_pressed(&mut self, scan_code: SDL_Scancode) {
+        if self.is_replaying() {
+            return;
+        }
+
+        if !self.keys_down.contains(&scan_code) {
+            self.keys_down.push(scan_code);
+            self.keys_just_pressed.push(scan_code);
+        }
+    }
+
+    pub fn key_released(&mut self, scan_code: SDL_Scancode) {
+        if self.is_replaying() {
+            return;
+        }
+
+        self.keys_down.retain(|k| *k != scan_code);
+        self.keys_just_pressed.retain(|k| *k != scan_code);
+    }
+
+    /// Call before each fixed step.
+    /// Records or overwrites the input the step will see, depending on the input mode.
+    pub fn begin_step(&mut self) {
+        match &mut self.mode {
+            InputMode::Live => {}
+            InputMode::Recording(recording) => {
+                let snapshot = InputSnapshot::capture(&self.keys_down, &self.keys_just_pressed);
+                recording.steps.push(snapshot);
+            }
+            InputMode::Replaying(replay) => match replay.next_snapshot() {
+                Some(snapshot) => {
+                    self.keys_down = snapshot.keys_down();
+                    self.keys_just_pressed = snapshot.keys_just_pressed();
+                }
+                None => {
+                    self.keys_down.clear();
+                    self.keys_just_pressed.clear();
+                }
+            },
+        }
+    }
+
+    /// Call after each fixed step.
+    pub fn end_step(&mut self) {
+        self.keys_just_pressed.clear();
+    }
+}
@@ -1,4 +1,247 @@
-pub fn main() {
-    println!("run an example like `cargo run --example basic_triangle`");
-    std::process::exit(1);
+use std::collections::VecDeque;
+use std::ffi::CStr;
+
+use sdl3_main::AppResult;
+use sdl3_sys::everything::*;
+
+use sdl3_experiment::app::{Frame, Game};
+use sdl3_experiment::blocks::BlockRenderer;
+use sdl3_experiment::canvas::VirtualCanvas;
+use sdl3_experiment::gpu::GpuContext;
+use sdl3_experiment::grid::{Grid, GridPos};
+use sdl3_experiment::input::Input;
+use sdl3_experiment::*;
+
+/// fixed steps between snake moves; about 7.5 moves per second
+const STEPS_PER_MOVE: u32 = 8;
+const INITIAL_LENGTH: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Cell {
+    Empty,
+    Snake,
+    Head,
+    Food,
 }
+
+struct SnakeGame {
+    blocks: BlockRenderer,
+    canvas: VirtualCanvas,
+    snake: Snake,
+}
+
+/// The gameplay state, independent of rendering.
+struct Snake {
+    grid: Grid<Cell>,
+    body: VecDeque<GridPos>,
+    direction: GridPos,
+    next_direction: GridPos,
+    steps_until_move: u32,
+    score: u32,
+}
+
+impl Snake {
+    fn new() -> Self {
+        let mut snake = Self {
+            grid: Grid::new(GAME_WIDTH as i32, GAME_HEIGHT as i32, Cell::Empty),
+            body: VecDeque::new(),
+            direction: GridPos::RIGHT,
+            next_direction: GridPos::RIGHT,
+            steps_until_move: STEPS_PER_MOVE,
+            score: 0,
+        };
+        snake.reset();
+        snake
+    }
+
+    fn reset(&mut self) {
+        self.grid.fill(Cell::Empty);
+        self.body.clear();
+
+        let start = GridPos::new(self.grid.width() / 4, self.grid.height() / 2);
+        for i in 0..INITIAL_LENGTH as i32 {
+            let pos = GridPos::new(start.x - i, start.y);
+            self.body.push_back(pos);
+            self.grid.set(pos, Cell::Snake);
+        }
+        self.grid.set(start, Cell::Head);
+
+        self.direction = GridPos::RIGHT;
+        self.next_direction = GridPos::RIGHT;
+        self.steps_until_move = STEPS_PER_MOVE;
+        self.score = 0;
+        self.place_food();
+    }
+
+    fn place_food(&mut self) {
+        let empty_cells: Vec<GridPos> = self
+            .grid
+            .iter()
+            .filter(|(_, cell)| **cell == Cell::Empty)
+            .map(|(pos, _)| pos)
+            .collect();
+        if empty_cells.is_empty() {
+            return;
+        }
+
+        let index = unsafe { SDL_rand(empty_cells.len() as i32) } as usize;
+        self.grid.set(empty_cells[index], Cell::Food);
+    }
+
+    fn fixed_update(&mut self, input: &Input) {
+        let turns = [
+            (SDL_Scancode::UP, GridPos::UP),
+            (SDL_Scancode::DOWN, GridPos::DOWN),
+            (SDL_Scancode::LEFT, GridPos::LEFT),
+            (SDL_Scancode::RIGHT, GridPos::RIGHT),
+        ];
+        for (key, direction) in turns {
+            // reversing into the neck is never allowed
+            if input.just_pressed(key) && direction != -self.direction {
+                self.next_direction = direction;
+            }
+        }
+
+        self.steps_until_move -= 1;
+        if self.steps_until_move > 0 {
+            return;
+        }
+        self.steps_until_move = STEPS_PER_MOVE;
+
+        self.direction = self.next_direction;
+        let head = self.body[0];
+        let next_head = self.grid.wrap(head + self.direction);
+
+        let ate = match self.grid.get(next_head) {
+            Some(Cell::Food) => true,
+            Some(Cell::Empty) => false,
+            // the tail moves out of the way this step, unless it's about to grow
+            _ if Some(&next_head) == self.body.back() => false,
+            _ => {
+                println!("game over! score: {}", self.score);
+                self.reset();
+                return;
+            }
+        };
+
+        if ate {
+            self.score += 1;
+        } else if let Some(tail) = self.body.pop_back() {
+            self.grid.set(tail, Cell::Empty);
+        }
+
+        self.grid.set(head, Cell::Snake);
+        self.grid.set(next_head, Cell::Head);
+        self.body.push_front(next_head);
+
+        if ate {
+            self.place_food();
+        }
+    }
+}
+
+impl Game for SnakeGame {
+    const TITLE: &'static CStr = c"Snake";
+    const WINDOW_WIDTH: i32 = WINDOW_WIDTH;
+    const WINDOW_HEIGHT: i32 = WINDOW_HEIGHT;
+
+    fn window_flags() -> SDL_WindowFlags {
+        SDL_WINDOW_RESIZABLE
+    }
+
+    fn init(gpu: &GpuContext) -> Option<Self> {
+        let device = gpu.device;
+
+        unsafe {
+            let target_format = gpu.target_format();
+            let max_blocks = GAME_WIDTH as u32 * GAME_HEIGHT as u32;
+            let blocks = BlockRenderer::new(
+                device,
+                target_format,
+                CANVAS_BLOCK_SIZE_IN_PIXELS,
+                max_blocks,
+            )?;
+            let canvas = VirtualCanvas::new(
+                device,
+                target_format,
+                CANVAS_WIDTH as u32,
+                CANVAS_HEIGHT as u32,
+            )?;
+
+            println!("Use the arrow keys to steer");
+
+            Some(Self {
+                blocks,
+                canvas,
+                snake: Snake::new(),
+            })
+        }
+    }
+
+    fn quit(&mut self, device: *mut SDL_GPUDevice) {
+        unsafe {
+            self.blocks.release(device);
+            self.canvas.release(device);
+        }
+    }
+
+    fn fixed_update(&mut self, input: &Input) {
+        self.snake.fixed_update(input);
+    }
+
+    fn render(&mut self, frame: &Frame) -> AppResult {
+        let background = SDL_FColor {
+            r: 0.05,
+            g: 0.05,
+            b: 0.08,
+            a: 1.0,
+        };
+
+        let canvas_frame = self.canvas.frame(frame);
+        let drawn = unsafe {
+            self.blocks.draw_grid(
+                &canvas_frame,
+                &self.snake.grid,
+                background,
+                |cell| match cell {
+                    Cell::Empty => None,
+                    Cell::Snake => Some(SDL_FColor {
+                        r: 0.2,
+                        g: 0.7,
+                        b: 0.3,
+                        a: 1.0,
+                    }),
+                    Cell::Head => Some(SDL_FColor {
+                        r: 0.4,
+                        g: 0.9,
+                        b: 0.4,
+                        a: 1.0,
+                    }),
+                    Cell::Food => Some(SDL_FColor {
+                        r: 0.9,
+                        g: 0.2,
+                        b: 0.2,
+                        a: 1.0,
+                    }),
+                },
+            )
+        };
+
+        if !drawn {
+            return AppResult::Failure;
+        }
+
+        unsafe {
+            self.canvas.present(
+                frame.command_buffer,
+                frame.target,
+                frame.width,
+                frame.height,
+            );
+        }
+
+        AppResult::Continue
+    }
+}
+
+run_game!(SnakeGame);
@@ -0,0 +1,699 @@
+use std::ffi::c_void;
+use std::ptr::null_mut;
+
+use sdl3_sys::everything::*;
+
+use crate::common::*;
+
+/// The format of the scene texture and every intermediate target.
+/// Pipelines that draw the scene must target it.
+pub const SCENE_FORMAT: SDL_GPUTextureFormat = SDL_GPUTextureFormat::R8G8B8A8_UNORM;
+
+/// The compute effects run 8x8 thread groups.
+const THREAD_GROUP_SIZE: u32 = 8;
+
+/// The color grading lookup table is LUT_SIZE^3 texels.
+const LUT_SIZE: u32 = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PostEffect {
+    /// A 5-tap blur with texel loads, from CustomSampling.frag; repeated `blur_passes` times.
+    Blur,
+    /// Rolling scanlines and a slight color fringe, from CrtScanlines.frag.
+    Scanlines,
+    /// Darkened corners, from the Vignette.comp compute shader.
+    Vignette,
+    /// Maps every color through a 3D lookup table, from ColorGrade.frag.
+    ColorGrade,
+}
+
+impl PostEffect {
+    pub const ALL: [PostEffect; 4] = [
+        Self::Blur,
+        Self::Scanlines,
+        Self::Vignette,
+        Self::ColorGrade,
+    ];
+}
+
+/// The looks the color grading lookup table can be built with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LutPreset {
+    Warm,
+    Cool,
+    Sepia,
+    Noir,
+}
+
+impl LutPreset {
+    pub const ALL: [LutPreset; 4] = [Self::Warm, Self::Cool, Self::Sepia, Self::Noir];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|p| *p == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// The graded color for an input color, both in 0..1.
+    fn grade(self, [r, g, b]: [f32; 3]) -> [f32; 3] {
+        let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+        let graded = match self {
+            Self::Warm => [r * 1.1 + 0.02, g * 1.02, b * 0.85],
+            Self::Cool => [r * 0.85, g * 1.0, b * 1.1 + 0.03],
+            Self::Sepia => [
+                0.393 * r + 0.769 * g + 0.189 * b,
+                0.349 * r + 0.686 * g + 0.168 * b,
+                0.272 * r + 0.534 * g + 0.131 * b,
+            ],
+            Self::Noir => {
+                // a smoothstep s-curve for more contrast
+                let l = luminance * luminance * (3.0 - 2.0 * luminance);
+                [l, l, l]
+            }
+        };
+        graded.map(|c| c.clamp(0.0, 1.0))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PostProcessSettings {
+    /// How many times `Blur` runs; each pass widens it.
+    pub blur_passes: u32,
+    /// How dark the scanlines get, from 0 to 1.
+    pub scanline_intensity: f32,
+    /// How dark the corners get, from 0 to 1.
+    pub vignette_strength: f32,
+    /// How far from the center the vignette starts, where 1 is the middle of each edge.
+    pub vignette_radius: f32,
+}
+
+impl Default for PostProcessSettings {
+    fn default() -> Self {
+        Self {
+            blur_passes: 2,
+            scanline_intensity: 0.35,
+            vignette_strength: 0.8,
+            vignette_radius: 0.4,
+        }
+    }
+}
+
+/// see the UBO cbuffer in CustomSampling.frag.hlsl
+#[repr(C)]
+struct BlurUniforms {
+    mode: i32,
+    padding: [i32; 3],
+}
+
+/// see the UBO cbuffer in CrtScanlines.frag.hlsl
+#[repr(C)]
+struct ScanlineUniforms {
+    resolution: [f32; 2],
+    intensity: f32,
+    time: f32,
+}
+
+/// see the UBO cbuffer in Vignette.comp.hlsl
+#[repr(C)]
+struct VignetteUniforms {
+    strength: f32,
+    radius: f32,
+    padding: [f32; 2],
+}
+
+/// Renders a scene offscreen and runs it through an ordered list of full screen effects.
+///
+/// Each frame: `prepare` for the target size, render into `scene_texture` with pipelines
+/// targeting `SCENE_FORMAT`, then `apply` outside of any pass. Enabled effects run in order,
+/// each reading the previous one's output and writing the other of two intermediate targets,
+/// and the last output is blitted to the frame's target.
+pub struct PostProcessStack {
+    blur_pipeline: *mut SDL_GPUGraphicsPipeline,
+    scanlines_pipeline: *mut SDL_GPUGraphicsPipeline,
+    color_grade_pipeline: *mut SDL_GPUGraphicsPipeline,
+    vignette_pipeline: *mut SDL_GPUComputePipeline,
+    sampler: *mut SDL_GPUSampler,
+
+    lut_texture: *mut SDL_GPUTexture,
+    lut_preset: LutPreset,
+
+    scene_texture: *mut SDL_GPUTexture,
+    /// Effects ping-pong between these.
+    targets: [*mut SDL_GPUTexture; 2],
+    width: u32,
+    height: u32,
+
+    /// Each effect and whether it's enabled, in the order they're applied.
+    effects: Vec<(PostEffect, bool)>,
+    pub settings: PostProcessSettings,
+}
+
+impl PostProcessStack {
+    /// A stack applying `effects` in the given order, all of them initially disabled.
+    pub unsafe fn new(device: *mut SDL_GPUDevice, effects: &[PostEffect]) -> Option<Self> {
+        // filled in as each part is created, so whatever was created can be released on failure
+        let mut stack = Self {
+            blur_pipeline: null_mut(),
+            scanlines_pipeline: null_mut(),
+            color_grade_pipeline: null_mut(),
+            vignette_pipeline: null_mut(),
+            sampler: null_mut(),
+            lut_texture: null_mut(),
+            lut_preset: LutPreset::Warm,
+            scene_texture: null_mut(),
+            targets: [null_mut(); 2],
+            width: 0,
+            height: 0,
+            effects: effects.iter().map(|effect| (*effect, false)).collect(),
+            settings: PostProcessSettings::default(),
+        };
+        if !stack.create_resources(device) {
+            stack.release(device);
+            return None;
+        }
+
+        Some(stack)
+    }
+
+    unsafe fn create_resources(&mut self, device: *mut SDL_GPUDevice) -> bool {
+        self.blur_pipeline = create_fullscreen_pipeline(device, "CustomSampling.frag");
+        self.scanlines_pipeline = create_fullscreen_pipeline(device, "CrtScanlines.frag");
+        self.color_grade_pipeline = create_fullscreen_pipeline(device, "ColorGrade.frag");
+        if self.blur_pipeline.is_null()
+            || self.scanlines_pipeline.is_null()
+            || self.color_grade_pipeline.is_null()
+        {
+            return false;
+        }
+
+        self.vignette_pipeline = load_compute_pipeline(
+            device,
+            "Vignette.comp",
+            SDL_GPUComputePipelineCreateInfo {
+                num_readonly_storage_textures: 1,
+                num_readwrite_storage_textures: 1,
+                num_uniform_buffers: 1,
+                threadcount_x: THREAD_GROUP_SIZE,
+                threadcount_y: THREAD_GROUP_SIZE,
+                threadcount_z: 1,
+                ..Default::default()
+            },
+        );
+        if self.vignette_pipeline.is_null() {
+            return false;
+        }
+
+        self.sampler = create_sampler(
+            device,
+            SDL_GPU_FILTER_LINEAR,
+            SDL_GPU_SAMPLERADDRESSMODE_CLAMP_TO_EDGE,
+        );
+        if self.sampler.is_null() {
+            dbg_sdl_error("failed to create post process sampler");
+            return false;
+        }
+
+        self.lut_texture = create_lut_texture(device, self.lut_preset);
+        !self.lut_texture.is_null()
+    }
+
+    pub unsafe fn release(&mut self, device: *mut SDL_GPUDevice) {
+        for pipeline in [
+            self.blur_pipeline,
+            self.scanlines_pipeline,
+            self.color_grade_pipeline,
+        ] {
+            if !pipeline.is_null() {
+                SDL_ReleaseGPUGraphicsPipeline(device, pipeline);
+            }
+        }
+        if !self.vignette_pipeline.is_null() {
+            SDL_ReleaseGPUComputePipeline(device, self.vignette_pipeline);
+        }
+        if !self.sampler.is_null() {
+            SDL_ReleaseGPUSampler(device, self.sampler);
+        }
+        if !self.lut_texture.is_null() {
+            SDL_ReleaseGPUTexture(device, self.lut_texture);
+        }
+        self.release_textures(device);
+    }
+
+    unsafe fn release_textures(&mut self, device: *mut SDL_GPUDevice) {
+        let [first, second] = &mut self.targets;
+        for texture in [&mut self.scene_texture, first, second] {
+            if !texture.is_null() {
+                SDL_ReleaseGPUTexture(device, *texture);
+                *texture = null_mut();
+            }
+        }
+        self.width = 0;
+        self.height = 0;
+    }
+
+    /// (Re)creates the scene and intermediate textures if the size changed.
+    pub unsafe fn prepare(&mut self, device: *mut SDL_GPUDevice, width: u32, height: u32) -> bool {
+        if !self.scene_texture.is_null() && self.width == width && self.height == height {
+            return true;
+        }
+
+        self.release_textures(device);
+
+        // any texture may be the input of a fragment or compute effect, and any target
+        // but the scene may be the output of one
+        let input_usage = SDL_GPU_TEXTUREUSAGE_COLOR_TARGET
+            | SDL_GPU_TEXTUREUSAGE_SAMPLER
+            | SDL_GPU_TEXTUREUSAGE_GRAPHICS_STORAGE_READ
+            | SDL_GPU_TEXTUREUSAGE_COMPUTE_STORAGE_READ;
+        self.scene_texture = create_target(device, width, height, input_usage);
+        self.targets = std::array::from_fn(|_| {
+            create_target(
+                device,
+                width,
+                height,
+                input_usage | SDL_GPU_TEXTUREUSAGE_COMPUTE_STORAGE_WRITE,
+            )
+        });
+
+        if self.scene_texture.is_null() || self.targets.iter().any(|t| t.is_null()) {
+            self.release_textures(device);
+            return false;
+        }
+
+        self.width = width;
+        self.height = height;
+
+        true
+    }
+
+    /// The render target for the scene; null until `prepare` succeeds.
+    pub fn scene_texture(&self) -> *mut SDL_GPUTexture {
+        self.scene_texture
+    }
+
+    /// Each effect and whether it's enabled, in the order they're applied.
+    pub fn effects(&self) -> &[(PostEffect, bool)] {
+        &self.effects
+    }
+
+    pub fn is_enabled(&self, effect: PostEffect) -> bool {
+        self.effects
+            .iter()
+            .any(|(e, enabled)| *e == effect && *enabled)
+    }
+
+    /// Has no effect on effects that aren't in the stack.
+    pub fn set_enabled(&mut self, effect: PostEffect, enabled: bool) {
+        for (e, e_enabled) in &mut self.effects {
+            if *e == effect {
+                *e_enabled = enabled;
+            }
+        }
+    }
+
+    /// Returns whether the effect is now enabled.
+    pub fn toggle(&mut self, effect: PostEffect) -> bool {
+        let enabled = !self.is_enabled(effect);
+        self.set_enabled(effect, enabled);
+        enabled
+    }
+
+    pub fn lut_preset(&self) -> LutPreset {
+        self.lut_preset
+    }
+
+    /// Rebuilds the color grading lookup table. Returns false, keeping the current one, on failure.
+    pub unsafe fn set_lut_preset(&mut self, device: *mut SDL_GPUDevice, preset: LutPreset) -> bool {
+        if preset == self.lut_preset {
+            return true;
+        }
+
+        let lut_texture = create_lut_texture(device, preset);
+        if lut_texture.is_null() {
+            return false;
+        }
+
+        SDL_ReleaseGPUTexture(device, self.lut_texture);
+        self.lut_texture = lut_texture;
+        self.lut_preset = preset;
+
+        true
+    }
+
+    /// Runs the enabled effects over the scene and blits the result over `target`.
+    /// `time` in seconds animates the effects that move.
+    /// Must be called outside of any pass, after the scene is rendered.
+    pub unsafe fn apply(
+        &self,
+        command_buffer: *mut SDL_GPUCommandBuffer,
+        target: *mut SDL_GPUTexture,
+        target_width: u32,
+        target_height: u32,
+        time: f32,
+    ) {
+        if self.scene_texture.is_null() {
+            return;
+        }
+
+        let mut input = self.scene_texture;
+        let mut next_target = 0;
+        for (effect, enabled) in &self.effects {
+            if !enabled {
+                continue;
+            }
+
+            let passes = if *effect == PostEffect::Blur {
+                self.settings.blur_passes
+            } else {
+                1
+            };
+            for _ in 0..passes {
+                let output = self.targets[next_target];
+                self.apply_effect(command_buffer, *effect, input, output, time);
+                input = output;
+                next_target = 1 - next_target;
+            }
+        }
+
+        SDL_BlitGPUTexture(
+            command_buffer,
+            &SDL_GPUBlitInfo {
+                source: SDL_GPUBlitRegion {
+                    texture: input,
+                    w: self.width,
+                    h: self.height,
+                    ..Default::default()
+                },
+                destination: SDL_GPUBlitRegion {
+                    texture: target,
+                    w: target_width,
+                    h: target_height,
+                    ..Default::default()
+                },
+                load_op: SDL_GPULoadOp::DONT_CARE,
+                filter: SDL_GPU_FILTER_LINEAR,
+                ..Default::default()
+            },
+        );
+    }
+
+    unsafe fn apply_effect(
+        &self,
+        command_buffer: *mut SDL_GPUCommandBuffer,
+        effect: PostEffect,
+        input: *mut SDL_GPUTexture,
+        output: *mut SDL_GPUTexture,
+        time: f32,
+    ) {
+        if effect == PostEffect::Vignette {
+            let uniforms = VignetteUniforms {
+                strength: self.settings.vignette_strength,
+                radius: self.settings.vignette_radius,
+                padding: [0.0; 2],
+            };
+            SDL_PushGPUComputeUniformData(
+                command_buffer,
+                0,
+                &uniforms as *const VignetteUniforms as *const c_void,
+                std::mem::size_of::<VignetteUniforms>() as u32,
+            );
+
+            let compute_pass = SDL_BeginGPUComputePass(
+                command_buffer,
+                &SDL_GPUStorageTextureReadWriteBinding {
+                    texture: output,
+                    ..Default::default()
+                },
+                1,
+                null_mut(),
+                0,
+            );
+            SDL_BindGPUComputePipeline(compute_pass, self.vignette_pipeline);
+            SDL_BindGPUComputeStorageTextures(compute_pass, 0, &input, 1);
+            SDL_DispatchGPUCompute(
+                compute_pass,
+                self.width.div_ceil(THREAD_GROUP_SIZE),
+                self.height.div_ceil(THREAD_GROUP_SIZE),
+                1,
+            );
+            SDL_EndGPUComputePass(compute_pass);
+            return;
+        }
+
+        // every fragment effect covers the whole output, so its old contents don't matter
+        let render_pass = SDL_BeginGPURenderPass(
+            command_buffer,
+            &SDL_GPUColorTargetInfo {
+                texture: output,
+                load_op: SDL_GPULoadOp::DONT_CARE,
+                store_op: SDL_GPUStoreOp::STORE,
+                ..Default::default()
+            },
+            1,
+            null_mut(),
+        );
+
+        match effect {
+            PostEffect::Blur => {
+                SDL_BindGPUGraphicsPipeline(render_pass, self.blur_pipeline);
+                SDL_BindGPUFragmentStorageTextures(render_pass, 0, &input, 1);
+                let uniforms = BlurUniforms {
+                    mode: 1,
+                    padding: [0; 3],
+                };
+                SDL_PushGPUFragmentUniformData(
+                    command_buffer,
+                    0,
+                    &uniforms as *const BlurUniforms as *const c_void,
+                    std::mem::size_of::<BlurUniforms>() as u32,
+                );
+            }
+            PostEffect::Scanlines => {
+                SDL_BindGPUGraphicsPipeline(render_pass, self.scanlines_pipeline);
+                SDL_BindGPUFragmentSamplers(
+                    render_pass,
+                    0,
+                    &SDL_GPUTextureSamplerBinding {
+                        texture: input,
+                        sampler: self.sampler,
+                    },
+                    1,
+                );
+                let uniforms = ScanlineUniforms {
+                    resolution: [self.width as f32, self.height as f32],
+                    intensity: self.settings.scanline_intensity,
+                    time,
+                };
+                SDL_PushGPUFragmentUniformData(
+                    command_buffer,
+                    0,
+                    &uniforms as *const ScanlineUniforms as *const c_void,
+                    std::mem::size_of::<ScanlineUniforms>() as u32,
+                );
+            }
+            PostEffect::ColorGrade => {
+                SDL_BindGPUGraphicsPipeline(render_pass, self.color_grade_pipeline);
+                let bindings = [
+                    SDL_GPUTextureSamplerBinding {
+                        texture: input,
+                        sampler: self.sampler,
+                    },
+                    SDL_GPUTextureSamplerBinding {
+                        texture: self.lut_texture,
+                        sampler: self.sampler,
+                    },
+                ];
+                SDL_BindGPUFragmentSamplers(
+                    render_pass,
+                    0,
+                    bindings.as_ptr(),
+                    bindings.len() as u32,
+                );
+            }
+            PostEffect::Vignette => unreachable!(),
+        }
+
+        // one triangle covering the screen, see Fullscreen.vert
+        SDL_DrawGPUPrimitives(render_pass, 3, 1, 0, 0);
+        SDL_EndGPURenderPass(render_pass);
+    }
+}
+
+/// A Fullscreen.vert pipeline with no vertex input or blending, drawing to `SCENE_FORMAT`.
+unsafe fn create_fullscreen_pipeline(
+    device: *mut SDL_GPUDevice,
+    frag_name: &'static str,
+) -> *mut SDL_GPUGraphicsPipeline {
+    let vert_shader = load_shader(device, "Fullscreen.vert");
+    if vert_shader.is_null() {
+        dbg_sdl_error("failed to load vert shader");
+        return null_mut();
+    }
+
+    let frag_shader = load_shader(device, frag_name);
+    if frag_shader.is_null() {
+        dbg_sdl_error("failed to load frag shader");
+        SDL_ReleaseGPUShader(device, vert_shader);
+        return null_mut();
+    }
+
+    let pipeline = SDL_CreateGPUGraphicsPipeline(
+        device,
+        &SDL_GPUGraphicsPipelineCreateInfo {
+            vertex_shader: vert_shader,
+            fragment_shader: frag_shader,
+            primitive_type: SDL_GPU_PRIMITIVETYPE_TRIANGLELIST,
+            target_info: SDL_GPUGraphicsPipelineTargetInfo {
+                num_color_targets: 1,
+                color_target_descriptions: [SDL_GPUColorTargetDescription {
+                    format: SCENE_FORMAT,
+                    ..Default::default()
+                }]
+                .as_ptr(),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    SDL_ReleaseGPUShader(device, vert_shader);
+    SDL_ReleaseGPUShader(device, frag_shader);
+
+    if pipeline.is_null() {
+        dbg_sdl_error(&format!(
+            "failed to create post process pipeline: {frag_name}"
+        ));
+    }
+
+    pipeline
+}
+
+unsafe fn create_target(
+    device: *mut SDL_GPUDevice,
+    width: u32,
+    height: u32,
+    usage: SDL_GPUTextureUsageFlags,
+) -> *mut SDL_GPUTexture {
+    let texture = SDL_CreateGPUTexture(
+        device,
+        &SDL_GPUTextureCreateInfo {
+            r#type: SDL_GPUTextureType::_2D,
+            format: SCENE_FORMAT,
+            width,
+            height,
+            layer_count_or_depth: 1,
+            num_levels: 1,
+            usage,
+            ..Default::default()
+        },
+    );
+    if texture.is_null() {
+        dbg_sdl_error("failed to create post process target");
+    }
+
+    texture
+}
+
+/// Builds the 3D lookup table for a preset, uploading it on a separate command buffer.
+unsafe fn create_lut_texture(device: *mut SDL_GPUDevice, preset: LutPreset) -> *mut SDL_GPUTexture {
+    let mut texels = Vec::with_capacity((LUT_SIZE * LUT_SIZE * LUT_SIZE * 4) as usize);
+    let max = (LUT_SIZE - 1) as f32;
+    for b in 0..LUT_SIZE {
+        for g in 0..LUT_SIZE {
+            for r in 0..LUT_SIZE {
+                let graded = preset.grade([r as f32 / max, g as f32 / max, b as f32 / max]);
+                texels.extend(graded.map(|c| (c * 255.0).round() as u8));
+                texels.push(255);
+            }
+        }
+    }
+
+    let texture = SDL_CreateGPUTexture(
+        device,
+        &SDL_GPUTextureCreateInfo {
+            r#type: SDL_GPUTextureType::_3D,
+            format: SDL_GPUTextureFormat::R8G8B8A8_UNORM,
+            width: LUT_SIZE,
+            height: LUT_SIZE,
+            layer_count_or_depth: LUT_SIZE,
+            num_levels: 1,
+            usage: SDL_GPU_TEXTUREUSAGE_SAMPLER,
+            ..Default::default()
+        },
+    );
+    if texture.is_null() {
+        dbg_sdl_error("failed to create color grading lut");
+        return texture;
+    }
+
+    if !upload_texture_data(device, texture, LUT_SIZE, LUT_SIZE, LUT_SIZE, &texels) {
+        SDL_ReleaseGPUTexture(device, texture);
+        return null_mut();
+    }
+
+    texture
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every corner of the color cube, the middle, and values just outside 0..1.
+    fn sample_colors() -> Vec<[f32; 3]> {
+        let mut colors = Vec::new();
+        for r in [0.0, 1.0] {
+            for g in [0.0, 1.0] {
+                for b in [0.0, 1.0] {
+                    colors.push([r, g, b]);
+                }
+            }
+        }
+        colors.push([0.5, 0.5, 0.5]);
+        colors.push([1.2, -0.1, 0.9]);
+        colors
+    }
+
+    #[test]
+    fn grade_stays_in_range() {
+        for preset in LutPreset::ALL {
+            for color in sample_colors() {
+                let graded = preset.grade(color);
+                assert!(
+                    graded.iter().all(|c| (0.0..=1.0).contains(c)),
+                    "{preset:?} graded {color:?} to {graded:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn grade_presets() {
+        // warm pushes red over blue, cool the opposite
+        let [r, _, b] = LutPreset::Warm.grade([0.5, 0.5, 0.5]);
+        assert!(r > b);
+        let [r, _, b] = LutPreset::Cool.grade([0.5, 0.5, 0.5]);
+        assert!(b > r);
+
+        // noir is grey, and keeps black and white
+        let [r, g, b] = LutPreset::Noir.grade([0.9, 0.2, 0.4]);
+        assert!(r == g && g == b);
+        assert_eq!(LutPreset::Noir.grade([0.0, 0.0, 0.0]), [0.0, 0.0, 0.0]);
+        assert_eq!(LutPreset::Noir.grade([1.0, 1.0, 1.0]), [1.0, 1.0, 1.0]);
+
+        // sepia clips white rather than overflowing it
+        assert_eq!(LutPreset::Sepia.grade([1.0, 1.0, 1.0])[0], 1.0);
+    }
+
+    #[test]
+    fn next_cycles_through_every_preset() {
+        let mut preset = LutPreset::Warm;
+        for expected in [
+            LutPreset::Cool,
+            LutPreset::Sepia,
+            LutPreset::Noir,
+            LutPreset::Warm,
+        ] {
+            preset = preset.next();
+            assert_eq!(preset, expected);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::ffi::c_void;
+
+use sdl3_sys::everything::*;
+
+use crate::common::*;
+
+/// The procedural shaders run 8x8 thread groups.
+const THREAD_GROUP_SIZE: u32 = 8;
+
+/// What a `ProceduralTexture` is filled with.
+#[derive(Clone, Copy, Debug)]
+pub enum ProceduralFill {
+    /// One color everywhere, from FillTexture.comp.
+    Solid { color: SDL_FColor },
+    /// A diagonal band between two colors that scrolls as `time` (in seconds) advances,
+    /// from GradientTexture.comp.
+    Gradient {
+        start: SDL_FColor,
+        end: SDL_FColor,
+        time: f32,
+    },
+}
+
+/// see the UBO cbuffer in FillTexture.comp.hlsl
+#[repr(C)]
+struct FillUniforms {
+    color: SDL_FColor,
+}
+
+/// see the UBO cbuffer in GradientTexture.comp.hlsl
+#[repr(C)]
+struct GradientUniforms {
+    start: SDL_FColor,
+    end: SDL_FColor,
+    time: f32,
+    padding: [f32; 3],
+}
+
+/// A texture whose contents are generated on the gpu by a compute shader.
+///
+/// `generate` outside of any pass, then sample `texture()` like any other texture,
+/// e.g. with a `SpriteBatch`. Regenerating every frame is fine; the texture cycles
+/// rather than waiting for the previous frame's draws.
+pub struct ProceduralTexture {
+    fill_pipeline: *mut SDL_GPUComputePipeline,
+    gradient_pipeline: *mut SDL_GPUComputePipeline,
+    texture: *mut SDL_GPUTexture,
+    width: u32,
+    height: u32,
+}
+
+impl ProceduralTexture {
+    pub unsafe fn new(device: *mut SDL_GPUDevice, width: u32, height: u32) -> Option<Self> {
+        let fill_pipeline = create_procedural_pipeline(device, "FillTexture.comp");
+        let gradient_pipeline = create_procedural_pipeline(device, "GradientTexture.comp");
+        if fill_pipeline.is_null() || gradient_pipeline.is_null() {
+            return None;
+        }
+
+        let texture = SDL_CreateGPUTexture(
+            device,
+            &SDL_GPUTextureCreateInfo {
+                r#type: SDL_GPUTextureType::_2D,
+                format: SDL_GPUTextureFormat::R8G8B8A8_UNORM,
+                width,
+                height,
+                layer_count_or_depth: 1,
+                num_levels: 1,
+                usage: SDL_GPU_TEXTUREUSAGE_SAMPLER | SDL_GPU_TEXTUREUSAGE_COMPUTE_STORAGE_WRITE,
+                ..Default::default()
+            },
+        );
+        if texture.is_null() {
+            dbg_sdl_error("failed to create procedural texture");
+            return None;
+        }
+
+        Some(Self {
+            fill_pipeline,
+            gradient_pipeline,
+            texture,
+            width,
+            height,
+        })
+    }
+
+    pub unsafe fn release(&mut self, device: *mut SDL_GPUDevice) {
+        if !self.fill_pipeline.is_null() {
+            SDL_ReleaseGPUComputePipeline(device, self.fill_pipeline);
+        }
+        if !self.gradient_pipeline.is_null() {
+            SDL_ReleaseGPUComputePipeline(device, self.gradient_pipeline);
+        }
+        if !self.texture.is_null() {
+            SDL_ReleaseGPUTexture(device, self.texture);
+        }
+    }
+
+    pub fn texture(&self) -> *mut SDL_GPUTexture {
+        self.texture
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Overwrites the whole texture. Must be called outside of any pass.
+    pub unsafe fn generate(
+        &self,
+        command_buffer: *mut SDL_GPUCommandBuffer,
+        fill: &ProceduralFill,
+    ) {
+        let compute_pass = SDL_BeginGPUComputePass(
+            command_buffer,
+            &SDL_GPUStorageTextureReadWriteBinding {
+                texture: self.texture,
+                cycle: true,
+                ..Default::default()
+            },
+            1,
+            std::ptr::null(),
+            0,
+        );
+
+        match *fill {
+            ProceduralFill::Solid { color } => {
+                SDL_BindGPUComputePipeline(compute_pass, self.fill_pipeline);
+                let uniforms = FillUniforms { color };
+                SDL_PushGPUComputeUniformData(
+                    command_buffer,
+                    0,
+                    &uniforms as *const FillUniforms as *const c_void,
+                    std::mem::size_of::<FillUniforms>() as u32,
+                );
+            }
+            ProceduralFill::Gradient { start, end, time } => {
+                SDL_BindGPUComputePipeline(compute_pass, self.gradient_pipeline);
+                let uniforms = GradientUniforms {
+                    start,
+                    end,
+                    time,
+                    padding: [0.0; 3],
+                };
+                SDL_PushGPUComputeUniformData(
+                    command_buffer,
+                    0,
+                    &uniforms as *const GradientUniforms as *const c_void,
+                    std::mem::size_of::<GradientUniforms>() as u32,
+                );
+            }
+        }
+
+        SDL_DispatchGPUCompute(
+            compute_pass,
+            self.width.div_ceil(THREAD_GROUP_SIZE),
+            self.height.div_ceil(THREAD_GROUP_SIZE),
+            1,
+        );
+        SDL_EndGPUComputePass(compute_pass);
+    }
+}
+
+/// Both procedural shaders write one storage texture and take one uniform block.
+unsafe fn create_procedural_pipeline(
+    device: *mut SDL_GPUDevice,
+    shader_name: &'static str,
+) -> *mut SDL_GPUComputePipeline {
+    load_compute_pipeline(
+        device,
+        shader_name,
+        SDL_GPUComputePipelineCreateInfo {
+            num_readwrite_storage_textures: 1,
+            num_uniform_buffers: 1,
+            threadcount_x: THREAD_GROUP_SIZE,
+            threadcount_y: THREAD_GROUP_SIZE,
+            threadcount_z: 1,
+            ..Default::default()
+        },
+    )
+}
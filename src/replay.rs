@@ -0,0 +1,304 @@
+use std::path::Path;
+
+use sdl3_sys::scancode::SDL_Scancode;
+
+use serde::{Deserialize, Serialize};
+
+use crate::input::Input;
+use crate::time::{FixedTimestep, ManualClock};
+
+/// The keyboard state seen by a single fixed step.
+/// Scancodes are stored as their raw values so recordings stay plain json.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputSnapshot {
+    pub keys_down: Vec<i32>,
+    pub keys_just_pressed: Vec<i32>,
+}
+
+impl InputSnapshot {
+    pub fn capture(keys_down: &[SDL_Scancode], keys_just_pressed: &[SDL_Scancode]) -> Self {
+        Self {
+            keys_down: keys_down.iter().map(|k| k.0).collect(),
+            keys_just_pressed: keys_just_pressed.iter().map(|k| k.0).collect(),
+        }
+    }
+
+    pub fn keys_down(&self) -> Vec<SDL_Scancode> {
+        self.keys_down.iter().map(|k| SDL_Scancode(*k)).collect()
+    }
+
+    pub fn keys_just_pressed(&self) -> Vec<SDL_Scancode> {
+        self.keys_just_pressed
+            .iter()
+            .map(|k| SDL_Scancode(*k))
+            .collect()
+    }
+}
+
+/// One input snapshot per fixed step, in order.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    pub steps_per_second: u32,
+    /// The `SDL_srand` seed of the recorded run, so `SDL_rand` repeats on replay.
+    /// None in recordings made before seeds were saved.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    pub steps: Vec<InputSnapshot>,
+}
+
+impl InputRecording {
+    pub fn new(steps_per_second: u32, seed: u64) -> Self {
+        Self {
+            steps_per_second,
+            seed: Some(seed),
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let json = serde_json::to_string(self)?;
+        std::fs::write(path, json)
+    }
+
+    /// Fails on malformed json, or a recording without a positive step rate.
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        let recording: Self = serde_json::from_str(&json)?;
+        if recording.steps_per_second == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "steps_per_second must be at least 1",
+            ));
+        }
+        Ok(recording)
+    }
+}
+
+/// Feeds a recording back one fixed step at a time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputReplay {
+    recording: InputRecording,
+    next_step: usize,
+}
+
+impl InputReplay {
+    pub fn new(recording: InputRecording) -> Self {
+        Self {
+            recording,
+            next_step: 0,
+        }
+    }
+
+    pub fn next_snapshot(&mut self) -> Option<&InputSnapshot> {
+        let snapshot = self.recording.steps.get(self.next_step)?;
+        self.next_step += 1;
+        Some(snapshot)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_step >= self.recording.steps.len()
+    }
+}
+
+/// Runs a recording through fresh `Input` with a manual clock,
+/// calling `fixed_step` once per recorded step. No window or GPU is involved,
+/// so gameplay logic can be checked against a recording directly.
+/// Logic that uses `SDL_rand` should be seeded with `recording.seed` first.
+pub fn replay(recording: InputRecording, mut fixed_step: impl FnMut(&Input)) {
+    let step_count = recording.steps.len();
+
+    let mut clock = ManualClock::default();
+    let mut timestep = FixedTimestep::new(recording.steps_per_second);
+    let mut input = Input::from_recording(recording);
+    timestep.advance(&clock);
+
+    for _ in 0..step_count {
+        clock.advance_ns(timestep.step_ns());
+        for _ in 0..timestep.advance(&clock) {
+            input.begin_step();
+            fixed_step(&input);
+            input.end_step();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A stand-in for gameplay: moves with the arrow keys and counts space presses.
+    #[derive(Clone, Debug, Default, PartialEq)]
+    struct Walker {
+        x: i32,
+        y: i32,
+        jumps: u32,
+        steps: u32,
+    }
+
+    impl Walker {
+        fn fixed_step(&mut self, input: &Input) {
+            if input.is_down(SDL_Scancode::LEFT) {
+                self.x -= 1;
+            }
+            if input.is_down(SDL_Scancode::RIGHT) {
+                self.x += 1;
+            }
+            if input.is_down(SDL_Scancode::DOWN) {
+                self.y += 1;
+            }
+            if input.just_pressed(SDL_Scancode::SPACE) {
+                self.jumps += 1;
+            }
+            self.steps += 1;
+        }
+    }
+
+    /// Plays key events between steps like the event loop would, recording as it goes.
+    fn record_walk() -> (InputRecording, Walker) {
+        let mut input = Input::new();
+        input.start_recording(60, 1234);
+        let mut walker = Walker::default();
+
+        let mut step = |input: &mut Input| {
+            input.begin_step();
+            walker.fixed_step(input);
+            input.end_step();
+        };
+
+        input.key_pressed(SDL_Scancode::RIGHT);
+        for _ in 0..10 {
+            step(&mut input);
+        }
+        input.key_pressed(SDL_Scancode::SPACE);
+        input.key_pressed(SDL_Scancode::DOWN);
+        step(&mut input);
+        input.key_released(SDL_Scancode::SPACE);
+        input.key_released(SDL_Scancode::RIGHT);
+        for _ in 0..5 {
+            step(&mut input);
+        }
+        input.key_pressed(SDL_Scancode::LEFT);
+        input.key_pressed(SDL_Scancode::SPACE);
+        for _ in 0..3 {
+            step(&mut input);
+        }
+
+        (input.finish_recording().unwrap(), walker)
+    }
+
+    #[test]
+    fn replay_matches_recorded_run() {
+        let (recording, recorded) = record_walk();
+        assert_eq!(recording.steps.len(), 19);
+
+        let mut replayed = Walker::default();
+        replay(recording, |input| replayed.fixed_step(input));
+
+        assert_eq!(replayed, recorded);
+        assert_eq!(
+            recorded,
+            Walker {
+                x: 8,
+                y: 9,
+                jumps: 2,
+                steps: 19,
+            }
+        );
+    }
+
+    #[test]
+    fn replayed_input_ignores_live_keys() {
+        let (recording, recorded) = record_walk();
+
+        let mut input = Input::from_recording(recording);
+        let mut replayed = Walker::default();
+        for _ in 0..recorded.steps {
+            input.key_pressed(SDL_Scancode::LEFT);
+            input.begin_step();
+            replayed.fixed_step(&input);
+            input.end_step();
+        }
+
+        assert_eq!(replayed, recorded);
+    }
+
+    #[test]
+    fn steps_past_the_recording_have_no_keys() {
+        let (recording, _) = record_walk();
+        let step_count = recording.steps.len();
+
+        let mut input = Input::from_recording(recording);
+        for _ in 0..step_count {
+            input.begin_step();
+            input.end_step();
+        }
+        input.begin_step();
+        assert!(!input.is_down(SDL_Scancode::LEFT));
+        assert!(!input.just_pressed(SDL_Scancode::SPACE));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let (recording, _) = record_walk();
+
+        let json = serde_json::to_string(&recording).unwrap();
+        let loaded: InputRecording = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, recording);
+        assert_eq!(loaded.seed, Some(1234));
+    }
+
+    #[test]
+    fn recordings_without_a_seed_still_load() {
+        let json =
+            r#"{"steps_per_second":60,"steps":[{"keys_down":[80],"keys_just_pressed":[80]}]}"#;
+        let loaded: InputRecording = serde_json::from_str(json).unwrap();
+
+        assert_eq!(loaded.seed, None);
+        assert_eq!(loaded.steps[0].keys_down(), [SDL_Scancode::LEFT]);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let (recording, _) = record_walk();
+        let path = std::env::temp_dir()
+            .join(format!("sdl3-experiment-replay-{}", std::process::id()))
+            .join("walk.json");
+
+        recording.save(&path).unwrap();
+        let loaded = InputRecording::load(&path);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+
+        assert_eq!(loaded.unwrap(), recording);
+    }
+
+    #[test]
+    fn load_rejects_malformed_json() {
+        let path = std::env::temp_dir().join(format!(
+            "sdl3-experiment-replay-malformed-{}.json",
+            std::process::id()
+        ));
+        std::fs::write(&path, "{\"steps_per_second\": 60, \"steps\": [").unwrap();
+        let loaded = InputRecording::load(&path);
+        let _ = std::fs::remove_file(&path);
+
+        assert!(loaded.is_err());
+    }
+
+    #[test]
+    fn load_rejects_zero_steps_per_second() {
+        let path = std::env::temp_dir().join(format!(
+            "sdl3-experiment-replay-zero-rate-{}.json",
+            std::process::id()
+        ));
+        InputRecording::new(0, 1234).save(&path).unwrap();
+        let loaded = InputRecording::load(&path);
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(loaded.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,302 @@
+use std::ffi::c_void;
+
+use sdl3_sys::everything::*;
+
+use crate::common::*;
+
+/// see SpriteData in PullSpriteBatch.vert.hlsl
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GPUSprite {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub rotation: f32,
+    pub w: f32,
+    pub h: f32,
+    /// The array layer to sample, for batches created with `SpriteBatch::new_array`.
+    pub layer: f32,
+    pub padding: f32,
+    pub tex_u: f32,
+    pub tex_v: f32,
+    pub tex_w: f32,
+    pub tex_h: f32,
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Default for GPUSprite {
+    /// A white 32x32 sprite covering the whole texture.
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            rotation: 0.0,
+            w: 32.0,
+            h: 32.0,
+            layer: 0.0,
+            padding: 0.0,
+            tex_u: 0.0,
+            tex_v: 0.0,
+            tex_w: 1.0,
+            tex_h: 1.0,
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+            a: 1.0,
+        }
+    }
+}
+
+/// Draws up to `capacity` textured quads in one draw call,
+/// using the vertex pulling technique from PullSpriteBatch.vert.
+///
+/// Each frame: `push` sprites, `upload` them before the render pass,
+/// then `draw` inside the render pass.
+///
+/// Batches from `new` sample 2D textures; batches from `new_array` sample 2D array
+/// textures, with each sprite picking its own `layer`.
+pub struct SpriteBatch {
+    pipeline: *mut SDL_GPUGraphicsPipeline,
+    transfer_buffer: *mut SDL_GPUTransferBuffer,
+    buffer: *mut SDL_GPUBuffer,
+    capacity: u32,
+    sprites: Vec<GPUSprite>,
+    uploaded_count: u32,
+}
+
+impl SpriteBatch {
+    pub unsafe fn new(
+        device: *mut SDL_GPUDevice,
+        target_format: SDL_GPUTextureFormat,
+        capacity: u32,
+    ) -> Option<Self> {
+        Self::with_fragment_shader(device, target_format, capacity, "TexturedQuadColor.frag")
+    }
+
+    /// A batch whose `draw` takes a 2D array texture, e.g. from `load_texture_array`.
+    pub unsafe fn new_array(
+        device: *mut SDL_GPUDevice,
+        target_format: SDL_GPUTextureFormat,
+        capacity: u32,
+    ) -> Option<Self> {
+        Self::with_fragment_shader(
+            device,
+            target_format,
+            capacity,
+            "TexturedQuadArrayColor.frag",
+        )
+    }
+
+    unsafe fn with_fragment_shader(
+        device: *mut SDL_GPUDevice,
+        target_format: SDL_GPUTextureFormat,
+        capacity: u32,
+        frag_name: &'static str,
+    ) -> Option<Self> {
+        let vert_shader = load_shader(device, "PullSpriteBatch.vert");
+        if vert_shader.is_null() {
+            dbg_sdl_error("failed to load vert shader");
+            return None;
+        }
+
+        let frag_shader = load_shader(device, frag_name);
+        if frag_shader.is_null() {
+            dbg_sdl_error("failed to load frag shader");
+            SDL_ReleaseGPUShader(device, vert_shader);
+            return None;
+        }
+
+        let pipeline = SDL_CreateGPUGraphicsPipeline(
+            device,
+            &SDL_GPUGraphicsPipelineCreateInfo {
+                primitive_type: SDL_GPUPrimitiveType::TRIANGLELIST,
+                vertex_shader: vert_shader,
+                fragment_shader: frag_shader,
+                target_info: SDL_GPUGraphicsPipelineTargetInfo {
+                    num_color_targets: 1,
+                    color_target_descriptions: [SDL_GPUColorTargetDescription {
+                        format: target_format,
+                        blend_state: SDL_GPUColorTargetBlendState {
+                            enable_blend: true,
+                            color_blend_op: SDL_GPUBlendOp::ADD,
+                            alpha_blend_op: SDL_GPUBlendOp::ADD,
+                            src_color_blendfactor: SDL_GPUBlendFactor::SRC_ALPHA,
+                            dst_color_blendfactor: SDL_GPUBlendFactor::ONE_MINUS_SRC_ALPHA,
+                            src_alpha_blendfactor: SDL_GPUBlendFactor::SRC_ALPHA,
+                            dst_alpha_blendfactor: SDL_GPUBlendFactor::ONE_MINUS_SRC_ALPHA,
+                            ..Default::default()
+                        },
+                    }]
+                    .as_ptr(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+
+        SDL_ReleaseGPUShader(device, vert_shader);
+        SDL_ReleaseGPUShader(device, frag_shader);
+
+        if pipeline.is_null() {
+            dbg_sdl_error("failed to create sprite batch pipeline");
+            return None;
+        }
+
+        let buffer_size = capacity * std::mem::size_of::<GPUSprite>() as u32;
+
+        let transfer_buffer = SDL_CreateGPUTransferBuffer(
+            device,
+            &SDL_GPUTransferBufferCreateInfo {
+                usage: SDL_GPUTransferBufferUsage::UPLOAD,
+                size: buffer_size,
+                ..Default::default()
+            },
+        );
+
+        let buffer = SDL_CreateGPUBuffer(
+            device,
+            &SDL_GPUBufferCreateInfo {
+                usage: SDL_GPU_BUFFERUSAGE_GRAPHICS_STORAGE_READ,
+                size: buffer_size,
+                ..Default::default()
+            },
+        );
+
+        let mut batch = Self {
+            pipeline,
+            transfer_buffer,
+            buffer,
+            capacity,
+            sprites: Vec::with_capacity(capacity as usize),
+            uploaded_count: 0,
+        };
+        if transfer_buffer.is_null() || buffer.is_null() {
+            dbg_sdl_error("failed to create sprite batch buffers");
+            batch.release(device);
+            return None;
+        }
+
+        Some(batch)
+    }
+
+    pub unsafe fn release(&mut self, device: *mut SDL_GPUDevice) {
+        if !self.pipeline.is_null() {
+            SDL_ReleaseGPUGraphicsPipeline(device, self.pipeline);
+        }
+        if !self.transfer_buffer.is_null() {
+            SDL_ReleaseGPUTransferBuffer(device, self.transfer_buffer);
+        }
+        if !self.buffer.is_null() {
+            SDL_ReleaseGPUBuffer(device, self.buffer);
+        }
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.sprites.clear();
+    }
+
+    /// How many sprites the last `upload` sent, which is what `draw` draws.
+    pub fn uploaded_len(&self) -> u32 {
+        self.uploaded_count
+    }
+
+    /// The size of the last `upload`.
+    pub fn uploaded_bytes(&self) -> usize {
+        self.uploaded_count as usize * std::mem::size_of::<GPUSprite>()
+    }
+
+    /// Sprites past the batch's capacity are dropped.
+    pub fn push(&mut self, sprite: GPUSprite) {
+        if self.sprites.len() < self.capacity as usize {
+            self.sprites.push(sprite);
+        }
+    }
+
+    /// Copies the pushed sprites to the gpu and clears them. Must be called outside of a render pass.
+    pub unsafe fn upload(
+        &mut self,
+        device: *mut SDL_GPUDevice,
+        command_buffer: *mut SDL_GPUCommandBuffer,
+    ) -> bool {
+        self.uploaded_count = self.sprites.len() as u32;
+        if self.sprites.is_empty() {
+            return true;
+        }
+
+        let data_ptr =
+            SDL_MapGPUTransferBuffer(device, self.transfer_buffer, true) as *mut GPUSprite;
+        if data_ptr.is_null() {
+            dbg_sdl_error("failed to map gpu transfer buffer");
+            return false;
+        }
+
+        std::ptr::copy_nonoverlapping(self.sprites.as_ptr(), data_ptr, self.sprites.len());
+        SDL_UnmapGPUTransferBuffer(device, self.transfer_buffer);
+
+        let copy_pass = SDL_BeginGPUCopyPass(command_buffer);
+        SDL_UploadToGPUBuffer(
+            copy_pass,
+            &SDL_GPUTransferBufferLocation {
+                transfer_buffer: self.transfer_buffer,
+                offset: 0,
+            },
+            &SDL_GPUBufferRegion {
+                buffer: self.buffer,
+                offset: 0,
+                size: self.uploaded_count * std::mem::size_of::<GPUSprite>() as u32,
+            },
+            true,
+        );
+        SDL_EndGPUCopyPass(copy_pass);
+
+        self.sprites.clear();
+
+        true
+    }
+
+    /// Draws the sprites from the last `upload` within an existing render pass.
+    pub unsafe fn draw(
+        &self,
+        command_buffer: *mut SDL_GPUCommandBuffer,
+        render_pass: *mut SDL_GPURenderPass,
+        texture: *mut SDL_GPUTexture,
+        sampler: *mut SDL_GPUSampler,
+        view_projection: &Matrix4x4,
+    ) {
+        if self.uploaded_count == 0 {
+            return;
+        }
+
+        SDL_BindGPUGraphicsPipeline(render_pass, self.pipeline);
+        SDL_BindGPUVertexStorageBuffers(render_pass, 0, &self.buffer, 1);
+        SDL_BindGPUFragmentSamplers(
+            render_pass,
+            0,
+            &SDL_GPUTextureSamplerBinding { texture, sampler },
+            1,
+        );
+        SDL_PushGPUVertexUniformData(
+            command_buffer,
+            0,
+            view_projection as *const Matrix4x4 as *const c_void,
+            std::mem::size_of::<Matrix4x4>() as u32,
+        );
+        SDL_DrawGPUPrimitives(render_pass, self.uploaded_count * 6, 1, 0, 0);
+    }
+}
@@ -0,0 +1,695 @@
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use std::ptr::null_mut;
+
+use sdl3_sys::everything::*;
+
+use crate::common::*;
+use crate::image::Image;
+use crate::sprite_batch::{GPUSprite, SpriteBatch};
+
+const FONTS_DIR: &str = "./content/fonts";
+
+/// Where one character is in a font's atlas and how it sits on a line, in atlas pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Glyph {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// From the pen position to the top left of the quad.
+    pub x_offset: f32,
+    pub y_offset: f32,
+    /// How far the pen moves for the next character.
+    pub x_advance: f32,
+}
+
+/// The parts of an AngelCode BMFont description needed for layout.
+/// Only single page fonts are supported, since a sprite batch draws from one texture.
+///
+/// https://www.angelcode.com/products/bmfont/doc/file_format.html
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FontData {
+    pub line_height: f32,
+    /// From the top of a line to the baseline.
+    pub base: f32,
+    pub atlas_width: f32,
+    pub atlas_height: f32,
+    /// The atlas image, relative to the font file.
+    pub page_file: String,
+    pub glyphs: HashMap<char, Glyph>,
+    /// Added to the advance between a pair of characters.
+    pub kerning: HashMap<(char, char), f32>,
+}
+
+impl FontData {
+    /// Parses either the binary or the text format, depending on the header.
+    pub fn parse(bytes: &[u8]) -> std::io::Result<Self> {
+        if bytes.starts_with(b"BMF") {
+            Self::parse_binary(bytes)
+        } else {
+            let text =
+                std::str::from_utf8(bytes).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+            Self::parse_text(text)
+        }
+    }
+
+    /// Parses the text format: one tag per line, followed by `key=value` pairs.
+    pub fn parse_text(text: &str) -> std::io::Result<Self> {
+        let mut font = Self::default();
+        let mut pages = 0;
+
+        for line in text.lines() {
+            let mut tokens = tokenize(line).into_iter();
+            let Some((tag, _)) = tokens.next() else {
+                continue;
+            };
+            let values: HashMap<&str, &str> = tokens.collect();
+            let number = |key: &str| -> std::io::Result<f32> {
+                values
+                    .get(key)
+                    .and_then(|value| value.parse::<f32>().ok())
+                    .ok_or_else(|| invalid_data(format!("{tag} is missing a number for {key}")))
+            };
+            let character = |key: &str| -> std::io::Result<char> {
+                values
+                    .get(key)
+                    .and_then(|value| value.parse::<u32>().ok())
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| invalid_data(format!("{tag} has an invalid {key}")))
+            };
+
+            match tag {
+                "common" => {
+                    font.line_height = number("lineHeight")?;
+                    font.base = number("base")?;
+                    font.atlas_width = number("scaleW")?;
+                    font.atlas_height = number("scaleH")?;
+                    pages = number("pages")? as u32;
+                }
+                "page" if values.get("id") == Some(&"0") => {
+                    font.page_file = values.get("file").unwrap_or(&"").to_string();
+                }
+                "char" => {
+                    let glyph = Glyph {
+                        x: number("x")?,
+                        y: number("y")?,
+                        width: number("width")?,
+                        height: number("height")?,
+                        x_offset: number("xoffset")?,
+                        y_offset: number("yoffset")?,
+                        x_advance: number("xadvance")?,
+                    };
+                    // a negative id is the fallback glyph in some generators; skip it
+                    if let Ok(c) = character("id") {
+                        font.glyphs.insert(c, glyph);
+                    }
+                }
+                "kerning" => {
+                    let pair = (character("first")?, character("second")?);
+                    font.kerning.insert(pair, number("amount")?);
+                }
+                _ => {}
+            }
+        }
+
+        font.validate(pages)?;
+        Ok(font)
+    }
+
+    /// Parses version 3 of the binary format.
+    pub fn parse_binary(bytes: &[u8]) -> std::io::Result<Self> {
+        if bytes.len() < 4 || &bytes[..3] != b"BMF" {
+            return Err(invalid_data("not a binary BMFont file".to_string()));
+        }
+        if bytes[3] != 3 {
+            return Err(invalid_data(format!(
+                "unsupported binary BMFont version {}",
+                bytes[3]
+            )));
+        }
+
+        let mut font = Self::default();
+        let mut pages = 0;
+        let mut reader = ByteReader::new(&bytes[4..]);
+        while !reader.is_empty() {
+            let block_type = reader.u8()?;
+            let block_size = reader.u32()? as usize;
+            let mut block = ByteReader::new(reader.take(block_size)?);
+
+            match block_type {
+                // common
+                2 => {
+                    font.line_height = block.u16()? as f32;
+                    font.base = block.u16()? as f32;
+                    font.atlas_width = block.u16()? as f32;
+                    font.atlas_height = block.u16()? as f32;
+                    pages = block.u16()? as u32;
+                }
+                // pages: null terminated file names; the first is page 0
+                3 => {
+                    let names = block.take(block_size)?;
+                    let first = names.split(|b| *b == 0).next().unwrap_or(&[]);
+                    font.page_file = String::from_utf8_lossy(first).into_owned();
+                }
+                // chars: 20 bytes each
+                4 => {
+                    while !block.is_empty() {
+                        let id = block.u32()?;
+                        let glyph = Glyph {
+                            x: block.u16()? as f32,
+                            y: block.u16()? as f32,
+                            width: block.u16()? as f32,
+                            height: block.u16()? as f32,
+                            x_offset: block.i16()? as f32,
+                            y_offset: block.i16()? as f32,
+                            x_advance: block.i16()? as f32,
+                        };
+                        // page and channel
+                        block.take(2)?;
+                        if let Some(c) = char::from_u32(id) {
+                            font.glyphs.insert(c, glyph);
+                        }
+                    }
+                }
+                // kerning pairs: 10 bytes each
+                5 => {
+                    while !block.is_empty() {
+                        let first = block.u32()?;
+                        let second = block.u32()?;
+                        let amount = block.i16()? as f32;
+                        if let (Some(first), Some(second)) =
+                            (char::from_u32(first), char::from_u32(second))
+                        {
+                            font.kerning.insert((first, second), amount);
+                        }
+                    }
+                }
+                // info, and anything newer
+                _ => {}
+            }
+        }
+
+        font.validate(pages)?;
+        Ok(font)
+    }
+
+    fn validate(&self, pages: u32) -> std::io::Result<()> {
+        if pages != 1 {
+            return Err(invalid_data(format!(
+                "fonts must have exactly one page, found {pages}"
+            )));
+        }
+        if self.page_file.is_empty() || self.atlas_width <= 0.0 || self.atlas_height <= 0.0 {
+            return Err(invalid_data(
+                "font is missing its common or page info".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The glyph for `c`, falling back to '?' for characters the font doesn't have.
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(&c).or_else(|| self.glyphs.get(&'?'))
+    }
+
+    pub fn kerning(&self, first: char, second: char) -> f32 {
+        self.kerning.get(&(first, second)).copied().unwrap_or(0.0)
+    }
+
+    /// The unscaled width of a single line, from the first pen position to the last advance.
+    pub fn line_width(&self, line: &str) -> f32 {
+        let mut width = 0.0;
+        let mut previous = None;
+        for c in line.chars() {
+            if let Some(previous) = previous {
+                width += self.kerning(previous, c);
+            }
+            if let Some(glyph) = self.glyph(c) {
+                width += glyph.x_advance;
+            }
+            previous = Some(c);
+        }
+        width
+    }
+
+    /// Splits text into lines at newlines, and between words to keep lines within `max_width`
+    /// (unscaled) if given. A single word wider than `max_width` gets a line of its own.
+    pub fn wrap<'a>(&self, text: &'a str, max_width: Option<f32>) -> Vec<&'a str> {
+        let mut lines = Vec::new();
+        for paragraph in text.split('\n') {
+            let Some(max_width) = max_width else {
+                lines.push(paragraph);
+                continue;
+            };
+
+            let mut start = 0;
+            let mut end = 0;
+            for (word_start, word) in word_spans(paragraph) {
+                let word_end = word_start + word.len();
+                if end > start && self.line_width(&paragraph[start..word_end]) > max_width {
+                    lines.push(&paragraph[start..end]);
+                    start = word_start;
+                }
+                end = word_end;
+            }
+            lines.push(&paragraph[start..end]);
+        }
+        lines
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl TextAlign {
+    /// How far a line `line_width` wide is moved along to align it within `block_width`.
+    /// Centered lines land on whole units so pixel fonts stay crisp.
+    pub fn offset(self, block_width: f32, line_width: f32) -> f32 {
+        match self {
+            TextAlign::Left => 0.0,
+            TextAlign::Center => ((block_width - line_width) * 0.5).round(),
+            TextAlign::Right => block_width - line_width,
+        }
+    }
+}
+
+/// How `BitmapFont::push_text` lays text out.
+#[derive(Clone, Copy, Debug)]
+pub struct TextStyle {
+    /// Screen units per font pixel. Whole numbers keep pixel fonts crisp.
+    pub scale: f32,
+    pub color: SDL_FColor,
+    /// Lines are aligned within `max_width` when wrapping, otherwise within the widest line.
+    pub align: TextAlign,
+    /// Wrap between words to stay within this width, in screen units.
+    pub max_width: Option<f32>,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            color: SDL_FColor {
+                r: 1.0,
+                g: 1.0,
+                b: 1.0,
+                a: 1.0,
+            },
+            align: TextAlign::Left,
+            max_width: None,
+        }
+    }
+}
+
+/// A BMFont and its atlas texture, for drawing text as sprites.
+///
+/// `push_text` adds a quad per glyph to a `SpriteBatch`; draw that batch with `texture()`
+/// and, for pixel fonts, a nearest sampler.
+pub struct BitmapFont {
+    data: FontData,
+    texture: *mut SDL_GPUTexture,
+}
+
+impl BitmapFont {
+    /// Loads a font file from the fonts directory, in either BMFont format, and its atlas bmp.
+    pub unsafe fn load(device: *mut SDL_GPUDevice, file_name: &str) -> Option<Self> {
+        let path = Path::new(FONTS_DIR).join(file_name);
+        let data = match std::fs::read(&path).and_then(|bytes| FontData::parse(&bytes)) {
+            Ok(data) => data,
+            Err(e) => {
+                println!("failed to load font: {e} {}", path.display());
+                return None;
+            }
+        };
+
+        let atlas_path = path.parent().unwrap_or(Path::new("")).join(&data.page_file);
+        let atlas = Image::load_bmp(&atlas_path)?;
+        let texture = create_texture_from_pixels(
+            device,
+            atlas.width,
+            atlas.height,
+            atlas.pixels.as_ptr() as _,
+        );
+        if texture.is_null() {
+            return None;
+        }
+
+        Some(Self { data, texture })
+    }
+
+    pub unsafe fn release(&mut self, device: *mut SDL_GPUDevice) {
+        if !self.texture.is_null() {
+            SDL_ReleaseGPUTexture(device, self.texture);
+            self.texture = null_mut();
+        }
+    }
+
+    pub fn texture(&self) -> *mut SDL_GPUTexture {
+        self.texture
+    }
+
+    pub fn data(&self) -> &FontData {
+        &self.data
+    }
+
+    pub fn line_height(&self, style: &TextStyle) -> f32 {
+        self.data.line_height * style.scale
+    }
+
+    /// The width and height `push_text` would cover.
+    pub fn measure(&self, text: &str, style: &TextStyle) -> (f32, f32) {
+        let lines = self.wrap(text, style);
+        let width = lines
+            .iter()
+            .map(|line| self.data.line_width(line) * style.scale)
+            .fold(0.0, f32::max);
+        (width, lines.len() as f32 * self.line_height(style))
+    }
+
+    /// Lays out `text` with its top left at `x`, `y` and pushes a sprite for each visible glyph.
+    /// Returns how many were pushed.
+    pub fn push_text(
+        &self,
+        batch: &mut SpriteBatch,
+        text: &str,
+        x: f32,
+        y: f32,
+        style: &TextStyle,
+    ) -> usize {
+        let scale = style.scale;
+        let lines = self.wrap(text, style);
+        let block_width = style.max_width.unwrap_or_else(|| {
+            lines
+                .iter()
+                .map(|line| self.data.line_width(line) * scale)
+                .fold(0.0, f32::max)
+        });
+
+        let mut pushed = 0;
+        for (i, line) in lines.iter().enumerate() {
+            let line_width = self.data.line_width(line) * scale;
+            let mut pen_x = x + style.align.offset(block_width, line_width);
+            let pen_y = y + i as f32 * self.line_height(style);
+
+            let mut previous = None;
+            for c in line.chars() {
+                if let Some(previous) = previous {
+                    pen_x += self.data.kerning(previous, c) * scale;
+                }
+                previous = Some(c);
+
+                let Some(glyph) = self.data.glyph(c) else {
+                    continue;
+                };
+                if glyph.width > 0.0 && glyph.height > 0.0 {
+                    batch.push(GPUSprite {
+                        x: pen_x + glyph.x_offset * scale,
+                        y: pen_y + glyph.y_offset * scale,
+                        w: glyph.width * scale,
+                        h: glyph.height * scale,
+                        tex_u: glyph.x / self.data.atlas_width,
+                        tex_v: glyph.y / self.data.atlas_height,
+                        tex_w: glyph.width / self.data.atlas_width,
+                        tex_h: glyph.height / self.data.atlas_height,
+                        r: style.color.r,
+                        g: style.color.g,
+                        b: style.color.b,
+                        a: style.color.a,
+                        ..Default::default()
+                    });
+                    pushed += 1;
+                }
+                pen_x += glyph.x_advance * scale;
+            }
+        }
+
+        pushed
+    }
+
+    fn wrap<'a>(&self, text: &'a str, style: &TextStyle) -> Vec<&'a str> {
+        let max_width = style.max_width.map(|width| width / style.scale);
+        self.data.wrap(text, max_width)
+    }
+}
+
+/// Splits a BMFont text line into `key=value` pairs; the tag comes first with an empty value.
+/// Values may be quoted to include spaces.
+fn tokenize(line: &str) -> Vec<(&str, &str)> {
+    let mut tokens = Vec::new();
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        let key_end = rest.find(['=', ' ']).unwrap_or(rest.len());
+        let key = &rest[..key_end];
+        rest = &rest[key_end..];
+
+        let mut value = "";
+        if let Some(after_equals) = rest.strip_prefix('=') {
+            if let Some(quoted) = after_equals.strip_prefix('"') {
+                let value_end = quoted.find('"').unwrap_or(quoted.len());
+                value = &quoted[..value_end];
+                rest = quoted.get(value_end + 1..).unwrap_or("");
+            } else {
+                let value_end = after_equals.find(' ').unwrap_or(after_equals.len());
+                value = &after_equals[..value_end];
+                rest = &after_equals[value_end..];
+            }
+        }
+
+        tokens.push((key, value));
+        rest = rest.trim_start();
+    }
+    tokens
+}
+
+/// The words of a line and where each starts, split on spaces.
+fn word_spans(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split(' ')
+        .scan(0, |start, word| {
+            let span = (*start, word);
+            *start += word.len() + 1;
+            Some(span)
+        })
+        .filter(|(_, word)| !word.is_empty())
+}
+
+fn invalid_data(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Little-endian reads from a binary BMFont, failing at the end of the data.
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn take(&mut self, count: usize) -> std::io::Result<&'a [u8]> {
+        if count > self.bytes.len() {
+            return Err(invalid_data("binary font ended early".to_string()));
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> std::io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> std::io::Result<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn i16(&mut self) -> std::io::Result<i16> {
+        Ok(i16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> std::io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FONT: &str = r#"info face="Test" size=8 bold=0 italic=0 charset="" unicode=1
+common lineHeight=10 base=8 scaleW=64 scaleH=32 pages=1 packed=0
+page id=0 file="test font.bmp"
+chars count=4
+char id=32 x=0  y=0 width=0 height=0 xoffset=0 yoffset=0 xadvance=3 page=0 chnl=15
+char id=63 x=0  y=0 width=4 height=7 xoffset=0 yoffset=1 xadvance=5 page=0 chnl=15
+char id=65 x=5  y=0 width=5 height=7 xoffset=0 yoffset=1 xadvance=6 page=0 chnl=15
+char id=86 x=11 y=0 width=5 height=7 xoffset=0 yoffset=1 xadvance=6 page=0 chnl=15
+kernings count=1
+kerning first=65 second=86 amount=-2
+"#;
+
+    fn test_font() -> FontData {
+        FontData::parse(TEST_FONT.as_bytes()).unwrap()
+    }
+
+    /// The same font as TEST_FONT in version 3 of the binary format.
+    fn test_font_binary() -> Vec<u8> {
+        fn block(bytes: &mut Vec<u8>, block_type: u8, data: &[u8]) {
+            bytes.push(block_type);
+            bytes.extend((data.len() as u32).to_le_bytes());
+            bytes.extend(data);
+        }
+
+        let mut bytes = b"BMF\x03".to_vec();
+
+        // info: font size, bit field, charset, stretch, aa, padding, spacing, outline, name
+        let mut info = vec![8, 0, 2, 0, 0, 100, 0, 1, 0, 0, 0, 0, 1, 1, 0];
+        info.extend(b"Test\0");
+        block(&mut bytes, 1, &info);
+
+        let mut common = Vec::new();
+        for value in [10u16, 8, 64, 32, 1] {
+            common.extend(value.to_le_bytes());
+        }
+        // bit field and channels
+        common.extend([0, 0, 4, 4, 4]);
+        block(&mut bytes, 2, &common);
+
+        block(&mut bytes, 3, b"test font.bmp\0");
+
+        let mut chars = Vec::new();
+        for (id, x, width, height, y_offset, x_advance) in [
+            (32u32, 0u16, 0u16, 0u16, 0i16, 3i16),
+            (63, 0, 4, 7, 1, 5),
+            (65, 5, 5, 7, 1, 6),
+            (86, 11, 5, 7, 1, 6),
+        ] {
+            chars.extend(id.to_le_bytes());
+            for value in [x, 0, width, height] {
+                chars.extend(value.to_le_bytes());
+            }
+            for value in [0, y_offset, x_advance] {
+                chars.extend(value.to_le_bytes());
+            }
+            // page and channel
+            chars.extend([0, 15]);
+        }
+        block(&mut bytes, 4, &chars);
+
+        let mut kerning = Vec::new();
+        kerning.extend(65u32.to_le_bytes());
+        kerning.extend(86u32.to_le_bytes());
+        kerning.extend((-2i16).to_le_bytes());
+        block(&mut bytes, 5, &kerning);
+
+        bytes
+    }
+
+    #[test]
+    fn parse_text_font() {
+        let font = test_font();
+        assert_eq!(font.line_height, 10.0);
+        assert_eq!(font.base, 8.0);
+        assert_eq!((font.atlas_width, font.atlas_height), (64.0, 32.0));
+        assert_eq!(font.page_file, "test font.bmp");
+        assert_eq!(font.glyphs.len(), 4);
+        assert_eq!(
+            font.glyphs[&'A'],
+            Glyph {
+                x: 5.0,
+                y: 0.0,
+                width: 5.0,
+                height: 7.0,
+                x_offset: 0.0,
+                y_offset: 1.0,
+                x_advance: 6.0,
+            }
+        );
+        assert_eq!(font.kerning('A', 'V'), -2.0);
+        assert_eq!(font.kerning('V', 'A'), 0.0);
+    }
+
+    #[test]
+    fn parse_binary_font_matches_text() {
+        let binary = FontData::parse(&test_font_binary()).unwrap();
+        assert_eq!(binary, test_font());
+    }
+
+    #[test]
+    fn parse_rejects_bad_fonts() {
+        let two_pages = TEST_FONT.replace("pages=1", "pages=2");
+        assert!(FontData::parse(two_pages.as_bytes()).is_err());
+
+        let no_page = TEST_FONT.replace("page id=0", "page id=1");
+        assert!(FontData::parse(no_page.as_bytes()).is_err());
+
+        let mut version_2 = test_font_binary();
+        version_2[3] = 2;
+        assert!(FontData::parse(&version_2).is_err());
+
+        let binary = test_font_binary();
+        assert!(FontData::parse(&binary[..binary.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn parse_committed_font() {
+        let bytes = std::fs::read(Path::new(FONTS_DIR).join("pixel.fnt")).unwrap();
+        let font = FontData::parse(&bytes).unwrap();
+        assert_eq!(font.page_file, "pixel.bmp");
+        assert!(font.glyph('?').is_some());
+    }
+
+    #[test]
+    fn missing_glyphs_fall_back_to_question_mark() {
+        let font = test_font();
+        assert_eq!(font.glyph('Z'), font.glyph('?'));
+        assert_eq!(font.line_width("Z"), 5.0);
+    }
+
+    #[test]
+    fn line_width_applies_kerning() {
+        let font = test_font();
+        assert_eq!(font.line_width(""), 0.0);
+        assert_eq!(font.line_width("AV"), 10.0);
+        assert_eq!(font.line_width("VA"), 12.0);
+        assert_eq!(font.line_width("A V"), 15.0);
+    }
+
+    #[test]
+    fn wrap_between_words() {
+        let font = test_font();
+        // "AA AA" is 27 wide, "AA AA AA" is 42
+        assert_eq!(font.wrap("AA AA AA", Some(30.0)), ["AA AA", "AA"]);
+        assert_eq!(font.wrap("AA AA AA", Some(42.0)), ["AA AA AA"]);
+        assert_eq!(font.wrap("AA AA AA", None), ["AA AA AA"]);
+    }
+
+    #[test]
+    fn wrap_keeps_long_words_and_newlines() {
+        let font = test_font();
+        assert_eq!(font.wrap("AAAAAA A", Some(10.0)), ["AAAAAA", "A"]);
+        assert_eq!(font.wrap("A\nV A", None), ["A", "V A"]);
+        assert_eq!(font.wrap("A\nV A", Some(6.0)), ["A", "V", "A"]);
+    }
+
+    #[test]
+    fn align_within_block() {
+        assert_eq!(TextAlign::Left.offset(20.0, 10.0), 0.0);
+        assert_eq!(TextAlign::Center.offset(20.0, 10.0), 5.0);
+        assert_eq!(TextAlign::Right.offset(20.0, 10.0), 10.0);
+        // centered lines are rounded to whole units
+        assert_eq!(TextAlign::Center.offset(20.0, 9.0), 6.0);
+        assert_eq!(TextAlign::Right.offset(20.0, 9.0), 11.0);
+    }
+}
@@ -0,0 +1,307 @@
+use sdl3_sys::timer::SDL_GetTicksNS;
+
+const NANOSECONDS_PER_SECOND: u64 = 1_000_000_000;
+
+/// A monotonic source of time in nanoseconds.
+pub trait Clock {
+    fn now_ns(&self) -> u64;
+}
+
+/// The real clock, backed by `SDL_GetTicksNS`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SdlClock;
+
+impl Clock for SdlClock {
+    fn now_ns(&self) -> u64 {
+        unsafe { SDL_GetTicksNS() }
+    }
+}
+
+/// A clock that only moves when told to; for replays and tests.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ManualClock {
+    now_ns: u64,
+}
+
+impl ManualClock {
+    pub fn advance_ns(&mut self, ns: u64) {
+        self.now_ns += ns;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_ns(&self) -> u64 {
+        self.now_ns
+    }
+}
+
+/// Accumulates elapsed time and hands it out in fixed-size steps.
+///
+/// Elapsed time beyond `max_steps_per_frame` steps is dropped rather than carried over,
+/// so a long stall (a breakpoint, a window drag) doesn't cause a spiral of catch-up steps.
+///
+/// For debugging, the simulation can be paused, single-stepped while paused,
+/// or run faster or slower than real time with `set_time_scale`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixedTimestep {
+    step_ns: u64,
+    max_steps_per_frame: u32,
+    accumulated_ns: u64,
+    last_ns: Option<u64>,
+
+    paused: bool,
+    pending_single_steps: u32,
+    time_scale: f32,
+}
+
+impl FixedTimestep {
+    pub const DEFAULT_MAX_STEPS_PER_FRAME: u32 = 5;
+    pub const MIN_TIME_SCALE: f32 = 0.1;
+    pub const MAX_TIME_SCALE: f32 = 4.0;
+
+    /// `steps_per_second` is at least 1.
+    pub fn new(steps_per_second: u32) -> Self {
+        Self {
+            step_ns: NANOSECONDS_PER_SECOND / steps_per_second.max(1) as u64,
+            max_steps_per_frame: Self::DEFAULT_MAX_STEPS_PER_FRAME,
+            accumulated_ns: 0,
+            last_ns: None,
+
+            paused: false,
+            pending_single_steps: 0,
+            time_scale: 1.0,
+        }
+    }
+
+    pub fn with_max_steps_per_frame(mut self, max_steps_per_frame: u32) -> Self {
+        self.max_steps_per_frame = max_steps_per_frame.max(1);
+        self
+    }
+
+    /// Reads the clock and returns how many fixed steps should run this frame.
+    /// The first call only starts the clock and always returns 0.
+    pub fn advance(&mut self, clock: &impl Clock) -> u32 {
+        let now_ns = clock.now_ns();
+        let last_ns = self.last_ns.replace(now_ns).unwrap_or(now_ns);
+
+        if self.paused {
+            // the clock keeps moving, but none of it reaches the accumulator
+            return std::mem::take(&mut self.pending_single_steps);
+        }
+
+        let elapsed_ns = now_ns.saturating_sub(last_ns);
+        self.accumulated_ns += if self.time_scale == 1.0 {
+            elapsed_ns
+        } else {
+            (elapsed_ns as f64 * self.time_scale as f64) as u64
+        };
+
+        let due_steps = self.accumulated_ns / self.step_ns;
+        let steps = due_steps.min(self.max_steps_per_frame as u64);
+        if due_steps > steps {
+            // drop the backlog, but keep the partial step so alpha stays continuous
+            self.accumulated_ns %= self.step_ns;
+        } else {
+            self.accumulated_ns -= steps * self.step_ns;
+        }
+
+        steps as u32
+    }
+
+    /// How far the accumulator is into the next step, in `0.0..1.0`.
+    /// Use it to interpolate between the previous and current simulation states when rendering.
+    pub fn alpha(&self) -> f32 {
+        self.accumulated_ns as f32 / self.step_ns as f32
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.pending_single_steps = 0;
+    }
+
+    pub fn toggle_paused(&mut self) {
+        self.set_paused(!self.paused);
+    }
+
+    /// Queues exactly one fixed step for the next `advance`. Does nothing unless paused.
+    pub fn step_once(&mut self) {
+        if self.paused {
+            self.pending_single_steps += 1;
+        }
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Clamped to `MIN_TIME_SCALE..=MAX_TIME_SCALE`.
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.clamp(Self::MIN_TIME_SCALE, Self::MAX_TIME_SCALE);
+    }
+
+    pub fn step_ns(&self) -> u64 {
+        self.step_ns
+    }
+
+    pub fn step_seconds(&self) -> f32 {
+        self.step_ns as f32 / NANOSECONDS_PER_SECOND as f32
+    }
+
+    pub fn steps_per_second(&self) -> u32 {
+        (NANOSECONDS_PER_SECOND / self.step_ns) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEPS_PER_SECOND: u32 = 60;
+
+    /// A timestep that has been advanced once, so the clock is running.
+    fn started() -> (FixedTimestep, ManualClock) {
+        let clock = ManualClock::default();
+        let mut timestep = FixedTimestep::new(STEPS_PER_SECOND);
+        timestep.advance(&clock);
+        (timestep, clock)
+    }
+
+    #[test]
+    fn first_advance_only_starts_the_clock() {
+        let mut clock = ManualClock::default();
+        clock.advance_ns(10 * NANOSECONDS_PER_SECOND);
+        let mut timestep = FixedTimestep::new(STEPS_PER_SECOND);
+
+        assert_eq!(timestep.advance(&clock), 0);
+        assert_eq!(timestep.alpha(), 0.0);
+    }
+
+    #[test]
+    fn one_step_per_step_length() {
+        let (mut timestep, mut clock) = started();
+
+        for _ in 0..10 {
+            clock.advance_ns(timestep.step_ns());
+            assert_eq!(timestep.advance(&clock), 1);
+        }
+        clock.advance_ns(timestep.step_ns() * 3);
+        assert_eq!(timestep.advance(&clock), 3);
+    }
+
+    #[test]
+    fn backlog_past_the_cap_is_dropped() {
+        let (mut timestep, mut clock) = started();
+
+        clock.advance_ns(timestep.step_ns() * 20 + timestep.step_ns() / 2);
+        assert_eq!(
+            timestep.advance(&clock),
+            FixedTimestep::DEFAULT_MAX_STEPS_PER_FRAME
+        );
+        // only the partial step carries over
+        assert!((timestep.alpha() - 0.5).abs() < 0.01);
+
+        clock.advance_ns(timestep.step_ns() / 2);
+        assert_eq!(timestep.advance(&clock), 1);
+    }
+
+    #[test]
+    fn max_steps_per_frame_is_at_least_one() {
+        let (timestep, mut clock) = started();
+        let mut timestep = timestep.with_max_steps_per_frame(0);
+
+        clock.advance_ns(timestep.step_ns() * 4);
+        assert_eq!(timestep.advance(&clock), 1);
+    }
+
+    #[test]
+    fn alpha_stays_within_a_step() {
+        let (mut timestep, mut clock) = started();
+
+        // an awkward frame length, so the remainder wanders through the step
+        let frame_ns = timestep.step_ns() * 7 / 5 + 12_345;
+        for _ in 0..1000 {
+            clock.advance_ns(frame_ns);
+            timestep.advance(&clock);
+            let alpha = timestep.alpha();
+            assert!((0.0..1.0).contains(&alpha), "alpha out of range: {alpha}");
+        }
+    }
+
+    #[test]
+    fn paused_only_runs_single_steps() {
+        let (mut timestep, mut clock) = started();
+
+        timestep.set_paused(true);
+        clock.advance_ns(timestep.step_ns() * 3);
+        assert_eq!(timestep.advance(&clock), 0);
+
+        timestep.step_once();
+        timestep.step_once();
+        clock.advance_ns(timestep.step_ns() * 3);
+        assert_eq!(timestep.advance(&clock), 2);
+        assert_eq!(timestep.advance(&clock), 0);
+
+        // time spent paused never catches up
+        timestep.set_paused(false);
+        clock.advance_ns(timestep.step_ns());
+        assert_eq!(timestep.advance(&clock), 1);
+    }
+
+    #[test]
+    fn step_once_does_nothing_while_running() {
+        let (mut timestep, clock) = started();
+
+        timestep.step_once();
+        assert_eq!(timestep.advance(&clock), 0);
+    }
+
+    #[test]
+    fn unpausing_drops_queued_single_steps() {
+        let (mut timestep, clock) = started();
+
+        timestep.set_paused(true);
+        timestep.step_once();
+        timestep.toggle_paused();
+        timestep.toggle_paused();
+        assert_eq!(timestep.advance(&clock), 0);
+    }
+
+    #[test]
+    fn time_scale_is_clamped() {
+        let mut timestep = FixedTimestep::new(STEPS_PER_SECOND);
+
+        timestep.set_time_scale(0.0);
+        assert_eq!(timestep.time_scale(), FixedTimestep::MIN_TIME_SCALE);
+        timestep.set_time_scale(100.0);
+        assert_eq!(timestep.time_scale(), FixedTimestep::MAX_TIME_SCALE);
+        timestep.set_time_scale(2.0);
+        assert_eq!(timestep.time_scale(), 2.0);
+    }
+
+    #[test]
+    fn time_scale_speeds_up_steps() {
+        let (mut timestep, mut clock) = started();
+
+        timestep.set_time_scale(2.0);
+        clock.advance_ns(timestep.step_ns());
+        assert_eq!(timestep.advance(&clock), 2);
+
+        timestep.set_time_scale(0.5);
+        clock.advance_ns(timestep.step_ns());
+        assert_eq!(timestep.advance(&clock), 0);
+        clock.advance_ns(timestep.step_ns());
+        assert_eq!(timestep.advance(&clock), 1);
+    }
+
+    #[test]
+    fn zero_steps_per_second_is_one() {
+        let timestep = FixedTimestep::new(0);
+
+        assert_eq!(timestep.steps_per_second(), 1);
+        assert_eq!(timestep.step_ns(), NANOSECONDS_PER_SECOND);
+    }
+}